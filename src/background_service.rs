@@ -3,6 +3,8 @@
 
 use crate::events::event::NotificationMethod;
 use crate::events::event_manager::{EventManager, EventManagerMode};
+use crate::events::invitation::Mailer;
+use crate::miscs::config::Config;
 use crate::miscs::notification::send_notification;
 use crate::miscs::utils::get_path;
 use daemonize::Daemonize;
@@ -40,10 +42,7 @@ pub fn service_main() -> Result<(), Error> {
         }
         Err(e) => {
             eprintln!("Error, {}", e);
-            Err(Error::new(
-                std::io::ErrorKind::Other,
-                "Error, can't daemonize",
-            ))
+            Err(Error::other("Error, can't daemonize"))
         }
     }
 }
@@ -57,6 +56,11 @@ pub fn main_loop() -> Result<(), Error> {
     let data_file_path = get_path();
 
     let event_manager: Arc<Mutex<EventManager>>;
+    // Email reminders go out through the SMTP server of the config, if there is one
+    let mailer = match &data_file_path {
+        Some(dfp) => Config::load(dfp).and_then(|config| Mailer::new(&config)),
+        None => Err("no config".to_string()),
+    };
 
     if let Some(dfp) = &data_file_path {
         event_manager = EventManager::new(
@@ -66,10 +70,7 @@ pub fn main_loop() -> Result<(), Error> {
         );
     } else {
         eprintln!("Can't open Event File");
-        return Err(Error::new(
-            std::io::ErrorKind::Other,
-            "Can't open Event File",
-        ));
+        return Err(Error::other("Can't open Event File"));
     }
 
     // event_manager.lock().unwrap().read_events_from_file();
@@ -91,10 +92,29 @@ pub fn main_loop() -> Result<(), Error> {
                 if notification.1 && !event.notification_settings[notification.0].has_notified {
                     match event.notification_settings[notification.0].method {
                         NotificationMethod::Push => {
-                            send_notification(&event.title, &event.description)
+                            send_notification(&event.title, &event.description, event.priority)
+                        }
+                        // Reminders that can't be sent otherwise are shown like push ones
+                        NotificationMethod::Email => {
+                            if let Err(e) = mailer
+                                .as_ref()
+                                .map_err(String::clone)
+                                .and_then(|mailer| mailer.remind(event))
+                            {
+                                eprintln!(
+                                    "warning: can't email the reminder for {}, showing it instead: {}",
+                                    event.title, e
+                                );
+                                send_notification(&event.title, &event.description, event.priority)
+                            }
+                        }
+                        NotificationMethod::Sms => {
+                            eprintln!(
+                                "warning: SMS reminders aren't supported, showing the one for {} instead",
+                                event.title
+                            );
+                            send_notification(&event.title, &event.description, event.priority)
                         }
-                        NotificationMethod::Email => todo!(),
-                        NotificationMethod::Sms => todo!(),
                     }
                     event.notification_settings[notification.0].has_notified = true;
                     has_to_save = true;
@@ -111,7 +131,9 @@ pub fn main_loop() -> Result<(), Error> {
         }
         if has_to_save {
             println!("Saving events...");
-            event_manager.lock().unwrap().save_events();
+            if let Err(e) = event_manager.lock().unwrap().save_events() {
+                eprintln!("{}", e);
+            }
         }
         let loop_duration_ns =
             chrono::Local::now().timestamp_nanos_opt().unwrap() - loop_timestamp_ns;
@@ -0,0 +1,396 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::miscs::arg_parsing::did_you_mean;
+use crate::miscs::render::{color_code, COLOR_NAMES};
+
+use super::event::{Notification, Transparency};
+
+pub static CATEGORIES: Mutex<Vec<Category>> = Mutex::new(vec![]);
+
+const DEFAULT_CATEGORIES: [&str; 7] = [
+    "Work",
+    "Personal",
+    "Family",
+    "Health",
+    "Education",
+    "Entertainment",
+    "Other",
+];
+
+// A category and the defaults of new events in it. Subcategories are written as paths like
+// "Work/Project-X" and inherit every property they don't set from their parent.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Category {
+    pub name: String, // Full path, e.g. "Work/Project-X"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>, // Colour name, see render::COLOR_NAMES
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>, // Shown before the name, e.g. an emoji
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>, // Length of new events, e.g. "30m"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notification_settings: Vec<Notification>, // Reminders of new events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<Transparency>, // Whether its events block their time
+}
+
+impl Category {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.name.matches('/').count()
+    }
+
+    // Last part of the path, "Project-X" for "Work/Project-X"
+    pub fn leaf(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+}
+
+// Trims the parts of a path like " Work / Project-X " and rejects empty ones
+pub fn normalize_name(name: &str) -> Result<String, String> {
+    let parts: Vec<&str> = name.split('/').map(str::trim).collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(format!(
+            "'{}' is not a category name, write subcategories like Work/Project-X",
+            name
+        ));
+    }
+    Ok(parts.join("/"))
+}
+
+pub fn check_color(color: &str) -> Result<(), String> {
+    match color_code(color) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "unknown colour '{}', expected one of {}",
+            color,
+            COLOR_NAMES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+// The path and each of its ancestors, "Work/Project-X" first, then "Work"
+fn lineage(name: &str) -> impl Iterator<Item = &str> {
+    let mut current = Some(name);
+    std::iter::from_fn(move || {
+        let name = current?;
+        current = name.rsplit_once('/').map(|(parent, _)| parent);
+        Some(name)
+    })
+}
+
+// Whether `name` is `category` itself or one of its subcategories
+pub fn is_within(name: &str, category: &str) -> bool {
+    name == category
+        || name
+            .strip_prefix(category)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+// Moves `name` from below `from` to below `to`, None if it isn't within `from`
+pub fn moved(name: &str, from: &str, to: &str) -> Option<String> {
+    is_within(name, from).then(|| format!("{}{}", to, &name[from.len()..]))
+}
+
+pub fn category_names() -> Vec<String> {
+    CATEGORIES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|category| category.name.clone())
+        .collect()
+}
+
+// A property of the category, or of its closest ancestor that sets it
+pub fn inherited<T>(name: &str, property: impl Fn(&Category) -> Option<T>) -> Option<T> {
+    let categories = CATEGORIES.lock().unwrap();
+    lineage(name).find_map(|name| {
+        categories
+            .iter()
+            .find(|category| category.name == name)
+            .and_then(&property)
+    })
+}
+
+// Length of new events in these categories, from the first one that has one
+pub fn category_duration(categories: &[String]) -> Option<String> {
+    categories
+        .iter()
+        .find_map(|category| inherited(category, |known| known.duration.clone()))
+}
+
+// Reminders of new events in these categories, from the first one that has them
+pub fn category_reminders(categories: &[String]) -> Vec<Notification> {
+    categories
+        .iter()
+        .find_map(|category| {
+            inherited(category, |known| {
+                (!known.notification_settings.is_empty())
+                    .then(|| known.notification_settings.clone())
+            })
+        })
+        .unwrap_or_default()
+}
+
+// Whether events in these categories are busy or free, from the first one that says so
+pub fn category_transparency(categories: &[String]) -> Option<Transparency> {
+    categories
+        .iter()
+        .find_map(|category| inherited(category, |known| known.transparency))
+}
+
+pub fn find_category(name: &str) -> Result<Category, String> {
+    let categories = CATEGORIES.lock().unwrap();
+    if let Some(category) = categories.iter().find(|category| category.name == name) {
+        return Ok(category.clone());
+    }
+    let names: Vec<&str> = categories
+        .iter()
+        .map(|category| category.name.as_str())
+        .collect();
+    Err(match did_you_mean(name, &names) {
+        Some(suggestion) => format!("no category '{}', did you mean '{}'?", name, suggestion),
+        None => format!("no category '{}'", name),
+    })
+}
+
+// Adds a category and any of its parents that don't exist yet
+pub fn add_category(category: Category) -> Result<(), String> {
+    let mut categories = CATEGORIES.lock().unwrap();
+    if categories.iter().any(|known| known.name == category.name) {
+        return Err(format!("category '{}' already exists", category.name));
+    }
+    insert_with_parents(&mut categories, category);
+    Ok(())
+}
+
+fn insert_with_parents(categories: &mut Vec<Category>, category: Category) {
+    let parents: Vec<String> = lineage(&category.name)
+        .skip(1)
+        .filter(|parent| !categories.iter().any(|known| known.name == *parent))
+        .map(str::to_string)
+        .collect();
+    for parent in parents.into_iter().rev() {
+        categories.push(Category::new(&parent));
+    }
+    categories.push(category);
+}
+
+pub fn update_category(category: Category) -> Result<(), String> {
+    let mut categories = CATEGORIES.lock().unwrap();
+    match categories
+        .iter_mut()
+        .find(|known| known.name == category.name)
+    {
+        Some(known) => {
+            *known = category;
+            Ok(())
+        }
+        None => Err(format!("no category '{}'", category.name)),
+    }
+}
+
+// Renames a category and its subcategories. With `merge` the target may already exist, its
+// own properties are kept then.
+pub fn rename_category(from: &str, to: &str, merge: bool) -> Result<(), String> {
+    find_category(from)?;
+    if is_within(to, from) {
+        return Err(format!("'{}' can't be moved into itself", from));
+    }
+    let mut categories = CATEGORIES.lock().unwrap();
+    let exists = categories.iter().any(|category| category.name == to);
+    match (exists, merge) {
+        (true, false) => {
+            return Err(format!(
+                "category '{}' already exists, use `category merge {} {}`",
+                to, from, to
+            ))
+        }
+        (false, true) => return Err(format!("no category '{}' to merge into", to)),
+        _ => {}
+    }
+    let (moving, mut kept): (Vec<Category>, Vec<Category>) = categories
+        .drain(..)
+        .partition(|category| is_within(&category.name, from));
+    for mut category in moving {
+        category.name = moved(&category.name, from, to).unwrap();
+        if !kept.iter().any(|known| known.name == category.name) {
+            insert_with_parents(&mut kept, category);
+        }
+    }
+    *categories = kept;
+    Ok(())
+}
+
+// Removes a category with its subcategories
+pub fn remove_category(name: &str) -> Result<(), String> {
+    find_category(name)?;
+    CATEGORIES
+        .lock()
+        .unwrap()
+        .retain(|category| !is_within(&category.name, name));
+    Ok(())
+}
+
+// Categories of an event after moving `from` to `to`, or dropping it without `to`. None if
+// the event isn't in `from`.
+pub fn recategorized(categories: &[String], from: &str, to: Option<&str>) -> Option<Vec<String>> {
+    if !categories.iter().any(|category| is_within(category, from)) {
+        return None;
+    }
+    let mut result: Vec<String> = vec![];
+    for category in categories {
+        let category = match to {
+            _ if !is_within(category, from) => Some(category.clone()),
+            Some(to) => moved(category, from, to),
+            None => None,
+        };
+        if let Some(category) = category.filter(|category| !result.contains(category)) {
+            result.push(category);
+        }
+    }
+    Some(result)
+}
+
+// Reads categories.json, or the plain list of names in categories.txt of older versions
+pub fn load_categories(data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join("categories.json");
+    let legacy_path = data_dir.join("categories.txt");
+    let loaded: Vec<Category> = if path.exists() {
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))?
+    } else if legacy_path.exists() {
+        let data = fs::read_to_string(&legacy_path)
+            .map_err(|e| format!("{}: {}", legacy_path.display(), e))?;
+        data.lines()
+            .filter_map(|line| normalize_name(line).ok())
+            .map(|name| Category::new(&name))
+            .collect()
+    } else {
+        DEFAULT_CATEGORIES
+            .iter()
+            .map(|name| Category::new(name))
+            .collect()
+    };
+
+    let mut categories = CATEGORIES.lock().unwrap();
+    categories.clear();
+    for category in loaded {
+        if !categories.iter().any(|known| known.name == category.name) {
+            insert_with_parents(&mut categories, category);
+        }
+    }
+    Ok(())
+}
+
+pub fn save_categories(data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join("categories.json");
+    let json_string =
+        serde_json::to_string_pretty(&*CATEGORIES.lock().unwrap()).map_err(|e| e.to_string())?;
+    fs::write(&path, json_string).map_err(|e| format!("{}: {}", path.display(), e))?;
+    // categories.json replaces the list of names
+    let legacy_path = data_dir.join("categories.txt");
+    if legacy_path.exists() {
+        fs::remove_file(&legacy_path).map_err(|e| format!("{}: {}", legacy_path.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(categories: &[&str]) -> Vec<String> {
+        categories.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_paths() {
+        assert_eq!(
+            normalize_name(" Work / Project-X ").unwrap(),
+            "Work/Project-X"
+        );
+        assert!(normalize_name("Work//X").is_err());
+        assert!(normalize_name("").is_err());
+        let category = Category::new("Work/Project-X/Design");
+        assert_eq!((category.depth(), category.leaf()), (2, "Design"));
+        assert!(is_within("Work/Project-X", "Work"));
+        assert!(is_within("Work", "Work"));
+        assert!(!is_within("Workshop", "Work"));
+        assert_eq!(moved("Work/X", "Work", "Job").as_deref(), Some("Job/X"));
+        assert_eq!(moved("Home", "Work", "Job"), None);
+        assert!(check_color("bright-blue").is_ok());
+        assert!(check_color("mauve")
+            .unwrap_err()
+            .starts_with("unknown colour 'mauve'"));
+    }
+
+    #[test]
+    fn moves_and_drops_event_categories() {
+        let categories = names(&["Work/X", "Home", "Job"]);
+        assert_eq!(
+            recategorized(&categories, "Work", Some("Job")),
+            Some(names(&["Job/X", "Home", "Job"]))
+        );
+        // Merging into a category the event already has keeps it once
+        assert_eq!(
+            recategorized(&categories, "Work/X", Some("Job")),
+            Some(names(&["Job", "Home"]))
+        );
+        assert_eq!(
+            recategorized(&categories, "Home", None),
+            Some(names(&["Work/X", "Job"]))
+        );
+        assert_eq!(recategorized(&categories, "Health", None), None);
+    }
+
+    // Categories are global, so this test only touches names no other test uses
+    #[test]
+    fn renames_merges_and_inherits() {
+        let category = |name: &str| find_category(name).unwrap();
+        add_category(Category {
+            color: Some("red".to_string()),
+            duration: Some("45m".to_string()),
+            ..Category::new("Rename-Test/A/B")
+        })
+        .unwrap();
+        // Parents are added on the way
+        assert_eq!(category("Rename-Test/A").color, None);
+        assert!(add_category(Category::new("Rename-Test/A")).is_err());
+        assert_eq!(
+            inherited("Rename-Test/A/B/C", |known| known.color.clone()).as_deref(),
+            Some("red")
+        );
+        assert_eq!(
+            category_duration(&names(&["Rename-Test/A/B"])).as_deref(),
+            Some("45m")
+        );
+
+        add_category(Category::new("Rename-Test/Z")).unwrap();
+        assert_eq!(
+            rename_category("Rename-Test/A", "Rename-Test/Z", false).unwrap_err(),
+            "category 'Rename-Test/Z' already exists, use `category merge Rename-Test/A Rename-Test/Z`"
+        );
+        assert!(rename_category("Rename-Test/A", "Rename-Test/A/B", false).is_err());
+        rename_category("Rename-Test/A", "Rename-Test/Z", true).unwrap();
+        assert!(find_category("Rename-Test/A").is_err());
+        assert_eq!(category("Rename-Test/Z/B").duration.as_deref(), Some("45m"));
+
+        remove_category("Rename-Test").unwrap();
+        assert!(find_category("Rename-Test/Z/B").is_err());
+    }
+}
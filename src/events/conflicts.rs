@@ -0,0 +1,430 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Weekday};
+
+use super::category::category_transparency;
+use super::event::{Event, Transparency};
+use crate::miscs::natural::{parse_hours, parse_weekday_set};
+use crate::miscs::utils::local_from_naive;
+
+// How far ahead a repeating event is checked for conflicts when it is added or edited
+const HORIZON_DAYS: i64 = 365;
+
+// One occurrence of an event, `index` being the event's position in the event list
+#[derive(Debug, Clone, Copy)]
+pub struct Booking {
+    pub index: usize,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+// Two busy occurrences that overlap, the earlier one first
+#[derive(Debug, Clone, Copy)]
+pub struct Conflict {
+    pub first: Booking,
+    pub second: Booking,
+}
+
+impl Conflict {
+    pub fn start(&self) -> DateTime<Local> {
+        self.first.start.max(self.second.start)
+    }
+
+    pub fn end(&self) -> DateTime<Local> {
+        self.first.end.min(self.second.end)
+    }
+
+    pub fn overlap(&self) -> Duration {
+        self.end() - self.start()
+    }
+}
+
+// The event's own setting, else its categories'. All-day events like holidays or birthdays
+// are free unless one of them says otherwise, everything else is busy.
+pub fn transparency(event: &Event) -> Transparency {
+    event
+        .transparency
+        .or_else(|| category_transparency(&event.categories))
+        .unwrap_or(match event.is_all_day {
+            true => Transparency::Free,
+            false => Transparency::Busy,
+        })
+}
+
+fn is_busy(event: &Event) -> bool {
+    transparency(event) == Transparency::Busy
+}
+
+// Occurrences of the busy events `keep` accepts overlapping [from, to) sorted by start, events
+// taking no time can't overlap anything
+fn bookings(
+    events: &[Event],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    keep: impl Fn(usize, &Event) -> bool,
+) -> Vec<Booking> {
+    let mut bookings: Vec<Booking> = events
+        .iter()
+        .enumerate()
+        .filter(|(index, event)| is_busy(event) && keep(*index, event))
+        .flat_map(|(index, event)| {
+            event
+                .occurrences_between(from, to)
+                .into_iter()
+                .map(move |(start, end)| Booking { index, start, end })
+        })
+        .filter(|booking| booking.end > booking.start)
+        .collect();
+    bookings.sort_by_key(|booking| (booking.start, booking.index));
+    bookings
+}
+
+// Every overlap of two busy events in [from, to), in order of time. Occurrences of the same
+// repeating event don't conflict with each other.
+pub fn conflicts_between(
+    events: &[Event],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Vec<Conflict> {
+    let bookings = bookings(events, from, to, |_, _| true);
+    let mut conflicts = vec![];
+    for (i, first) in bookings.iter().enumerate() {
+        // Sorted by start, so only the following bookings starting before this one ends overlap
+        for second in bookings[i + 1..]
+            .iter()
+            .take_while(|second| second.start < first.end)
+        {
+            if second.index != first.index {
+                conflicts.push(Conflict {
+                    first: *first,
+                    second: *second,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+// Overlaps of `event` with the other events, `skip` being its own index when it is edited.
+// A new event is taken to be at the end of the list. Repeating events are checked from now
+// on for a year.
+pub fn conflicts_with(events: &[Event], event: &Event, skip: Option<usize>) -> Vec<Conflict> {
+    if !is_busy(event) {
+        return vec![];
+    }
+    let (from, to) = if event.is_recurring && event.recurrence.is_some() {
+        let from = event.start_time.max(Local::now());
+        (from, from + Duration::days(HORIZON_DAYS))
+    } else {
+        (event.start_time, event.end_time)
+    };
+    let index = skip.unwrap_or(events.len());
+    let others = bookings(events, from, to, |other, _| Some(other) != skip);
+    let mut conflicts = vec![];
+    for (start, end) in event.occurrences_between(from, to) {
+        let own = Booking { index, start, end };
+        for other in others
+            .iter()
+            .filter(|other| other.start < own.end && own.start < other.end)
+        {
+            let (first, second) = match other.start < own.start {
+                true => (*other, own),
+                false => (own, *other),
+            };
+            conflicts.push(Conflict { first, second });
+        }
+    }
+    conflicts
+}
+
+// The part of the day and the days of the week free slots are looked for in
+#[derive(Debug, Clone)]
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+}
+
+impl WorkingHours {
+    // Hours like "9-17" or "08:30-12:00" and days like "mon-fri" or "mon,wed,fri"
+    pub fn parse(hours: &str, days: &str) -> Result<Self, String> {
+        let (start, end) = parse_hours(hours).ok_or_else(|| {
+            format!(
+                "cannot parse '{}' as working hours, expected e.g. 9-17 or 08:30-12:00",
+                hours
+            )
+        })?;
+        if end <= start {
+            return Err(format!(
+                "working hours '{}' must end after they start on the same day",
+                hours
+            ));
+        }
+        let days = parse_weekday_set(&days.trim().to_lowercase()).ok_or_else(|| {
+            format!(
+                "cannot parse '{}' as days, expected e.g. mon-fri or mon,wed,fri",
+                days
+            )
+        })?;
+        Ok(Self { start, end, days })
+    }
+}
+
+// Gaps of at least `length` between the busy events `blocks` accepts, within the working
+// hours of each day in [from, to)
+pub fn free_slots(
+    events: &[Event],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    length: Duration,
+    hours: &WorkingHours,
+    blocks: impl Fn(&Event) -> bool,
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let mut slots = vec![];
+    if to <= from {
+        return slots;
+    }
+    let busy = bookings(events, from, to, |_, event| blocks(event));
+    let mut day = from.date_naive();
+    while day <= to.date_naive() {
+        let window = (
+            local_from_naive(day.and_time(hours.start)),
+            local_from_naive(day.and_time(hours.end)),
+        );
+        // Days outside the working days, and hours skipped by a clock change, have no slots
+        if let (true, Ok(start), Ok(end)) =
+            (hours.days.contains(&day.weekday()), window.0, window.1)
+        {
+            let (start, end) = (start.max(from), end.min(to));
+            let mut cursor = start;
+            for booking in busy
+                .iter()
+                .filter(|booking| booking.end > start && booking.start < end)
+            {
+                if booking.start - cursor >= length {
+                    slots.push((cursor, booking.start));
+                }
+                cursor = cursor.max(booking.end);
+            }
+            if end - cursor >= length {
+                slots.push((cursor, end));
+            }
+        }
+        day = day.succ_opt().unwrap();
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event::{Recurrence, RecurrenceFrequency};
+    use crate::miscs::utils::datetime_from_str;
+    use chrono::Timelike;
+
+    fn at(text: &str) -> DateTime<Local> {
+        datetime_from_str(text, None).unwrap()
+    }
+
+    fn event(title: &str, start: &str, end: &str) -> Event {
+        Event {
+            title: title.to_string(),
+            start_time: at(start),
+            end_time: at(end),
+            ..Default::default()
+        }
+    }
+
+    fn daily(title: &str, start: &str, end: &str) -> Event {
+        let mut event = event(title, start, end);
+        event.is_recurring = true;
+        event.recurrence = Some(Recurrence {
+            frequency: RecurrenceFrequency::Daily,
+            interval: 1,
+            start_date: event.start_time,
+            end_date: None,
+            minute: Some(event.start_time.minute()),
+            hour: Some(event.start_time.hour()),
+            day: None,
+            week_day: None,
+            week_days: vec![],
+            month: None,
+            year: None,
+        });
+        event
+    }
+
+    // The indices and overlap of each conflict in the week of 2026-10-05
+    fn conflicts(events: &[Event]) -> Vec<(usize, usize, i64)> {
+        conflicts_between(events, at("2026-10-05 00:00"), at("2026-10-12 00:00"))
+            .iter()
+            .map(|c| (c.first.index, c.second.index, c.overlap().num_minutes()))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_events_conflict() {
+        let events = [
+            event("Review", "2026-10-06 10:30", "2026-10-06 12:00"),
+            event("Meeting", "2026-10-06 10:00", "2026-10-06 11:00"),
+            event("Lunch", "2026-10-06 12:00", "2026-10-06 13:00"),
+        ];
+        // The earlier one first, touching events don't conflict
+        assert_eq!(conflicts(&events), [(1, 0, 30)]);
+        let conflict =
+            conflicts_between(&events, at("2026-10-05 00:00"), at("2026-10-12 00:00"))[0];
+        assert_eq!(conflict.start(), at("2026-10-06 10:30"));
+        assert_eq!(conflict.end(), at("2026-10-06 11:00"));
+        // Outside the range nothing is checked
+        assert!(
+            conflicts_between(&events, at("2026-10-07 00:00"), at("2026-10-08 00:00")).is_empty()
+        );
+    }
+
+    #[test]
+    fn free_and_empty_events_dont_conflict() {
+        let mut free = event("Focus time", "2026-10-06 09:00", "2026-10-06 17:00");
+        free.transparency = Some(Transparency::Free);
+        let mut holiday = event("Holiday", "2026-10-06 00:00", "2026-10-07 00:00");
+        holiday.is_all_day = true;
+        let events = [
+            free,
+            holiday,
+            event("Reminder", "2026-10-06 10:00", "2026-10-06 10:00"),
+            event("Meeting", "2026-10-06 09:30", "2026-10-06 11:00"),
+        ];
+        assert!(conflicts(&events).is_empty());
+        // An all-day event marked busy blocks the day
+        let mut busy = events[1].clone();
+        busy.transparency = Some(Transparency::Busy);
+        assert_eq!(conflicts(&[busy, events[3].clone()]), [(0, 1, 90)]);
+    }
+
+    #[test]
+    fn each_occurrence_of_a_series_is_checked() {
+        let events = [
+            daily("Standup", "2026-10-01 09:00", "2026-10-01 09:30"),
+            event("Call", "2026-10-07 09:15", "2026-10-07 10:00"),
+            event("Workshop", "2026-10-09 08:00", "2026-10-09 09:10"),
+        ];
+        // Occurrences of the series don't conflict with each other
+        assert_eq!(conflicts(&events), [(0, 1, 15), (2, 0, 10)]);
+        let found = conflicts_between(&events, at("2026-10-05 00:00"), at("2026-10-12 00:00"));
+        assert_eq!(found[0].first.start, at("2026-10-07 09:00"));
+    }
+
+    #[test]
+    fn conflicts_of_a_new_or_edited_event() {
+        let events = [
+            event("Meeting", "2026-10-06 10:00", "2026-10-06 11:00"),
+            event("Lunch", "2026-10-06 12:00", "2026-10-06 13:00"),
+        ];
+        let new = event("Call", "2026-10-06 10:45", "2026-10-06 12:15");
+        let found: Vec<(usize, usize)> = conflicts_with(&events, &new, None)
+            .iter()
+            .map(|c| (c.first.index, c.second.index))
+            .collect();
+        assert_eq!(found, [(0, 2), (2, 1)]);
+        // An edited event doesn't conflict with its old self
+        let moved = event("Meeting", "2026-10-06 10:30", "2026-10-06 11:30");
+        assert!(conflicts_with(&events, &moved, Some(0)).is_empty());
+    }
+
+    fn slots(
+        events: &[Event],
+        from: &str,
+        to: &str,
+        minutes: i64,
+        blocks: impl Fn(&Event) -> bool,
+    ) -> Vec<(String, String)> {
+        let hours = WorkingHours::parse("9-17", "mon-fri").unwrap();
+        free_slots(
+            events,
+            at(from),
+            at(to),
+            Duration::minutes(minutes),
+            &hours,
+            blocks,
+        )
+        .iter()
+        .map(|(start, end)| {
+            (
+                start.format("%a %H:%M").to_string(),
+                end.format("%a %H:%M").to_string(),
+            )
+        })
+        .collect()
+    }
+
+    fn texts(slots: &[(&str, &str)]) -> Vec<(String, String)> {
+        slots
+            .iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn free_slots_between_events() {
+        let events = [
+            event("Meeting", "2026-10-05 10:00", "2026-10-05 11:00"),
+            event("Review", "2026-10-05 10:30", "2026-10-05 12:00"),
+            event("Lunch", "2026-10-05 13:00", "2026-10-05 14:30"),
+        ];
+        let monday = |minutes| {
+            slots(
+                &events,
+                "2026-10-05 00:00",
+                "2026-10-06 00:00",
+                minutes,
+                |_| true,
+            )
+        };
+        assert_eq!(
+            monday(60),
+            texts(&[
+                ("Mon 09:00", "Mon 10:00"),
+                ("Mon 12:00", "Mon 13:00"),
+                ("Mon 14:30", "Mon 17:00")
+            ])
+        );
+        assert_eq!(monday(120), texts(&[("Mon 14:30", "Mon 17:00")]));
+        // Events left out by the filter don't block anything
+        let lunch_only = slots(
+            &events,
+            "2026-10-05 00:00",
+            "2026-10-06 00:00",
+            60,
+            |event| event.title == "Lunch",
+        );
+        assert_eq!(
+            lunch_only,
+            texts(&[("Mon 09:00", "Mon 13:00"), ("Mon 14:30", "Mon 17:00")])
+        );
+    }
+
+    #[test]
+    fn free_slots_stay_in_working_hours() {
+        let none: [Event; 0] = [];
+        // Weekends are skipped, and the range cuts the working hours
+        assert_eq!(
+            slots(&none, "2026-10-09 15:00", "2026-10-12 10:30", 30, |_| true),
+            texts(&[("Fri 15:00", "Fri 17:00"), ("Mon 09:00", "Mon 10:30")])
+        );
+        assert!(slots(&none, "2026-10-10 00:00", "2026-10-12 00:00", 30, |_| true).is_empty());
+        assert!(slots(&none, "2026-10-06 00:00", "2026-10-05 00:00", 30, |_| true).is_empty());
+        // An event running past the end of the day takes the rest of it
+        let late = [event("Offsite", "2026-10-05 16:00", "2026-10-06 10:00")];
+        assert_eq!(
+            slots(&late, "2026-10-05 00:00", "2026-10-07 00:00", 60, |_| true),
+            texts(&[("Mon 09:00", "Mon 16:00"), ("Tue 10:00", "Tue 17:00")])
+        );
+    }
+
+    #[test]
+    fn parses_working_hours() {
+        let hours = WorkingHours::parse("08:30-12:00", "mon,wed").unwrap();
+        assert_eq!(hours.start, NaiveTime::from_hms_opt(8, 30, 0).unwrap());
+        assert_eq!(hours.days, [Weekday::Mon, Weekday::Wed]);
+        assert!(WorkingHours::parse("17-9", "mon-fri").is_err());
+        assert!(WorkingHours::parse("9to5", "mon-fri").is_err());
+        assert!(WorkingHours::parse("9-17", "someday").is_err());
+    }
+}
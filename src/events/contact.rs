@@ -0,0 +1,478 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::miscs::arg_parsing::did_you_mean;
+
+use super::event::Attendee;
+use super::validation::is_valid_email;
+
+pub static CONTACTS: Mutex<Vec<Contact>> = Mutex::new(vec![]);
+
+// An entry of the address book, attendees of events refer to it by `contact_id`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Contact {
+    pub contact_id: String, // "c1", "c2", ...
+    pub name: String,
+    pub emails: Vec<String>, // The first one is the one events use
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl Contact {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn email(&self) -> &str {
+        self.emails.first().map(String::as_str).unwrap_or("")
+    }
+
+    pub fn has_email(&self, email: &str) -> bool {
+        self.emails
+            .iter()
+            .any(|own| own.eq_ignore_ascii_case(email))
+    }
+
+    // The contact as an attendee of an event, with its first email
+    pub fn attendee(&self) -> Result<Attendee, String> {
+        if self.emails.is_empty() {
+            return Err(format!(
+                "{} ({}) has no email address to invite",
+                self.name, self.contact_id
+            ));
+        }
+        Ok(Attendee {
+            attendee_id: self.contact_id.clone(),
+            name: self.name.clone(),
+            email: self.email().to_string(),
+            ..Default::default()
+        })
+    }
+
+    // Rejects a contact without a name or with a malformed email
+    pub fn check(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("a contact needs a name".to_string());
+        }
+        match self.emails.iter().find(|email| !is_valid_email(email)) {
+            Some(email) => Err(format!("'{}' is not a valid email address", email)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn contact_list() -> Vec<Contact> {
+    CONTACTS.lock().unwrap().clone()
+}
+
+// A contact by ID, name or email ignoring case, or by the start of a single name
+pub fn find_contact(reference: &str) -> Result<Contact, String> {
+    let reference = reference.trim();
+    let contacts = CONTACTS.lock().unwrap();
+    if let Some(contact) = contacts.iter().find(|contact| {
+        contact.contact_id == reference
+            || contact.name.eq_ignore_ascii_case(reference)
+            || contact.has_email(reference)
+    }) {
+        return Ok(contact.clone());
+    }
+    let lower = reference.to_lowercase();
+    let started: Vec<&Contact> = contacts
+        .iter()
+        .filter(|contact| !lower.is_empty() && contact.name.to_lowercase().starts_with(&lower))
+        .collect();
+    match started.as_slice() {
+        [contact] => return Ok((*contact).clone()),
+        [] => {}
+        several => {
+            return Err(format!(
+                "'{}' could be {}",
+                reference,
+                several
+                    .iter()
+                    .map(|contact| format!("{} ({})", contact.name, contact.contact_id))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    }
+    let names: Vec<&str> = contacts
+        .iter()
+        .map(|contact| contact.name.as_str())
+        .collect();
+    Err(match did_you_mean(reference, &names) {
+        Some(suggestion) => format!("no contact '{}', did you mean '{}'?", reference, suggestion),
+        None => format!("no contact '{}'", reference),
+    })
+}
+
+pub fn contact_with_email(email: &str) -> Option<Contact> {
+    CONTACTS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|contact| contact.has_email(email))
+        .cloned()
+}
+
+// An attendee from a contact reference, or someone who isn't in the contacts written as an
+// email or "Name <email>". Emails of contacts are linked to them.
+pub fn resolve_attendee(text: &str) -> Result<Attendee, String> {
+    let text = text.trim();
+    let (name, email) = match text
+        .strip_suffix('>')
+        .and_then(|rest| rest.rsplit_once('<'))
+    {
+        Some((name, email)) => (name.trim(), email.trim()),
+        None if text.contains('@') => ("", text),
+        None => return find_contact(text)?.attendee(),
+    };
+    if !is_valid_email(email) {
+        return Err(format!("'{}' is not a valid email address", email));
+    }
+    if let Some(contact) = contact_with_email(email) {
+        return Ok(Attendee {
+            attendee_id: contact.contact_id.clone(),
+            name: contact.name.clone(),
+            email: email.to_string(),
+            ..Default::default()
+        });
+    }
+    Ok(Attendee {
+        attendee_id: String::new(),
+        name: match name {
+            "" => email.to_string(),
+            name => name.to_string(),
+        },
+        email: email.to_string(),
+        ..Default::default()
+    })
+}
+
+// Adds a contact under the next free ID and returns that ID
+pub fn add_contact(mut contact: Contact) -> Result<String, String> {
+    contact.check()?;
+    let mut contacts = CONTACTS.lock().unwrap();
+    check_emails_unused(&contacts, &contact)?;
+    let next = contacts
+        .iter()
+        .filter_map(|known| known.contact_id.strip_prefix('c')?.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    contact.contact_id = format!("c{}", next);
+    contacts.push(contact);
+    Ok(format!("c{}", next))
+}
+
+// An email belongs to one contact only, so replies and imports find the right one
+fn check_emails_unused(contacts: &[Contact], contact: &Contact) -> Result<(), String> {
+    for email in &contact.emails {
+        if let Some(owner) = contacts
+            .iter()
+            .find(|known| known.contact_id != contact.contact_id && known.has_email(email))
+        {
+            return Err(format!(
+                "{} already belongs to {} ({})",
+                email, owner.name, owner.contact_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn update_contact(contact: Contact) -> Result<(), String> {
+    contact.check()?;
+    let mut contacts = CONTACTS.lock().unwrap();
+    check_emails_unused(&contacts, &contact)?;
+    match contacts
+        .iter_mut()
+        .find(|known| known.contact_id == contact.contact_id)
+    {
+        Some(known) => {
+            *known = contact;
+            Ok(())
+        }
+        None => Err(format!("no contact '{}'", contact.contact_id)),
+    }
+}
+
+pub fn remove_contact(contact_id: &str) -> Result<Contact, String> {
+    let mut contacts = CONTACTS.lock().unwrap();
+    match contacts
+        .iter()
+        .position(|contact| contact.contact_id == contact_id)
+    {
+        Some(index) => Ok(contacts.remove(index)),
+        None => Err(format!("no contact '{}'", contact_id)),
+    }
+}
+
+// Attendees of an event after `contact` was added or changed, or was removed without it.
+// Linked attendees, and unlinked ones with one of its emails, take its name, and its first
+// email unless theirs is still one of the contact's. None if nothing changes.
+pub fn relinked(
+    attendees: &[Attendee],
+    contact_id: &str,
+    contact: Option<&Contact>,
+) -> Option<Vec<Attendee>> {
+    let mut result = attendees.to_vec();
+    for attendee in result
+        .iter_mut()
+        .filter(|attendee| match attendee.contact_id() {
+            Some(id) => id == contact_id,
+            None => contact.is_some_and(|contact| contact.has_email(&attendee.email)),
+        })
+    {
+        match contact {
+            Some(contact) => {
+                attendee.attendee_id = contact.contact_id.clone();
+                attendee.name = contact.name.clone();
+                if !contact.has_email(&attendee.email) && !contact.emails.is_empty() {
+                    attendee.email = contact.email().to_string();
+                }
+            }
+            // The event keeps who was invited, only the link goes
+            None => attendee.attendee_id = String::new(),
+        }
+    }
+    let changed = result.iter().zip(attendees).any(|(new, old)| {
+        new.attendee_id != old.attendee_id || new.name != old.name || new.email != old.email
+    });
+    changed.then_some(result)
+}
+
+// Contacts in the vCard files of address books and phones. Only the name, emails, first phone
+// number and note are read.
+pub fn parse_vcards(text: &str) -> Result<Vec<Contact>, String> {
+    // Long lines are folded onto following lines that start with a space or tab
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut contacts = vec![];
+    let mut current: Option<(Contact, String)> = None; // The contact and its N name
+    for (number, line) in lines.iter().enumerate() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = key.split(';');
+        let property = params.next().unwrap_or("").to_uppercase();
+        // Properties can be grouped like "item1.EMAIL"
+        let property = property.rsplit('.').next().unwrap_or("");
+        let preferred = params.any(|param| param.to_uppercase().contains("PREF"));
+        let value = unescape_vcard(value.trim());
+        match (property, current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("vcard") => {
+                current = Some((Contact::default(), String::new()));
+            }
+            ("BEGIN", Some(_)) if value.eq_ignore_ascii_case("vcard") => {
+                return Err(format!("line {}: vCard inside a vCard", number + 1));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("vcard") => {
+                let (mut contact, structured) = current.take().unwrap();
+                if contact.name.is_empty() {
+                    contact.name = match structured.is_empty() {
+                        true => contact.email().to_string(),
+                        false => structured,
+                    };
+                }
+                if !contact.name.is_empty() {
+                    contacts.push(contact);
+                }
+            }
+            ("FN", Some((contact, _))) => contact.name = value,
+            // Family;Given;Additional;Prefix;Suffix
+            ("N", Some((_, structured))) => {
+                let parts: Vec<&str> = value.split(';').map(str::trim).collect();
+                *structured = [parts.get(1), parts.first()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+            ("EMAIL", Some((contact, _))) if !value.is_empty() => {
+                let email = value.trim_start_matches("mailto:").to_string();
+                if !contact.has_email(&email) {
+                    match preferred {
+                        true => contact.emails.insert(0, email),
+                        false => contact.emails.push(email),
+                    }
+                }
+            }
+            ("TEL", Some((contact, _))) if contact.phone.is_empty() || preferred => {
+                contact.phone = value.trim_start_matches("tel:").to_string();
+            }
+            ("NOTE", Some((contact, _))) => contact.notes = value,
+            _ => {}
+        }
+    }
+    if current.is_some() {
+        return Err("the last vCard has no END:VCARD".to_string());
+    }
+    if contacts.is_empty() {
+        return Err("no vCards with a name or email found".to_string());
+    }
+    Ok(contacts)
+}
+
+fn unescape_vcard(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+pub fn load_contacts(data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join("contacts.json");
+    let loaded: Vec<Contact> = if path.exists() {
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))?
+    } else {
+        vec![]
+    };
+    *CONTACTS.lock().unwrap() = loaded;
+    Ok(())
+}
+
+pub fn save_contacts(data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join("contacts.json");
+    let json_string =
+        serde_json::to_string_pretty(&*CONTACTS.lock().unwrap()).map_err(|e| e.to_string())?;
+    fs::write(&path, json_string).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(id: &str, name: &str, emails: &[&str]) -> Contact {
+        Contact {
+            contact_id: id.to_string(),
+            emails: emails.iter().map(|email| email.to_string()).collect(),
+            ..Contact::new(name)
+        }
+    }
+
+    fn attendee(id: &str, name: &str, email: &str) -> Attendee {
+        Attendee {
+            attendee_id: id.to_string(),
+            name: name.to_string(),
+            email: email.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Contacts are global, so this test only uses names and emails no other test does
+    #[test]
+    fn resolves_attendees_from_contacts() {
+        let ann = contact(
+            "",
+            "Ann Resolve",
+            &["ann@resolve.example", "a@resolve.example"],
+        );
+        let ann_id = add_contact(ann).unwrap();
+        add_contact(contact("", "Anton Resolve", &[])).unwrap();
+        assert_eq!(
+            add_contact(contact("", "Other", &["A@resolve.example"])).unwrap_err(),
+            format!(
+                "A@resolve.example already belongs to Ann Resolve ({})",
+                ann_id
+            )
+        );
+
+        let linked = resolve_attendee("ann resolve").unwrap();
+        assert_eq!(
+            (linked.attendee_id.as_str(), linked.email.as_str()),
+            (ann_id.as_str(), "ann@resolve.example")
+        );
+        // Another email of a contact links to it and stays the one invited
+        let linked = resolve_attendee("Someone <a@resolve.example>").unwrap();
+        assert_eq!(linked.attendee_id, ann_id);
+        assert_eq!(linked.email, "a@resolve.example");
+        let stranger = resolve_attendee("Bob <bob@resolve.example>").unwrap();
+        assert_eq!(
+            (stranger.contact_id(), stranger.name.as_str()),
+            (None, "Bob")
+        );
+
+        assert!(find_contact("an").unwrap_err().starts_with("'an' could be"));
+        assert!(resolve_attendee("Anton Resolve")
+            .unwrap_err()
+            .ends_with("has no email address to invite"));
+        assert!(resolve_attendee("Bob <bob@>").is_err());
+
+        remove_contact(&ann_id).unwrap();
+        assert!(find_contact(&ann_id).is_err());
+    }
+
+    #[test]
+    fn relinks_attendees_when_contacts_change() {
+        let attendees = [
+            attendee("c7", "Ann", "ann@example.org"),
+            attendee("", "ann", "a@example.org"),
+            attendee("", "Bob", "bob@example.org"),
+        ];
+        let ann = contact("c7", "Ann Smith", &["a@example.org", "ann@example.org"]);
+        let result = relinked(&attendees, "c7", Some(&ann)).unwrap();
+        assert!(result[..2]
+            .iter()
+            .all(|attendee| attendee.attendee_id == "c7" && attendee.name == "Ann Smith"));
+        assert_eq!(result[0].email, "ann@example.org");
+        assert_eq!(result[2].contact_id(), None);
+        assert!(relinked(&result, "c7", Some(&ann)).is_none());
+
+        // Removing the contact keeps who was invited
+        let result = relinked(&result, "c7", None).unwrap();
+        assert!(result
+            .iter()
+            .all(|attendee| attendee.contact_id().is_none()));
+        assert_eq!(result[1].email, "a@example.org");
+    }
+
+    #[test]
+    fn reads_vcards() {
+        let text = "BEGIN:VCARD\r\nFN:Ann Smith\r\nEMAIL:ann@example.org\r\n\
+                    EMAIL;TYPE=PREF:ann@work.example\r\nTEL:+49 30 1234\r\n\
+                    NOTE:Line one\\nline two\r\nEND:VCARD\r\n\
+                    BEGIN:VCARD\nN:Doe;John;;;\nitem1.EMAIL:john@exa\n mple.org\nEND:VCARD\n";
+        let contacts = parse_vcards(text).unwrap();
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[0].emails, ["ann@work.example", "ann@example.org"]);
+        assert_eq!(contacts[0].phone, "+49 30 1234");
+        assert_eq!(contacts[0].notes, "Line one\nline two");
+        assert_eq!(contacts[1].name, "John Doe");
+        assert_eq!(contacts[1].email(), "john@example.org");
+
+        assert!(parse_vcards("BEGIN:VCARD\nFN:Ann\n").is_err());
+        assert!(parse_vcards("hello").is_err());
+        assert_eq!(
+            contact("", " ", &[]).check().unwrap_err(),
+            "a contact needs a name"
+        );
+    }
+}
@@ -1,43 +1,23 @@
 use chrono::Datelike;
-use chrono::{DateTime, Duration, Local, Timelike, Weekday};
+use chrono::{
+    DateTime, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday,
+};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::HashMap;
 
+use super::contact::{find_contact, resolve_attendee};
 use crate::miscs::{
-    arg_parsing::Data,
-    utils::{date_from_str, parse_duration, time_from_str},
+    arg_parsing::{did_you_mean, Data},
+    natural::{parse_datetime, parse_reminder, parse_when},
+    render::time_range,
+    utils::{
+        date_from_str, local_from_naive, parse_duration, parse_time_zone, time_from_str, wall_time,
+        zoned_from_naive,
+    },
 };
 
-pub static CATEGORIES: Mutex<Vec<String>> = Mutex::new(vec![]);
-
-pub fn load_categories(path: &PathBuf) {
-    let mut categories = CATEGORIES.lock().unwrap();
-    if !std::path::Path::new(path).exists() {
-        categories.append(&mut vec![
-            "Work".to_string(),
-            "Personal".to_string(),
-            "Family".to_string(),
-            "Health".to_string(),
-            "Education".to_string(),
-            "Entertainment".to_string(),
-            "Other".to_string(),
-        ]);
-    } else {
-        let categories_str = std::fs::read_to_string(path).unwrap();
-        for category in categories_str.lines() {
-            categories.push(category.trim().to_string());
-        }
-    }
-}
-
-pub fn save_categories(path: &PathBuf) {
-    let categories = CATEGORIES.lock().unwrap();
-    let categories_str = categories.join("\n");
-    std::fs::write(path, categories_str).unwrap();
-}
-
-fn parse_weekday(value: &str) -> Option<Weekday> {
+pub fn parse_weekday(value: &str) -> Option<Weekday> {
     match value.to_lowercase().as_str() {
         "mon" | "monday" => Some(Weekday::Mon),
         "tue" | "tuesday" => Some(Weekday::Tue),
@@ -74,21 +54,42 @@ impl std::fmt::Display for NotificationMethod {
     }
 }
 
+// A reminder at a fixed time of day, e.g. the day before at 18:00
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayReminder {
+    pub days_before: u32, // 0 is the day of the event itself
+    pub time: NaiveTime,  // Wall-clock time in the event's time zone
+}
+
+impl std::fmt::Display for DayReminder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let day = match self.days_before {
+            0 => "on the day".to_string(),
+            1 => "the day before".to_string(),
+            days => format!("{} days before", days),
+        };
+        f.pad(&format!("{} at {}", day, self.time.format("%H:%M")))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Notification {
     pub notify_before: i64, // Time in minutes before the event to send the notification
     pub method: NotificationMethod, // Method of notification (e.g., email, SMS, push)
     pub has_notified: bool,
+    // Replaces notify_before with a time of day, mostly for all-day events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_day: Option<DayReminder>,
 }
 impl std::fmt::Display for Notification {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.pad(
-            format!(
+        match self.on_day {
+            Some(on_day) => f.pad(&format!("Notify: {}, Method: {}", on_day, self.method)),
+            None => f.pad(&format!(
                 "Notify Before: {}, Method: {}",
                 self.notify_before, self.method
-            )
-            .as_str(),
-        )
+            )),
+        }
     }
 }
 
@@ -98,29 +99,157 @@ impl Default for Notification {
             notify_before: Default::default(),
             method: NotificationMethod::Push,
             has_notified: false,
+            on_day: None,
         }
     }
 }
 
 impl Notification {
+    // When the reminder for an event starting at `start` goes off
+    pub fn due_time(&self, start: DateTime<Local>, zone: Option<Tz>) -> DateTime<Local> {
+        match self.on_day {
+            Some(on_day) => {
+                let day = wall_time(start, zone).date() - Duration::days(on_day.days_before as i64);
+                zoned_from_naive(day.and_time(on_day.time), zone)
+                    .unwrap_or(start - Duration::days(on_day.days_before as i64))
+            }
+            None => start - Duration::minutes(self.notify_before),
+        }
+    }
+
     pub fn from_data(data: &Data) -> Result<Self, String> {
-        match data {
-            Data::Object(data_object) => {
-                let mut notification = Self::default();
-                if let Some(Data::String(duration_str)) = data_object.get("remind-before") {
-                    notification.notify_before = duration_str.parse::<i64>().unwrap_or(10);
-                }
-                if let Some(Data::String(method_str)) = data_object.get("method") {
-                    notification.method = match method_str.to_lowercase().as_str() {
-                        "email" => NotificationMethod::Email,
-                        "sms" => NotificationMethod::Sms,
-                        "push" => NotificationMethod::Push,
-                        _ => NotificationMethod::Push,
-                    };
+        let fields = data_fields(data, &NOTIFICATION_FIELDS, &[], "notification")?;
+        let mut notification = Self::default();
+        if let Some(value) = fields.get("remind_before") {
+            // minutes, a duration like "1h" or a time of day like "the day before at 18:00"
+            let reminder = match value.as_int() {
+                Some(minutes) => parse_reminder(&minutes.to_string()),
+                None => value
+                    .as_text()
+                    .ok_or_else(|| "'remind-before' must be minutes or text".to_string())
+                    .and_then(|text| parse_reminder(&text)),
+            }?;
+            notification.notify_before = reminder.notify_before;
+            notification.on_day = reminder.on_day;
+        }
+        if let Some(method) = data_text(&fields, "method")? {
+            notification.method = match method.to_lowercase().as_str() {
+                "email" => NotificationMethod::Email,
+                "sms" => NotificationMethod::Sms,
+                "push" => NotificationMethod::Push,
+                _ => {
+                    return Err(format!(
+                        "unknown notification method '{}', expected email, sms or push",
+                        method
+                    ))
                 }
-                Ok(notification)
-            }
-            _ => Err("Data must be an Object".to_string()),
+            };
+        }
+        Ok(notification)
+    }
+}
+
+// How important an event is, urgent ones get critical notifications and an extra reminder
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+pub enum Priority {
+    #[serde(alias = "low")]
+    Low,
+    #[default]
+    #[serde(alias = "normal")]
+    Normal,
+    #[serde(alias = "high")]
+    High,
+    #[serde(alias = "urgent")]
+    Urgent,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(Self::NAMES[*self as usize])
+    }
+}
+
+impl Priority {
+    pub const NAMES: [&str; 4] = ["low", "normal", "high", "urgent"];
+    const ALL: [Priority; 4] = [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+        Priority::Urgent,
+    ];
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim().to_lowercase();
+        match Self::NAMES.iter().position(|name| *name == value) {
+            Some(index) => Ok(Self::ALL[index]),
+            None => Err(match did_you_mean(&value, &Self::NAMES) {
+                Some(name) => format!("unknown priority '{}', did you mean '{}'?", value, name),
+                None => format!(
+                    "unknown priority '{}', expected one of {}",
+                    value,
+                    Self::NAMES.join(", ")
+                ),
+            }),
+        }
+    }
+}
+
+// A priority to filter by, `high` matches that priority alone and `high+` it or higher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityFilter {
+    pub priority: Priority,
+    pub or_higher: bool,
+}
+
+impl PriorityFilter {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let (value, or_higher) = match value.strip_suffix('+') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        Ok(Self {
+            priority: Priority::parse(value)?,
+            or_higher,
+        })
+    }
+
+    pub fn matches(&self, priority: Priority) -> bool {
+        match self.or_higher {
+            true => priority >= self.priority,
+            false => priority == self.priority,
+        }
+    }
+}
+
+// Whether an event blocks its time for others, like TRANSP in iCalendar. Only busy events
+// can conflict with each other.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transparency {
+    #[default]
+    #[serde(alias = "busy")]
+    Busy,
+    #[serde(alias = "free")]
+    Free,
+}
+
+impl std::fmt::Display for Transparency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Transparency::Busy => "busy",
+            Transparency::Free => "free",
+        })
+    }
+}
+
+impl Transparency {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "busy" => Ok(Transparency::Busy),
+            "free" => Ok(Transparency::Free),
+            other => Err(format!("'{}' is neither busy nor free", other)),
         }
     }
 }
@@ -147,6 +276,117 @@ impl RecurrenceFrequency {
     }
 }
 
+// What is expected of an attendee, ROLE in iCalendar
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    #[serde(alias = "chair")]
+    Chair,
+    #[default]
+    #[serde(alias = "required")]
+    Required,
+    #[serde(alias = "optional")]
+    Optional,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(Self::NAMES[*self as usize])
+    }
+}
+
+impl Role {
+    pub const NAMES: [&str; 3] = ["chair", "required", "optional"];
+    const ALL: [Role; 3] = [Role::Chair, Role::Required, Role::Optional];
+    const ICAL: [&str; 3] = ["CHAIR", "REQ-PARTICIPANT", "OPT-PARTICIPANT"];
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim().to_lowercase();
+        match Self::NAMES.iter().position(|name| *name == value) {
+            Some(index) => Ok(Self::ALL[index]),
+            None => Err(format!(
+                "unknown role '{}', expected one of {}",
+                value,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn ical(&self) -> &'static str {
+        Self::ICAL[*self as usize]
+    }
+
+    // NON-PARTICIPANT and roles of other programs count as optional
+    pub fn from_ical(value: &str) -> Self {
+        match Self::ICAL
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+        {
+            Some(index) => Self::ALL[index],
+            None => Role::Optional,
+        }
+    }
+}
+
+// Whether an attendee comes, PARTSTAT in iCalendar
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticipationStatus {
+    #[default]
+    #[serde(alias = "needs-action")]
+    NeedsAction,
+    #[serde(alias = "accepted")]
+    Accepted,
+    #[serde(alias = "declined")]
+    Declined,
+    #[serde(alias = "tentative")]
+    Tentative,
+}
+
+impl std::fmt::Display for ParticipationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(Self::NAMES[*self as usize])
+    }
+}
+
+impl ParticipationStatus {
+    pub const NAMES: [&str; 4] = ["needs-action", "accepted", "declined", "tentative"];
+    const ALL: [ParticipationStatus; 4] = [
+        ParticipationStatus::NeedsAction,
+        ParticipationStatus::Accepted,
+        ParticipationStatus::Declined,
+        ParticipationStatus::Tentative,
+    ];
+
+    // Also the answers of `respond`: accept, decline and tentative
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim().to_lowercase();
+        let value = match value.as_str() {
+            "accept" => "accepted",
+            "decline" => "declined",
+            other => other,
+        };
+        match Self::NAMES.iter().position(|name| *name == value) {
+            Some(index) => Ok(Self::ALL[index]),
+            None => Err(format!(
+                "unknown participation status '{}', expected one of {}",
+                value,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn ical(&self) -> String {
+        self.to_string().to_uppercase()
+    }
+
+    // DELEGATED and the statuses of to-dos count as not answered yet
+    pub fn from_ical(value: &str) -> Self {
+        Self::parse(value).unwrap_or_default()
+    }
+}
+
+// Longest interval a series can have, every 1000 years is as far as dates go anyway
+pub const MAX_INTERVAL: i64 = 1000;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Recurrence {
     pub frequency: RecurrenceFrequency, // Frequency of recurrence (e.g., daily, weekly, monthly)
@@ -161,102 +401,241 @@ pub struct Recurrence {
     pub week_day: Option<Weekday>,
     pub month: Option<u32>,
     pub year: Option<u32>,
+    // Several days of the week for weekly series (e.g. mon-fri), replaces `week_day` if not empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub week_days: Vec<Weekday>,
 }
 
 #[allow(dead_code)]
 impl Recurrence {
     pub fn from_data(data: &Data) -> Result<Self, String> {
-        //data.print(0);
-        match data {
-            Data::Object(_data) => {
-                let mut recurrence = Self {
-                    frequency: RecurrenceFrequency::Daily,
-                    interval: 1,
-                    start_date: Local::now(),
-                    end_date: None,
-
-                    minute: None,
-                    hour: None,
-                    day: None,
-                    week_day: None,
-                    month: None,
-                    year: None,
-                };
-                if let Some(Data::String(frequency)) = _data.get("frequency") {
-                    recurrence.frequency = RecurrenceFrequency::from_str(frequency);
-                }
+        let fields = data_fields(data, &RECURRENCE_FIELDS, &RECURRENCE_ALIASES, "recurrence")?;
+        let mut recurrence = Self {
+            frequency: RecurrenceFrequency::Daily,
+            interval: 1,
+            start_date: Local::now(),
+            end_date: None,
+
+            minute: None,
+            hour: None,
+            day: None,
+            week_day: None,
+            month: None,
+            year: None,
+            week_days: vec![],
+        };
+        if let Some(frequency) = data_text(&fields, "frequency")? {
+            if !["hourly", "daily", "weekly", "monthly", "yearly"]
+                .contains(&frequency.to_lowercase().as_str())
+            {
+                return Err(format!(
+                    "unknown frequency '{}', expected hourly, daily, weekly, monthly or yearly",
+                    frequency
+                ));
+            }
+            recurrence.frequency = RecurrenceFrequency::from_str(&frequency);
+        }
 
-                if let Some(Data::Int(intervall)) = _data.get("intervall") {
-                    recurrence.interval = *intervall;
-                }
+        if let Some(intervall) = data_int(&fields, "intervall")? {
+            recurrence.interval = intervall;
+        }
 
-                if let Some(Data::String(start_time)) = _data.get("start-date") {
-                    let start_time_naive =
-                        date_from_str(start_time).and_time(time_from_str("00:00"));
-                    recurrence.start_date = DateTime::from_naive_utc_and_offset(
-                        start_time_naive,
-                        *Local::now().offset(),
-                    );
-                }
+        if let Some(start_time) = data_text(&fields, "start_time")? {
+            recurrence.start_date =
+                local_from_naive(date_from_str(&start_time)?.and_time(NaiveTime::MIN))?;
+        }
 
-                if let Some(Data::String(end_time)) = _data.get("end-") {
-                    let end_time_naive = date_from_str(end_time).and_time(time_from_str("24:00"));
-                    recurrence.end_date = Some(DateTime::from_naive_utc_and_offset(
-                        end_time_naive,
-                        *Local::now().offset(),
-                    ));
-                }
+        if let Some(end_time) = data_text(&fields, "end_time")? {
+            let last_second = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+            recurrence.end_date = Some(local_from_naive(
+                date_from_str(&end_time)?.and_time(last_second),
+            )?);
+        }
 
-                if let Some(Data::Int(minute)) = _data.get("minute") {
-                    recurrence.minute = Some(*minute as u32);
-                }
-                if let Some(Data::Int(hour)) = _data.get("hour") {
-                    recurrence.hour = Some(*hour as u32);
-                }
-                if let Some(Data::Int(day)) = _data.get("day") {
-                    recurrence.day = Some(*day as u32);
-                }
-                if let Some(Data::String(week_day)) = _data.get("week-day") {
-                    recurrence.week_day = parse_weekday(week_day);
+        // Negative numbers can't be kept, validation checks the rest of the range
+        let number = |key: &str| -> Result<Option<u32>, String> {
+            data_int(&fields, key)?
+                .map(|value| {
+                    u32::try_from(value)
+                        .map_err(|_| format!("'{}' is out of range, got {}", key, value))
+                })
+                .transpose()
+        };
+        recurrence.minute = number("minute")?;
+        recurrence.hour = number("hour")?;
+        recurrence.day = number("day")?;
+        if let Some(week_day) = data_text(&fields, "week_day")? {
+            recurrence.week_day = Some(
+                parse_weekday(&week_day)
+                    .ok_or_else(|| format!("'{}' is not a day of the week", week_day))?,
+            );
+        }
+        recurrence.month = number("month")?;
+        recurrence.year = number("year")?;
+
+        Ok(recurrence)
+    }
+
+    // Start times of the series within [from, to). Fields that are not set (time of day,
+    // weekday, day of month, month) are taken from `template`, usually the event's start.
+    // The series follows the wall clock of `zone` (the local one if None) across DST changes.
+    pub fn occurrences(
+        &self,
+        template: DateTime<Local>,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        zone: Option<Tz>,
+    ) -> Vec<DateTime<Local>> {
+        const MAX_OCCURRENCES: usize = 100_000;
+
+        // A series on several weekdays is one weekly series per day, merged
+        if self.frequency == RecurrenceFrequency::Weekly && !self.week_days.is_empty() {
+            let mut result: Vec<DateTime<Local>> = vec![];
+            for week_day in &self.week_days {
+                let single = Self {
+                    week_day: Some(*week_day),
+                    week_days: vec![],
+                    ..self.clone()
+                };
+                result.extend(single.occurrences(template, from, to, zone));
+            }
+            result.sort();
+            result.dedup();
+            result.truncate(MAX_OCCURRENCES);
+            return result;
+        }
+
+        let interval = self.interval.max(1);
+        let anchor = wall_time(self.start_date.min(template), zone).date();
+        let template = wall_time(template, zone);
+        let time = template
+            .time()
+            .with_hour(self.hour.unwrap_or(template.hour()))
+            .and_then(|time| time.with_minute(self.minute.unwrap_or(template.minute())))
+            .unwrap_or(template.time());
+        let day = self.day.unwrap_or(template.day());
+        let month = self.month.unwrap_or(template.month());
+        let until = match self.end_date {
+            Some(end_date) => end_date.min(to),
+            None => to,
+        };
+
+        let mut result = vec![];
+        let mut push = |naive: NaiveDateTime| -> bool {
+            // returns false once the series has passed the end of the range
+            match zoned_from_naive(naive, zone) {
+                Ok(start) if start >= until => false,
+                Ok(start) => {
+                    if start >= from {
+                        result.push(start);
+                    }
+                    result.len() < MAX_OCCURRENCES
                 }
-                if let Some(Data::Int(month)) = _data.get("month") {
-                    recurrence.month = Some(*month as u32);
+                Err(_) => true, // skipped by a DST gap
+            }
+        };
+
+        // A step no date can take ends the series after its first occurrence
+        let fixed_step = match self.frequency {
+            RecurrenceFrequency::Hourly => Some(Duration::try_hours(interval)),
+            RecurrenceFrequency::Daily => Some(Duration::try_days(interval)),
+            RecurrenceFrequency::Weekly => Some(Duration::try_weeks(interval)),
+            _ => None,
+        };
+        if let Some(step) = fixed_step {
+            let mut first = anchor.and_time(time);
+            if self.frequency == RecurrenceFrequency::Weekly {
+                let week_day = self.week_day.unwrap_or(template.weekday());
+                let offset = (week_day.num_days_from_monday() as i64
+                    - first.weekday().num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                first += Duration::days(offset);
+            }
+            // skip whole steps before the range instead of walking through them
+            let mut current = Some(first);
+            let behind = step.map_or(0, |step| {
+                (wall_time(from, zone) - first).num_seconds() / step.num_seconds()
+            });
+            if behind > 1 {
+                current = step
+                    .and_then(|step| step.num_seconds().checked_mul(behind - 1))
+                    .and_then(Duration::try_seconds)
+                    .and_then(|skip| first.checked_add_signed(skip));
+            }
+            // Past the last date there can be the series stops
+            while let Some(start) = current.filter(|start| push(*start)) {
+                current = step.and_then(|step| start.checked_add_signed(step));
+            }
+        } else {
+            let months = match self.frequency {
+                RecurrenceFrequency::Yearly => interval.checked_mul(12),
+                _ => Some(interval),
+            }
+            .and_then(|months| u32::try_from(months).ok());
+            let mut base = NaiveDate::from_ymd_opt(anchor.year(), anchor.month(), 1).unwrap();
+            if self.frequency == RecurrenceFrequency::Yearly {
+                base = base.with_month(month).unwrap_or(base);
+            }
+            loop {
+                let candidate = NaiveDate::from_ymd_opt(base.year(), base.month(), day);
+                if let Some(date) = candidate.filter(|date| *date >= anchor) {
+                    if !push(date.and_time(time)) {
+                        break;
+                    }
                 }
-                if let Some(Data::Int(year)) = _data.get("year") {
-                    recurrence.year = Some(*year as u32);
+                match months.and_then(|months| base.checked_add_months(Months::new(months))) {
+                    Some(next) if next.and_time(time) < wall_time(until, zone) => base = next,
+                    _ => break,
                 }
-
-                Ok(recurrence)
             }
-            _ => Err("Data must be Type Object".to_string()),
         }
+        result
     }
 
-    pub fn is_now(&self, now: DateTime<Local>) -> bool {
+    // Whether `now` matches the pattern on the wall clock of `zone`, the local one if None
+    pub fn is_now(&self, now: DateTime<Local>, zone: Option<Tz>) -> bool {
+        let wall = wall_time(now, zone);
         let is_minute = self
             .minute
             .map_or(self.frequency != RecurrenceFrequency::Hourly, |minute| {
-                minute == now.minute()
+                minute == wall.minute()
             });
         let is_hour = self
             .hour
             .map_or(self.frequency != RecurrenceFrequency::Daily, |hour| {
-                hour == now.hour()
+                hour == wall.hour()
             });
         let is_day = self
             .day
             .map_or(self.frequency != RecurrenceFrequency::Monthly, |day| {
-                day == now.day()
-            });
-        let is_week_day = self
-            .week_day
-            .map_or(self.frequency != RecurrenceFrequency::Weekly, |week_day| {
-                week_day == now.weekday()
+                day == wall.day()
             });
+        let is_week_day = if self.week_days.is_empty() {
+            self.week_day
+                .map_or(self.frequency != RecurrenceFrequency::Weekly, |week_day| {
+                    week_day == wall.weekday()
+                })
+        } else {
+            self.week_days.contains(&wall.weekday())
+        };
+        // A zero interval would divide by zero below, validation reports it
+        let interval = self.interval.max(1);
+        // Whole weeks since the week the series started in
+        let weeks = (wall.date().week(Weekday::Mon).first_day()
+            - wall_time(self.start_date, zone)
+                .date()
+                .week(Weekday::Mon)
+                .first_day())
+        .num_weeks();
+        // Whole months and years since the month and year the series started in
+        let start = wall_time(self.start_date, zone);
+        let months = (wall.year() as i64 * 12 + wall.month() as i64)
+            - (start.year() as i64 * 12 + start.month() as i64);
+        let years = wall.year() as i64 - start.year() as i64;
         let is_month = self
             .month
             .map_or(self.frequency != RecurrenceFrequency::Yearly, |month| {
-                month == now.month()
+                month == wall.month()
             });
 
         match self.frequency {
@@ -264,14 +643,14 @@ impl Recurrence {
                 is_minute
                     && self.start_date <= now
                     && self.end_date.unwrap_or(now) >= now
-                    && (now - self.start_date).num_hours() % self.interval == 0
+                    && (now - self.start_date).num_hours() % interval == 0
             }
             RecurrenceFrequency::Daily => {
                 !(!is_minute
                     || !is_hour
                     || self.start_date > now
                     || self.end_date.unwrap_or(now) < now
-                    || (now - self.start_date).num_days() % self.interval != 0)
+                    || (now - self.start_date).num_days() % interval != 0)
             }
             RecurrenceFrequency::Weekly => {
                 is_minute
@@ -279,15 +658,15 @@ impl Recurrence {
                     && is_week_day
                     && self.start_date <= now
                     && self.end_date.unwrap_or(now) >= now
-                    && (now - self.start_date).num_days() % (self.interval * 7) == 0
+                    && weeks % interval == 0
             }
             RecurrenceFrequency::Monthly => {
                 is_minute
                     && is_hour
                     && is_day
-                    && self.start_date > now
+                    && self.start_date <= now
                     && self.end_date.unwrap_or(now) >= now
-                    && (now - self.start_date).num_days() % self.interval == 0
+                    && months % interval == 0
             }
             RecurrenceFrequency::Yearly => {
                 !(!is_minute
@@ -296,49 +675,83 @@ impl Recurrence {
                     || !is_month
                     || self.start_date > now
                     || self.end_date.unwrap_or(now) < now
-                    || (now - self.start_date).num_days() % self.interval != 0)
+                    || years % interval != 0)
             }
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct Attendee {
-    pub attendee_id: String, // Unique identifier for the attendee
+    pub attendee_id: String, // ID of the contact, "" for someone who isn't in the contacts
     pub name: String,        // Name of the attendee
     pub email: String,       // Email of the attendee
+    pub role: Role,          // Required unless set
+    pub status: ParticipationStatus, // Their answer, needs-action until they give one
 }
 
 impl std::fmt::Display for Attendee {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.pad(format!("Name: {}, Email: {}", self.name, self.email).as_str())
+        let mut text = format!("Name: {}, Email: {}", self.name, self.email);
+        if let Some(id) = self.contact_id() {
+            text += &format!(" ({})", id);
+        }
+        if self.role != Role::Required {
+            text += &format!(", {}", self.role);
+        }
+        text += &format!(", {}", self.status);
+        f.pad(&text)
     }
 }
 
 impl Attendee {
+    // Older versions wrote "None" for every attendee
+    pub fn contact_id(&self) -> Option<&str> {
+        match self.attendee_id.as_str() {
+            "" | "None" => None,
+            id => Some(id),
+        }
+    }
+
+    // `[contact: c1]`, or `[name: ..., email: ...]` which is linked to the contact with that email
     pub fn from_data(data: &Data) -> Result<Self, String> {
-        match data {
-            Data::Object(attendee_data) => {
-                if let Some(Data::String(name)) = attendee_data.get("name") {
-                    if let Some(Data::String(email)) = attendee_data.get("email") {
-                        Ok(Self {
-                            attendee_id: "None".to_string(),
-                            name: name.to_string(),
-                            email: email.to_string(),
-                        })
-                    } else {
-                        Err("Email must be given".to_string())
-                    }
-                } else {
-                    Err("Name must be given".to_string())
-                }
+        let fields = data_fields(data, &ATTENDEE_FIELDS, &[], "attendee")?;
+        if let Some(reference) = data_text(&fields, "contact")? {
+            let mut attendee = find_contact(&reference)?.attendee()?;
+            if let Some(email) = data_text(&fields, "email")? {
+                attendee.email = email;
             }
-            _ => Err("Data must be object".to_string()),
+            return attendee.with_answer(&fields);
+        }
+        let name = data_text(&fields, "name")?.ok_or_else(|| "Name must be given".to_string())?;
+        let email =
+            data_text(&fields, "email")?.ok_or_else(|| "Email must be given".to_string())?;
+        let attendee_id = find_contact(&email)
+            .map(|contact| contact.contact_id)
+            .unwrap_or_default();
+        Self {
+            attendee_id,
+            name,
+            email,
+            ..Default::default()
         }
+        .with_answer(&fields)
+    }
+
+    fn with_answer(mut self, fields: &HashMap<String, &Data>) -> Result<Self, String> {
+        if let Some(role) = data_text(fields, "role")? {
+            self.role = Role::parse(&role)?;
+        }
+        if let Some(status) = data_text(fields, "status")? {
+            self.status = ParticipationStatus::parse(&status)?;
+        }
+        Ok(self)
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct Event {
     pub event_id: String,                         // Unique identifier for the event
     pub title: String,                            // Title of the event
@@ -346,19 +759,44 @@ pub struct Event {
     pub location: String,                         // Location of the event
     pub start_time: DateTime<Local>,              // Start time of the event
     pub end_time: DateTime<Local>,                // End time of the event
-    pub is_recurring: bool,                       // Flag to indicate if the event is recurring
-    pub recurrence: Option<Recurrence>,           // Recurrence details (if applicable)
-    pub attendees: Vec<Attendee>,                 // List of attendees
-    pub created_at: DateTime<Local>,              // Timestamp when the event was created
-    pub updated_at: DateTime<Local>,              // Timestamp when the event was last updated
+    pub time_zone: Option<Tz>, // IANA zone the times were given in, the local one if None
+    pub is_recurring: bool,    // Flag to indicate if the event is recurring
+    pub recurrence: Option<Recurrence>, // Recurrence details (if applicable)
+    pub attendees: Vec<Attendee>, // List of attendees
+    pub created_at: DateTime<Local>, // Timestamp when the event was created
+    pub updated_at: DateTime<Local>, // Timestamp when the event was last updated
     pub notification_settings: Vec<Notification>, // Notification settings
-    pub is_all_day: bool,                         // Some comment for astetic reasons
-    pub categories: Vec<String>,                  // Categories for the event
+    pub is_all_day: bool,      // Some comment for astetic reasons
+    pub categories: Vec<String>, // Categories for the event
+    pub tags: Vec<String>,     // Free-form labels, unlike categories not kept in a list
+    pub priority: Priority,    // Normal unless set
+    pub transparency: Option<Transparency>, // Busy or free, None takes it from the categories
+    pub organizer: Option<Attendee>, // Who sends the invitations, None for private events
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub uid: String, // UID of an event imported from iCalendar, see `uid()`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub sequence: u32, // Revision of the invitation, raised when its time or place changes
+}
+
+fn is_zero(number: &u32) -> bool {
+    *number == 0
 }
 
 impl std::fmt::Display for Event {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.pad(&serde_json::to_string_pretty(&self).unwrap_or("Error".to_string()))
+        let mut summary = format!(
+            "{} {} {}",
+            self.start_time.format("%Y-%m-%d"),
+            time_range(self),
+            self.title
+        );
+        if !self.location.is_empty() {
+            summary += &format!(" @ {}", self.location);
+        }
+        if !self.categories.is_empty() {
+            summary += &format!(" [{}]", self.categories.join(", "));
+        }
+        f.pad(&summary)
     }
 }
 
@@ -372,6 +810,7 @@ impl Default for Event {
             location: Default::default(),
             start_time,
             end_time: start_time + Duration::hours(1),
+            time_zone: None,
             is_recurring: Default::default(),
             recurrence: Default::default(),
             attendees: Default::default(),
@@ -380,6 +819,12 @@ impl Default for Event {
             notification_settings: Default::default(),
             is_all_day: false,
             categories: Vec::new(),
+            tags: Vec::new(),
+            priority: Priority::Normal,
+            transparency: None,
+            organizer: None,
+            uid: String::new(),
+            sequence: 0,
         }
     }
 }
@@ -389,6 +834,10 @@ impl Event {
         self.event_id = event_id;
         self
     }
+    pub fn set_time_zone(mut self, time_zone: Option<Tz>) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
     pub fn set_title(mut self, title: String) -> Self {
         self.title = title;
         self
@@ -434,6 +883,31 @@ impl Event {
         self
     }
 
+    pub fn set_is_all_day(mut self, is_all_day: bool) -> Self {
+        self.is_all_day = is_all_day;
+        self
+    }
+
+    pub fn set_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    pub fn set_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn set_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn set_transparency(mut self, transparency: Option<Transparency>) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
     // Update the title of the event
     pub fn update_title(&mut self, new_title: String) {
         self.title = new_title;
@@ -476,6 +950,38 @@ impl Event {
         self.updated_at = chrono::Local::now();
     }
 
+    pub fn find_attendee(&self, email: &str) -> Option<&Attendee> {
+        self.attendees
+            .iter()
+            .find(|attendee| attendee.email.eq_ignore_ascii_case(email))
+    }
+
+    pub fn find_attendee_mut(&mut self, email: &str) -> Option<&mut Attendee> {
+        self.attendees
+            .iter_mut()
+            .find(|attendee| attendee.email.eq_ignore_ascii_case(email))
+    }
+
+    // How many attendees gave each answer
+    pub fn count_answers(&self, status: ParticipationStatus) -> usize {
+        self.attendees
+            .iter()
+            .filter(|attendee| attendee.status == status)
+            .count()
+    }
+
+    // The UID in iCalendar files, the imported one or one made from the ID and creation time
+    pub fn uid(&self) -> String {
+        match self.uid.is_empty() {
+            true => format!(
+                "{}-{}@rustyplanner",
+                self.created_at.timestamp(),
+                self.event_id.trim_start_matches('#')
+            ),
+            false => self.uid.clone(),
+        }
+    }
+
     // Add an attendee to the event
     pub fn add_attendee(&mut self, attendee: Attendee) {
         self.attendees.push(attendee);
@@ -508,139 +1014,396 @@ impl Event {
         }
     }
 
-    pub fn from_data(data: Data) -> Result<Self, String> {
-        match data {
-            Data::Object(fields) => {
-                let mut event = Event::default();
-
-                // Extract fields from the HashMap
-                if let Some(Data::String(event_id)) = fields.get("event_id") {
-                    event.event_id = event_id.clone();
-                }
-                if let Some(Data::String(title)) = fields.get("title") {
-                    event.title = title.clone();
+    // Changes a single field from its string form, as used by `edit <event> --set key=value`.
+    // Moving the start (start_time, date, time) keeps the duration of the event.
+    // Dates and times are read in the event's time zone.
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let length = self.end_time - self.start_time;
+        let zone = self.time_zone;
+        let start = wall_time(self.start_time, zone);
+        match key {
+            "title" => self.update_title(value.to_string()),
+            "description" => self.update_description(value.to_string()),
+            "location" => self.update_location(value.to_string()),
+            "start_time" => {
+                let start_time = parse_datetime(value, Local::now(), zone)?;
+                self.update_start_time(start_time);
+                self.update_end_time(start_time + length);
+            }
+            "end_time" => self.update_end_time(parse_datetime(value, Local::now(), zone)?),
+            "date" => {
+                let date = wall_time(parse_datetime(value, Local::now(), zone)?, zone).date();
+                let start_time = zoned_from_naive(date.and_time(start.time()), zone)?;
+                self.update_start_time(start_time);
+                self.update_end_time(start_time + length);
+            }
+            "time" => {
+                let time = wall_time(parse_datetime(value, Local::now(), zone)?, zone).time();
+                let start_time = zoned_from_naive(start.date().and_time(time), zone)?;
+                self.update_start_time(start_time);
+                self.update_end_time(start_time + length);
+            }
+            "time_zone" => {
+                // Keeps the wall-clock times, 10:00 stays 10:00 but in the new zone
+                let new_zone = parse_time_zone(value)?;
+                let end = wall_time(self.end_time, zone);
+                self.update_start_time(zoned_from_naive(start, new_zone)?);
+                self.update_end_time(zoned_from_naive(end, new_zone)?);
+                self.time_zone = new_zone;
+            }
+            "duration" => self.update_end_time(self.start_time + parse_duration(value)?),
+            "is_recurring" => self.update_is_recurring(parse_bool(key, value)?),
+            "is_all_day" | "whole_day" => {
+                self.is_all_day = parse_bool(key, value)?;
+                self.snap_all_day()?;
+                self.updated_at = chrono::Local::now();
+            }
+            "end_date" => {
+                if !self.is_all_day {
+                    return Err("'end_date' is the last day of an all-day event".to_string());
                 }
-                if let Some(Data::String(description)) = fields.get("description") {
-                    event.description = description.clone();
+                let last = wall_time(parse_datetime(value, Local::now(), zone)?, zone).date();
+                if last < start.date() {
+                    return Err(format!("the last day {} is before the first day", last));
                 }
-
-                let date = if let Some(Data::String(date)) = fields.get("date") {
-                    date_from_str(date)
-                } else {
-                    Local::now().naive_local().date()
-                };
-                let time = if let Some(Data::String(time)) = fields.get("time") {
-                    time_from_str(time)
-                } else if let Some(Data::String(_)) = fields.get("whole_day") {
-                    event.is_all_day = true;
-                    time_from_str("0:00")
-                } else {
-                    Local::now().naive_utc().time()
+                let end_time = (last + Duration::days(1)).and_time(NaiveTime::MIN);
+                self.update_end_time(zoned_from_naive(end_time, zone)?);
+            }
+            "categories" => {
+                self.categories = split_list(value);
+                self.updated_at = chrono::Local::now();
+            }
+            "tags" => {
+                self.tags = unique(split_list(value));
+                self.updated_at = chrono::Local::now();
+            }
+            "priority" => {
+                self.priority = Priority::parse(value)?;
+                self.updated_at = chrono::Local::now();
+            }
+            // An empty value goes back to what the categories say
+            "transparency" => {
+                self.transparency = match value {
+                    "" => None,
+                    value => Some(Transparency::parse(value)?),
                 };
-                let naive_datetime = date.and_time(time);
-
-                event.start_time =
-                    DateTime::from_naive_utc_and_offset(naive_datetime, *Local::now().offset());
-
-                let duration = if !event.is_all_day {
-                    Duration::days(1)
-                } else if let Some(Data::String(duration)) = fields.get("duration") {
-                    match parse_duration(duration) {
-                        Ok(d) => d,
-                        Err(_) => {
-                            println!("duration invalid");
-                            Duration::hours(2)
-                        }
+                self.updated_at = chrono::Local::now();
+            }
+            // Contacts, emails or "Name <email>", those already invited keep their role and answer
+            "attendees" => {
+                let mut attendees = vec![];
+                for attendee in split_list(value) {
+                    let mut attendee = resolve_attendee(&attendee)?;
+                    if let Some(known) = self.find_attendee(&attendee.email) {
+                        attendee.role = known.role;
+                        attendee.status = known.status;
                     }
-                } else {
-                    Duration::hours(2)
+                    attendees.push(attendee);
+                }
+                self.attendees = attendees;
+                self.updated_at = chrono::Local::now();
+            }
+            // A contact, email or "Name <email>", empty for none
+            "organizer" => {
+                self.organizer = match value {
+                    "" => None,
+                    value => Some(resolve_attendee(value)?),
                 };
+                self.updated_at = chrono::Local::now();
+            }
+            "notification_settings" => {
+                let mut notifications = vec![];
+                for reminder in split_list(value) {
+                    notifications.push(parse_reminder(&reminder)?);
+                }
+                self.notification_settings = notifications;
+                self.updated_at = chrono::Local::now();
+            }
+            _ => {
+                return Err(format!(
+                    "unknown field '{}', expected one of: {}",
+                    key,
+                    SETTABLE_FIELDS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
 
-                event.end_time = event.start_time + duration;
+    // Quick-add one-liner: `Standup mon-fri 09:15 30m @Room4 #Work +daily ^high !10m -- notes`.
+    // The first word is the title, `@` sets the location, `#` adds a category, `+` a tag, `^`
+    // sets the priority, `!` adds a reminder that long before the start and everything after
+    // `--` is the description. The remaining words say when, see `parse_when`.
+    pub fn from_quick_add(
+        words: &[String],
+        now: DateTime<Local>,
+        default_length: Duration,
+        zone: Option<Tz>,
+    ) -> Result<Self, String> {
+        let (title, rest) = words
+            .split_first()
+            .ok_or_else(|| "an event needs a title".to_string())?;
+        let (rest, description) = match rest.iter().position(|word| word == "--") {
+            Some(split) => (&rest[..split], rest[split + 1..].join(" ")),
+            None => (rest, String::new()),
+        };
+
+        let mut location = String::new();
+        let mut categories = vec![];
+        let mut tags = vec![];
+        let mut priority = Priority::Normal;
+        let mut notifications = vec![];
+        let mut when = vec![];
+        for word in rest {
+            if let Some(place) = word.strip_prefix('@').filter(|place| !place.is_empty()) {
+                location = place.to_string();
+            } else if let Some(category) = word.strip_prefix('#').filter(|c| !c.is_empty()) {
+                categories.push(category.to_string());
+            } else if let Some(tag) = word.strip_prefix('+').filter(|tag| !tag.is_empty()) {
+                tags.push(tag.to_string());
+            } else if let Some(level) = word.strip_prefix('^') {
+                priority = Priority::parse(level)?;
+            } else if let Some(lead) = word.strip_prefix('!') {
+                notifications.push(parse_reminder(lead).map_err(|_| {
+                    format!("'{}' is not a reminder like !10m, !1h or !1d@18:00", word)
+                })?);
+            } else {
+                when.push(word.as_str());
+            }
+        }
 
-                if let Some(Data::String(start_time_str)) = fields.get("start_time") {
-                    if let Ok(start_time) = DateTime::parse_from_rfc3339(start_time_str) {
-                        event.start_time = start_time.with_timezone(&Local);
-                    } else {
-                        return Err("Invalid start_time format".to_string());
-                    }
-                }
-                if let Some(Data::String(end_time_str)) = fields.get("end_time") {
-                    if let Ok(end_time) = DateTime::parse_from_rfc3339(end_time_str) {
-                        event.end_time = end_time.with_timezone(&Local);
-                    } else {
-                        return Err("Invalid end_time format".to_string());
-                    }
-                }
-                if let Some(Data::String(location)) = fields.get("location") {
-                    event.location = location.clone();
-                }
-                if let Some(Data::Int(is_recurring)) = fields.get("is_recurring") {
-                    event.is_recurring = *is_recurring != 0; // Assuming 0 is false, 1 is true
-                }
+        let (start, end, all_day, recurrence) = if when.is_empty() {
+            (now, now + default_length, false, None)
+        } else {
+            let when = parse_when(&when.join(" "), now, zone)?;
+            let end = when.end.unwrap_or(when.start + default_length);
+            (when.start, end, when.all_day, when.recurrence)
+        };
+
+        let mut event = Event::default()
+            .set_title(title.clone())
+            .set_description(description)
+            .set_location(location)
+            .set_start_time(start)
+            .set_end_time(end)
+            .set_time_zone(zone)
+            .set_is_all_day(all_day)
+            .set_is_recurring(recurrence.is_some())
+            .set_recurrence(recurrence)
+            .set_categories(categories)
+            .set_tags(unique(tags))
+            .set_priority(priority)
+            .set_notification_settings(notifications);
+        event.snap_all_day()?;
+        Ok(event)
+    }
 
-                if let Some(data) = fields.get("recurrence") {
-                    event.is_recurring = true;
-                    match Recurrence::from_data(data) {
-                        Ok(recurrence) => event.recurrence = Some(recurrence),
-                        Err(_) => eprintln!("error parsing recurrence"),
-                    }
-                }
+    // Events without a duration last `default_length`, all-day events a day
+    pub fn from_data(data: Data, default_length: Duration) -> Result<Self, String> {
+        let fields = data_fields(&data, &EVENT_FIELDS, &[], "event")?;
+        let mut event = Event::default();
 
-                if let Some(Data::List(attendees)) = fields.get("attendees") {
-                    for anttendee_data in attendees {
-                        if let Ok(attendee) = Attendee::from_data(anttendee_data) {
-                            event.attendees.push(attendee);
-                        }
-                    }
-                }
+        if let Some(event_id) = data_text(&fields, "event_id")? {
+            event.event_id = event_id;
+        }
+        if let Some(title) = data_text(&fields, "title")? {
+            event.title = title;
+        }
+        if let Some(description) = data_text(&fields, "description")? {
+            event.description = description;
+        }
 
-                if let Some(Data::List(notifications)) = fields.get("notification-settings") {
-                    for notification_data in notifications {
-                        if let Ok(notification) = Notification::from_data(notification_data) {
-                            event.notification_settings.push(notification);
-                        }
-                    }
-                }
+        if let Some(time_zone) = data_text(&fields, "time_zone")? {
+            event.time_zone = parse_time_zone(&time_zone)?;
+        }
+        // `whole_day` is the older name of `is_all_day`
+        event.is_all_day = match (
+            data_bool(&fields, "is_all_day")?,
+            data_bool(&fields, "whole_day")?,
+        ) {
+            (Some(is_all_day), Some(whole_day)) if is_all_day != whole_day => {
+                return Err("'is_all_day' and 'whole_day' contradict each other".to_string())
+            }
+            (Some(is_all_day), _) | (None, Some(is_all_day)) => is_all_day,
+            (None, None) => false,
+        };
+
+        // date and time are wall-clock times in the event's zone
+        let now = wall_time(Local::now(), event.time_zone);
+        let date = match data_text(&fields, "date")? {
+            Some(date) => date_from_str(&date)?,
+            None => now.date(),
+        };
+        let time = match data_text(&fields, "time")? {
+            Some(_) if event.is_all_day => {
+                return Err("an all-day event has no 'time', use start_time instead".to_string())
+            }
+            Some(time) => time_from_str(&time)?,
+            None if event.is_all_day => NaiveTime::MIN,
+            None => now.time(),
+        };
+        event.start_time = zoned_from_naive(date.and_time(time), event.time_zone)?;
+
+        let duration = match data_text(&fields, "duration")? {
+            Some(duration) => parse_duration(&duration)?,
+            None if event.is_all_day => Duration::days(1),
+            None => default_length,
+        };
+        event.end_time = event.start_time + duration;
+
+        if let Some(end_date) = data_text(&fields, "end_date")? {
+            if !event.is_all_day {
+                return Err("'end_date' is the last day of an all-day event".to_string());
+            }
+            let last = date_from_str(&end_date)?;
+            event.end_time = zoned_from_naive(
+                (last + Duration::days(1)).and_time(NaiveTime::MIN),
+                event.time_zone,
+            )?;
+        }
 
-                if let Some(Data::String(is_all_day)) = fields.get("is-all-day") {
-                    event.is_all_day = is_all_day == "true";
-                }
+        if let Some(start_time_str) = data_text(&fields, "start_time")? {
+            if let Ok(start_time) = DateTime::parse_from_rfc3339(&start_time_str) {
+                event.start_time = start_time.with_timezone(&Local);
+            } else {
+                return Err("Invalid start_time format".to_string());
+            }
+        }
+        if let Some(end_time_str) = data_text(&fields, "end_time")? {
+            if let Ok(end_time) = DateTime::parse_from_rfc3339(&end_time_str) {
+                event.end_time = end_time.with_timezone(&Local);
+            } else {
+                return Err("Invalid end_time format".to_string());
+            }
+        }
+        if let Some(location) = data_text(&fields, "location")? {
+            event.location = location;
+        }
+        if let Some(is_recurring) = data_bool(&fields, "is_recurring")? {
+            event.is_recurring = is_recurring;
+        }
 
-                if let Some(Data::List(categories)) = fields.get("categories") {
-                    for category in categories {
-                        if let Data::String(category) = category {
-                            event.categories.push(category.clone());
-                        }
-                    }
-                }
+        if let Some(data) = fields.get("recurrence") {
+            event.is_recurring = true;
+            event.recurrence =
+                Some(Recurrence::from_data(data).map_err(|e| format!("recurrence: {}", e))?);
+        }
 
-                if event.notification_settings.is_empty() {
-                    event.notification_settings.push(Notification::default());
-                }
-                Ok(event)
+        for (i, attendee_data) in data_list(&fields, "attendees").into_iter().enumerate() {
+            let attendee = Attendee::from_data(attendee_data)
+                .map_err(|e| format!("attendee {}: {}", i + 1, e))?;
+            event.attendees.push(attendee);
+        }
+
+        for (i, notification_data) in data_list(&fields, "notification_settings")
+            .into_iter()
+            .enumerate()
+        {
+            let notification = Notification::from_data(notification_data)
+                .map_err(|e| format!("notification {}: {}", i + 1, e))?;
+            event.notification_settings.push(notification);
+        }
+
+        for category in data_list(&fields, "categories") {
+            let category = category
+                .as_text()
+                .ok_or_else(|| format!("categories must be text, got {}", category.type_name()))?;
+            event.categories.push(category);
+        }
+        for tag in data_list(&fields, "tags") {
+            let tag = tag
+                .as_text()
+                .ok_or_else(|| format!("tags must be text, got {}", tag.type_name()))?;
+            event.tags.push(tag);
+        }
+        event.tags = unique(std::mem::take(&mut event.tags));
+        if let Some(priority) = data_text(&fields, "priority")? {
+            event.priority = Priority::parse(&priority)?;
+        }
+        if let Some(transparency) = data_text(&fields, "transparency")? {
+            event.transparency = Some(Transparency::parse(&transparency)?);
+        }
+        if let Some(organizer) = data_text(&fields, "organizer")? {
+            event.organizer = Some(resolve_attendee(&organizer)?);
+        }
+
+        event.snap_all_day()?;
+        Ok(event)
+    }
+
+    // First and last day of an all-day event, on the calendar of its time zone
+    pub fn all_day_dates(&self) -> (NaiveDate, NaiveDate) {
+        let first = wall_time(self.start_time, self.time_zone).date();
+        let last = wall_time(self.end_time - Duration::seconds(1), self.time_zone).date();
+        (first, last.max(first))
+    }
+
+    // All-day events cover whole days: from midnight of the first day to midnight after the last
+    pub fn snap_all_day(&mut self) -> Result<(), String> {
+        if !self.is_all_day {
+            return Ok(());
+        }
+        let (first, last) = self.all_day_dates();
+        self.start_time = zoned_from_naive(first.and_time(NaiveTime::MIN), self.time_zone)?;
+        self.end_time = zoned_from_naive(
+            (last + Duration::days(1)).and_time(NaiveTime::MIN),
+            self.time_zone,
+        )?;
+        Ok(())
+    }
+
+    // Start and end of every occurrence overlapping [from, to), a single one for plain events
+    pub fn occurrences_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        let length = self.end_time - self.start_time;
+        // Repeated all-day events span the same number of days, whatever DST does to the hours
+        let days = {
+            let (first, last) = self.all_day_dates();
+            (last - first).num_days() + 1
+        };
+        let end_of = |start: DateTime<Local>| {
+            if self.is_all_day {
+                let last = wall_time(start, self.time_zone).date() + Duration::days(days);
+                zoned_from_naive(last.and_time(NaiveTime::MIN), self.time_zone)
+                    .unwrap_or(start + length)
+            } else {
+                start + length
+            }
+        };
+        let overlaps = |start: DateTime<Local>, end: DateTime<Local>| {
+            start < to && (end > from || start == from)
+        };
+        match (&self.recurrence, self.is_recurring) {
+            (Some(recurrence), true) => recurrence
+                .occurrences(self.start_time, from - length, to, self.time_zone)
+                .into_iter()
+                .map(|start| (start, end_of(start)))
+                .filter(|(start, end)| overlaps(*start, *end))
+                .collect(),
+            _ if overlaps(self.start_time, self.end_time) => {
+                vec![(self.start_time, self.end_time)]
             }
-            _ => Err("Expected an Object variant".to_string()),
+            _ => vec![],
         }
     }
 
     pub fn is_time_to_notify(&self, now: DateTime<Local>) -> Vec<(usize, bool)> {
         let mut notifications = vec![];
         for (i, notification) in self.notification_settings.iter().enumerate() {
-            if self.is_recurring {
-                if self
-                    .recurrence
-                    .as_ref()
-                    .unwrap()
-                    .is_now(now + Duration::minutes(notification.notify_before))
-                {
+            // How long before the start the reminder goes off, e.g. 6 hours for the day before at 18:00
+            let lead = self.start_time - notification.due_time(self.start_time, self.time_zone);
+            // Without a recurrence the event is taken to happen once
+            if let Some(recurrence) = self.recurrence.as_ref().filter(|_| self.is_recurring) {
+                if recurrence.is_now(now + lead, self.time_zone) {
                     notifications.push((i, true));
                 } else {
                     notifications.push((i, false));
                 }
-            } else if self.start_time - Duration::minutes(notification.notify_before) <= now
-                && !notification.has_notified
-            {
+            } else if self.start_time - lead <= now && !notification.has_notified {
                 notifications.push((i, true));
             } else {
                 notifications.push((i, false));
@@ -650,20 +1413,212 @@ impl Event {
     }
 }
 
+// Fields of a `[key: value]` object with `-` in keys read as `_`, and `aliases` (old name,
+// current name) read as the current name. Unknown keys are an error that suggests the closest
+// known one.
+fn data_fields<'a>(
+    data: &'a Data,
+    known: &[[&str; 2]],
+    aliases: &[[&str; 2]],
+    context: &str,
+) -> Result<HashMap<String, &'a Data>, String> {
+    let Data::Object(object) = data else {
+        return Err(format!(
+            "{} must be an object like [key: value], got {}",
+            context,
+            data.type_name()
+        ));
+    };
+    let names: Vec<String> = known
+        .iter()
+        .map(|[name, _]| name.replace('-', "_"))
+        .collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let documented: Vec<&str> = known.iter().map(|[name, _]| *name).collect();
+    let what = if context == "event" {
+        "field".to_string()
+    } else {
+        format!("{} field", context)
+    };
+
+    let mut keys: Vec<&String> = object.keys().collect();
+    keys.sort();
+    let mut fields = HashMap::new();
+    let mut given = HashMap::new();
+    for key in keys {
+        let mut normalized = key.replace('-', "_");
+        if let Some([_, name]) = aliases
+            .iter()
+            .find(|[alias, _]| alias.replace('-', "_") == normalized)
+        {
+            normalized = name.replace('-', "_");
+        }
+        if !names.contains(&normalized.as_str()) {
+            return Err(match did_you_mean(&normalized, &names) {
+                Some(name) => {
+                    let index = names.iter().position(|n| *n == name).unwrap();
+                    format!(
+                        "unknown {} '{}', did you mean '{}'?",
+                        what, key, documented[index]
+                    )
+                }
+                None => format!(
+                    "unknown {} '{}', expected one of: {}",
+                    what,
+                    key,
+                    documented.join(", ")
+                ),
+            });
+        }
+        // `start-time` and `start_time`, or a name and its alias
+        if let Some(first) = given.insert(normalized.clone(), key) {
+            return Err(format!(
+                "{}s '{}' and '{}' are the same, give only one",
+                what, first, key
+            ));
+        }
+        fields.insert(normalized, &object[key]);
+    }
+    Ok(fields)
+}
+
+fn data_text(fields: &HashMap<String, &Data>, key: &str) -> Result<Option<String>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_text()
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be text, got {}", key, value.type_name())),
+    }
+}
+
+fn data_int(fields: &HashMap<String, &Data>, key: &str) -> Result<Option<i64>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value.as_int().map(Some).ok_or_else(|| {
+            format!(
+                "'{}' must be a whole number, got {}",
+                key,
+                value.type_name()
+            )
+        }),
+    }
+}
+
+fn data_bool(fields: &HashMap<String, &Data>, key: &str) -> Result<Option<bool>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be true or false, got {}", key, value.type_name())),
+    }
+}
+
+// A list field, a single value counts as a list of one
+fn data_list<'a>(fields: &HashMap<String, &'a Data>, key: &str) -> Vec<&'a Data> {
+    match fields.get(key) {
+        None => vec![],
+        Some(Data::List(list)) => list.iter().collect(),
+        Some(value) => vec![*value],
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("'{}' expects true or false, got '{}'", key, value)),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Drops repeated items, keeping the first of each
+fn unique(items: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for item in items {
+        if !result.contains(&item) {
+            result.push(item);
+        }
+    }
+    result
+}
+
+// fields of an Event as stored and exported, including those only written when set
+pub const STORED_FIELDS: [&str; 21] = [
+    "event_id",
+    "title",
+    "description",
+    "location",
+    "start_time",
+    "end_time",
+    "time_zone",
+    "is_recurring",
+    "recurrence",
+    "attendees",
+    "created_at",
+    "updated_at",
+    "notification_settings",
+    "is_all_day",
+    "categories",
+    "tags",
+    "priority",
+    "transparency",
+    "organizer",
+    "uid",
+    "sequence",
+];
+
+// fields that can be changed with Event::set_field
+pub const SETTABLE_FIELDS: [&str; 20] = [
+    "title",
+    "description",
+    "location",
+    "start_time",
+    "end_time",
+    "date",
+    "time",
+    "time_zone",
+    "end_date",
+    "duration",
+    "is_recurring",
+    "is_all_day",
+    "whole_day",
+    "categories",
+    "tags",
+    "priority",
+    "transparency",
+    "attendees",
+    "organizer",
+    "notification_settings",
+];
+
 // list of keywords for creating an event from data, with description as [[&str; 2]; num_of_keywords]
-pub const EVENT_FIELDS: [[&str; 2]; 16] = [
+pub const EVENT_FIELDS: [[&str; 2]; 22] = [
     ["event_id", "ID of the event, currently autogenerated"],
     ["title", "Name of the event"],
     ["description", "More detailed Description of the event"],
     ["date", "Date of the event"],
     ["time", "Time of the event"],
+    ["whole_day", "Same as is_all_day"],
+    ["duration", "Duration of the event"],
     [
-        "whole_day",
-        "Flag to indicate if the event is an all-day event",
+        "end_date",
+        "Last day of an all-day event spanning several days",
     ],
-    ["duration", "Duration of the event"],
     ["start_time", "Start time of the event"],
     ["end_time", "End time of the event"],
+    [
+        "time_zone",
+        "IANA time zone of date and time, e.g. Europe/Berlin",
+    ],
     ["location", "Location of the event"],
     ["is_recurring", "Flag to indicate if the event is recurring"],
     ["recurrence", "Recurrence details for the event"],
@@ -677,8 +1632,21 @@ pub const EVENT_FIELDS: [[&str; 2]; 16] = [
         "Flag to indicate if the event is an all-day event",
     ],
     ["categories", "Categories for the event"],
+    ["tags", "Free-form tags, e.g. [travel, q3]"],
+    ["priority", "low, normal, high or urgent"],
+    [
+        "transparency",
+        "busy or free, only busy events conflict with others",
+    ],
+    [
+        "organizer",
+        "Contact, email or \"Name <email>\" of who invites the attendees",
+    ],
 ];
 
+// Names the recurrence keys had before, still read as the documented ones
+const RECURRENCE_ALIASES: [[&str; 2]; 2] = [["start-date", "start-time"], ["end-date", "end-time"]];
+
 pub const RECURRENCE_FIELDS: [[&str; 2]; 10] = [
     [
         "frequency",
@@ -698,15 +1666,368 @@ pub const RECURRENCE_FIELDS: [[&str; 2]; 10] = [
     ["year", "Year for the event"],
 ];
 
-pub const ATTENDEE_FIELDS: [[&str; 2]; 2] = [
+pub const ATTENDEE_FIELDS: [[&str; 2]; 5] = [
+    [
+        "contact",
+        "ID, name or email of a contact, instead of name and email",
+    ],
     ["name", "Name of the attendee"],
     ["email", "Email of the attendee"],
+    ["role", "chair, required or optional"],
+    ["status", "needs-action, accepted, declined or tentative"],
 ];
 
 pub const NOTIFICATION_FIELDS: [[&str; 2]; 2] = [
     [
         "remind-before",
-        "Time in minutes before the event to send the notification",
+        "Minutes or a duration before the event, or e.g. \"the day before at 18:00\"",
     ],
     ["method", "Method of notification (e.g., email, SMS, push)"],
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miscs::utils::datetime_from_str;
+
+    fn at(input: &str) -> DateTime<Local> {
+        datetime_from_str(input, None).unwrap()
+    }
+
+    fn event() -> Event {
+        Event::default()
+            .set_start_time(at("2026-10-20 14:00"))
+            .set_end_time(at("2026-10-20 15:30"))
+    }
+
+    fn weekly(start: &str, end_date: Option<&str>) -> Recurrence {
+        Recurrence {
+            frequency: RecurrenceFrequency::Weekly,
+            interval: 1,
+            start_date: at(start),
+            end_date: end_date.map(at),
+            minute: None,
+            hour: None,
+            day: None,
+            week_day: None,
+            week_days: vec![],
+            month: None,
+            year: None,
+        }
+    }
+
+    #[test]
+    fn moving_the_start_keeps_the_length() {
+        let mut event = event();
+        event.set_field("start_time", "2026-10-21 09:00").unwrap();
+        assert_eq!(event.end_time, at("2026-10-21 10:30"));
+        event.set_field("date", "2026-10-25").unwrap();
+        assert_eq!(event.start_time, at("2026-10-25 09:00"));
+        event.set_field("time", "11:15").unwrap();
+        assert_eq!(event.start_time, at("2026-10-25 11:15"));
+        assert_eq!(event.end_time, at("2026-10-25 12:45"));
+        event.set_field("duration", "2h").unwrap();
+        assert_eq!(event.end_time, at("2026-10-25 13:15"));
+        event.set_field("end_time", "2026-10-25 18:00").unwrap();
+        assert_eq!(event.start_time, at("2026-10-25 11:15"));
+    }
+
+    #[test]
+    fn sets_text_lists_and_flags() {
+        let mut event = event();
+        event.set_field("title", "  Lunch ").unwrap();
+        event.set_field("categories", "work, ,home").unwrap();
+        event.set_field("whole_day", "yes").unwrap();
+        event.set_field("notification_settings", "5, 30").unwrap();
+        assert_eq!(event.title, "Lunch");
+        assert_eq!(event.categories, ["work", "home"]);
+        assert!(event.is_all_day);
+        let minutes: Vec<_> = event
+            .notification_settings
+            .iter()
+            .map(|notification| notification.notify_before)
+            .collect();
+        assert_eq!(minutes, [5, 30]);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_bad_values() {
+        let mut event = event();
+        assert!(event
+            .set_field("colour", "red")
+            .unwrap_err()
+            .starts_with("unknown field 'colour'"));
+        assert!(event.set_field("is_recurring", "maybe").is_err());
+        assert!(event.set_field("notification_settings", "5, soon").is_err());
+        assert!(event.set_field("start_time", "someday").is_err());
+        assert_eq!(event.title, "");
+    }
+
+    #[test]
+    fn expands_series_within_a_range() {
+        let mut standup = event();
+        standup.is_recurring = true;
+        standup.recurrence = Some(weekly("2026-10-20 14:00", Some("2026-11-10")));
+        let starts: Vec<_> = standup
+            .occurrences_between(at("2026-10-22"), at("2026-12-01"))
+            .into_iter()
+            .map(|(start, end)| {
+                assert_eq!(end - start, Duration::minutes(90));
+                start
+            })
+            .collect();
+        assert_eq!(starts, [at("2026-10-27 14:00"), at("2026-11-03 14:00")]);
+
+        // One still running at the start of the range is included
+        let running = event().occurrences_between(at("2026-10-20 15:00"), at("2026-10-21"));
+        assert_eq!(running, [(at("2026-10-20 14:00"), at("2026-10-20 15:30"))]);
+        assert!(event()
+            .occurrences_between(at("2026-10-20 15:30"), at("2026-10-21"))
+            .is_empty());
+    }
+
+    #[test]
+    fn quick_add_reads_markers_and_when() {
+        let words: Vec<String> =
+            "Standup mon-fri 09:15 15m @Room4 #Work !10m !1h !1d@18:00 -- daily sync"
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+        let now = at("2026-10-14 08:00");
+        let event = Event::from_quick_add(&words, now, Duration::hours(1), None).unwrap();
+        assert_eq!(event.title, "Standup");
+        assert_eq!(event.location, "Room4");
+        assert_eq!(event.categories, ["Work"]);
+        assert_eq!(event.description, "daily sync");
+        let minutes: Vec<_> = event
+            .notification_settings
+            .iter()
+            .map(|notification| notification.notify_before)
+            .collect();
+        assert_eq!(minutes, [10, 60, 0]);
+        let day = event.notification_settings[2].on_day.as_ref().unwrap();
+        assert_eq!(day.days_before, 1);
+        assert_eq!(event.start_time, at("2026-10-14 09:15"));
+        assert_eq!(event.end_time, at("2026-10-14 09:30"));
+        assert!(event.is_recurring);
+        assert_eq!(event.recurrence.unwrap().week_days.len(), 5);
+    }
+
+    #[test]
+    fn quick_add_falls_back_to_defaults() {
+        let now = at("2026-10-14 08:00");
+        let words = [
+            "Call".to_string(),
+            "tomorrow".to_string(),
+            "14:00".to_string(),
+        ];
+        let event = Event::from_quick_add(&words, now, Duration::minutes(30), None).unwrap();
+        assert_eq!(event.start_time, at("2026-10-15 14:00"));
+        assert_eq!(event.end_time, at("2026-10-15 14:30"));
+        assert!(event.notification_settings.is_empty());
+        assert!(!event.is_recurring);
+
+        let event = Event::from_quick_add(&words[..1], now, Duration::minutes(30), None).unwrap();
+        assert_eq!(
+            (event.start_time, event.end_time),
+            (now, at("2026-10-14 08:30"))
+        );
+
+        assert!(Event::from_quick_add(&[], now, Duration::minutes(30), None).is_err());
+        let words = ["Call".to_string(), "!soon".to_string()];
+        assert_eq!(
+            Event::from_quick_add(&words, now, Duration::minutes(30), None).unwrap_err(),
+            "'!soon' is not a reminder like !10m, !1h or !1d@18:00"
+        );
+    }
+
+    #[test]
+    fn reads_recurrences_with_old_key_names() {
+        let data = crate::miscs::arg_parsing::parse_data(
+            "frequency: weekly, intervall: 2, start-date: 2026-10-20, end_date: 2026-12-01",
+        )
+        .unwrap();
+        let recurrence = Recurrence::from_data(&data).unwrap();
+        assert_eq!(recurrence.frequency, RecurrenceFrequency::Weekly);
+        assert_eq!(recurrence.interval, 2);
+        assert!(recurrence.end_date.is_some());
+
+        let data = crate::miscs::arg_parsing::parse_data(
+            "frequency: daily, start-date: 2026-10-20, start-time: 2026-10-21",
+        )
+        .unwrap();
+        assert_eq!(
+            Recurrence::from_data(&data).unwrap_err(),
+            "recurrence fields 'start-date' and 'start-time' are the same, give only one"
+        );
+    }
+
+    #[test]
+    fn huge_intervals_end_the_series() {
+        let start = at("2026-10-20 14:00");
+        let (from, to) = (at("2026-10-01"), at("2030-01-01"));
+        for (frequency, interval) in [
+            (RecurrenceFrequency::Hourly, 9_000_000_000_000_000),
+            (RecurrenceFrequency::Daily, 400_000_000_000),
+            (RecurrenceFrequency::Weekly, i64::MAX),
+            (RecurrenceFrequency::Monthly, 5_000_000_000),
+            (RecurrenceFrequency::Yearly, 400_000_000),
+        ] {
+            let recurrence = Recurrence {
+                frequency,
+                interval,
+                ..weekly("2026-10-20 14:00", None)
+            };
+            assert_eq!(recurrence.occurrences(start, from, to, None), [start]);
+            // Far past the start, skipping ahead must not overflow either
+            assert!(recurrence
+                .occurrences(start, at("2029-01-01"), to, None)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn yearly_series_count_whole_years() {
+        let recurrence = Recurrence {
+            frequency: RecurrenceFrequency::Yearly,
+            interval: 2,
+            minute: Some(0),
+            hour: Some(10),
+            day: Some(5),
+            month: Some(3),
+            ..weekly("2026-03-05 10:00", None)
+        };
+        assert!(recurrence.is_now(at("2026-03-05 10:00"), None));
+        assert!(!recurrence.is_now(at("2027-03-05 10:00"), None));
+        // 2028 is a leap year, the day count since the start isn't a multiple of anything
+        assert!(recurrence.is_now(at("2028-03-05 10:00"), None));
+        assert!(!recurrence.is_now(at("2028-03-05 10:01"), None));
+        assert!(!recurrence.is_now(at("2024-03-05 10:00"), None));
+    }
+
+    #[test]
+    fn monthly_series_fire_once_they_have_started() {
+        let recurrence = Recurrence {
+            frequency: RecurrenceFrequency::Monthly,
+            interval: 2,
+            minute: Some(0),
+            hour: Some(10),
+            day: Some(5),
+            ..weekly("2026-03-05 10:00", None)
+        };
+        assert!(recurrence.is_now(at("2026-03-05 10:00"), None));
+        assert!(!recurrence.is_now(at("2026-04-05 10:00"), None));
+        assert!(recurrence.is_now(at("2026-05-05 10:00"), None));
+        assert!(recurrence.is_now(at("2027-01-05 10:00"), None));
+        assert!(!recurrence.is_now(at("2026-01-05 10:00"), None));
+    }
+
+    #[test]
+    fn zoned_series_keep_their_wall_time_across_dst() {
+        let zone = Some(chrono_tz::Europe::Berlin);
+        let berlin = |input: &str| {
+            zoned_from_naive(
+                NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap(),
+                zone,
+            )
+            .unwrap()
+        };
+        // Berlin leaves summer time on 2026-10-25
+        let start = berlin("2026-10-20 14:00");
+        let recurrence = Recurrence {
+            start_date: start,
+            minute: Some(0),
+            hour: Some(14),
+            week_day: Some(Weekday::Tue),
+            ..weekly("2026-10-20 14:00", None)
+        };
+        let times: Vec<_> = recurrence
+            .occurrences(start, start, berlin("2026-11-04 00:00"), zone)
+            .into_iter()
+            .map(|time| wall_time(time, zone))
+            .collect();
+        assert_eq!(
+            times,
+            ["2026-10-20 14:00", "2026-10-27 14:00", "2026-11-03 14:00"]
+                .map(|input| { NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap() })
+        );
+        assert!(recurrence.is_now(berlin("2026-10-27 14:00"), zone));
+        assert!(!recurrence.is_now(berlin("2026-10-27 13:00"), zone));
+    }
+
+    #[test]
+    fn all_day_events_cover_whole_days() {
+        let mut event = event().set_is_all_day(true);
+        event.end_time = at("2026-10-22 09:00");
+        event.snap_all_day().unwrap();
+        assert_eq!(event.start_time, at("2026-10-20"));
+        assert_eq!(event.end_time, at("2026-10-23"));
+        assert_eq!(
+            event.all_day_dates(),
+            (
+                date_from_str("2026-10-20").unwrap(),
+                date_from_str("2026-10-22").unwrap()
+            )
+        );
+
+        // Ending at midnight, the last day is the one before
+        let mut event = event.set_end_time(at("2026-10-21"));
+        event.snap_all_day().unwrap();
+        assert_eq!(event.end_time, at("2026-10-21"));
+    }
+
+    #[test]
+    fn day_reminders_fire_at_a_wall_clock_time() {
+        let start = at("2026-10-20 14:00");
+        let reminder = |days_before| Notification {
+            on_day: Some(DayReminder {
+                days_before,
+                time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(reminder(1).due_time(start, None), at("2026-10-19 18:00"));
+        assert_eq!(reminder(0).due_time(start, None), at("2026-10-20 18:00"));
+        let minutes = Notification {
+            notify_before: 90,
+            ..Default::default()
+        };
+        assert_eq!(minutes.due_time(start, None), at("2026-10-20 12:30"));
+
+        let event = event().set_notification_settings(vec![reminder(1)]);
+        assert_eq!(
+            event.is_time_to_notify(at("2026-10-19 17:59")),
+            [(0, false)]
+        );
+        assert_eq!(event.is_time_to_notify(at("2026-10-19 18:00")), [(0, true)]);
+    }
+
+    #[test]
+    fn priorities_and_tags() {
+        assert_eq!(Priority::parse(" High "), Ok(Priority::High));
+        assert_eq!(
+            Priority::parse("urgnet").unwrap_err(),
+            "unknown priority 'urgnet', did you mean 'urgent'?"
+        );
+        let filter = PriorityFilter::parse("high+").unwrap();
+        assert!(filter.matches(Priority::Urgent) && !filter.matches(Priority::Normal));
+        let filter = PriorityFilter::parse("high").unwrap();
+        assert!(filter.matches(Priority::High) && !filter.matches(Priority::Urgent));
+
+        let mut event = event();
+        event.set_field("tags", "travel, q3, travel").unwrap();
+        assert_eq!(event.tags, ["travel", "q3"]);
+        event.set_field("priority", "urgent").unwrap();
+        assert_eq!(event.priority, Priority::Urgent);
+        assert!(event.set_field("priority", "asap").is_err());
+
+        let words: Vec<String> = "Flight +travel +q3 +travel ^high tomorrow 9:00"
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        let event = Event::from_quick_add(&words, at("2026-10-14 08:00"), Duration::hours(2), None)
+            .unwrap();
+        assert_eq!(event.tags, ["travel", "q3"]);
+        assert_eq!(event.priority, Priority::High);
+    }
+}
@@ -1,36 +1,107 @@
+use chrono::{DateTime, Duration, Local};
 use futures::channel::mpsc::{channel, Receiver};
 use futures::{SinkExt, StreamExt};
 use notify::{Config, RecommendedWatcher};
 use notify::{Event as NotifyEvent, RecursiveMode, Watcher};
+use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-use crate::miscs::arg_parsing::parse_data;
+use crate::miscs::arg_parsing::{did_you_mean, parse_data};
+use crate::miscs::natural::parse_datetime;
+use crate::miscs::utils::parse_time_zone;
 
-use super::event::Event;
+use super::category::recategorized;
+use super::conflicts::{conflicts_between, conflicts_with, free_slots, Conflict, WorkingHours};
+use super::contact::{relinked, Contact};
+use super::event::{Event, Priority, STORED_FIELDS};
+use super::query::{FullTextHits, Query};
+use super::search_index::SearchIndex;
+use super::validation::check_event;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchType {
     Title,
     Description,
     Location,
     Category,
+    Tag,
     Attendee,
+    Priority,
     Date,
-    FullText,
+    FullText, // All of the text fields above but the priority
 }
 
 impl SearchType {
-    pub fn from(s: &str) -> SearchType {
+    pub const NAMES: [&str; 9] = [
+        "title",
+        "description",
+        "location",
+        "category",
+        "tag",
+        "attendee",
+        "priority",
+        "date",
+        "fulltext",
+    ];
+
+    pub fn from(s: &str) -> Option<SearchType> {
         match s {
-            "title" => SearchType::Title,
-            "description" => SearchType::Description,
-            "location" => SearchType::Location,
-            "category" => SearchType::Category,
-            "attendee" => SearchType::Attendee,
-            "date" => SearchType::Date,
-            "fulltext" => SearchType::FullText,
-            _ => SearchType::Title,
+            "title" => Some(SearchType::Title),
+            "description" => Some(SearchType::Description),
+            "location" => Some(SearchType::Location),
+            "category" | "categories" => Some(SearchType::Category),
+            "tag" | "tags" => Some(SearchType::Tag),
+            "attendee" | "attendees" => Some(SearchType::Attendee),
+            "priority" => Some(SearchType::Priority),
+            "date" => Some(SearchType::Date),
+            "fulltext" | "text" => Some(SearchType::FullText),
+            _ => None,
+        }
+    }
+
+    // The texts of an event that a search in this field looks at
+    pub fn texts(&self, event: &Event) -> Vec<String> {
+        match self {
+            SearchType::Title => vec![event.title.clone()],
+            SearchType::Description => vec![event.description.clone()],
+            SearchType::Location => vec![event.location.clone()],
+            SearchType::Category => event.categories.clone(),
+            SearchType::Tag => event.tags.clone(),
+            SearchType::Attendee => event
+                .attendees
+                .iter()
+                .flat_map(|attendee| [attendee.name.clone(), attendee.email.clone()])
+                .collect(),
+            SearchType::Priority => vec![event.priority.to_string()],
+            SearchType::Date => vec![event.start_time.format("%Y-%m-%d %H:%M").to_string()],
+            SearchType::FullText => [
+                SearchType::Title,
+                SearchType::Description,
+                SearchType::Location,
+                SearchType::Category,
+                SearchType::Tag,
+                SearchType::Attendee,
+            ]
+            .iter()
+            .flat_map(|field| field.texts(event))
+            .collect(),
+        }
+    }
+
+    // What results are ordered by, the first category, tag or attendee for the lists
+    fn sort_key(&self, event: &Event) -> String {
+        match self {
+            SearchType::Date => event.start_time.to_rfc3339(),
+            // Most important first
+            SearchType::Priority => (Priority::Urgent as u8 - event.priority as u8).to_string(),
+            SearchType::FullText => event.title.to_lowercase(),
+            _ => self
+                .texts(event)
+                .first()
+                .map(|text| text.to_lowercase())
+                .unwrap_or_default(),
         }
     }
 }
@@ -48,6 +119,11 @@ pub struct EventManager {
     events: Vec<Event>,
 
     mode: EventManagerMode,
+
+    invalid_count: usize, // Events of the last loaded file that fail validation
+    report_invalid: bool, // Print a note about them to stderr after loading
+
+    index: SearchIndex, // Kept in step with `events` by every method changing them
 }
 
 impl EventManager {
@@ -66,12 +142,15 @@ impl EventManager {
             auto_save,
             events: Vec::new(),
             mode,
+            invalid_count: 0,
+            report_invalid: true,
+            index: SearchIndex::default(),
         }));
 
         event_manager.lock().unwrap().read_events_from_file();
 
         //if let EventManagerMode::Passive = event_manager.lock().unwrap().mode {
-        println!("Monitoring file: {:?}", file_path);
+        eprintln!("Monitoring file: {:?}", file_path);
         EventManager::monitor_file(event_manager.clone(), file_path);
         //}
 
@@ -82,7 +161,7 @@ impl EventManager {
         std::thread::spawn(move || {
             futures::executor::block_on(async {
                 if let Err(e) = async_watch(event_manager, file_path).await {
-                    println!("error: {:?}", e)
+                    eprintln!("error: {:?}", e)
                 }
             });
         });
@@ -96,58 +175,152 @@ impl EventManager {
 
             if let Ok(Some(events)) = serde_json::from_str(&data) {
                 self.events = events;
+                self.index = SearchIndex::build(&self.events);
+            }
+            // The file may have been edited by hand, so point out events that wouldn't pass `add`
+            self.invalid_count = self
+                .events
+                .iter()
+                .filter(|event| check_event(event).is_err())
+                .count();
+            if self.invalid_count > 0 && self.report_invalid {
+                eprintln!(
+                    "{}: {} invalid events, run `check` for details",
+                    self.file_path.display(),
+                    self.invalid_count
+                );
             }
         }
     }
 
-    pub fn save_events(&self) {
-        let json_string = serde_json::to_string(&self.events).expect("Failed to convert to JSON");
+    pub fn invalid_count(&self) -> usize {
+        self.invalid_count
+    }
 
-        if let Err(e) = fs::write(&self.file_path, json_string) {
-            eprintln!("Failed to save file: {}", e);
-        } else {
-            println!("Events saved successfully.");
-        }
+    pub fn set_report_invalid(&mut self, report_invalid: bool) {
+        self.report_invalid = report_invalid;
     }
 
-    pub fn list_events(&self) {
-        println!("Events:");
-        for (index, event) in self.events.iter().enumerate() {
-            println!("{}: {}", index + 1, event);
-        }
+    pub fn save_events(&self) -> Result<(), String> {
+        let json_string = serde_json::to_string(&self.events).map_err(|e| e.to_string())?;
+
+        fs::write(&self.file_path, json_string)
+            .map_err(|e| format!("Failed to save file {}: {}", self.file_path.display(), e))
     }
 
     pub fn clear(&mut self) {
         if EventManagerMode::Active == self.mode {
             self.events.clear();
+            self.index = SearchIndex::default();
             if self.auto_save {
-                self.save_events();
+                if let Err(e) = self.save_events() {
+                    eprintln!("{}", e);
+                }
             }
         } else {
-            println!("Cannot clear events in Passive mode.");
+            eprintln!("Cannot clear events in Passive mode.");
+        }
+    }
+
+    pub fn get_event(&self, x: usize) -> Option<&Event> {
+        self.events.get(x)
+    }
+
+    // Replaces the event at `x` and updates the search index, false if there is none
+    pub fn replace_event(&mut self, x: usize, event: Event) -> bool {
+        let Some(slot) = self.events.get_mut(x) else {
+            return false;
+        };
+        self.index.replace(x, &event);
+        *slot = event;
+        true
+    }
+
+    // Resolves an event reference, either a 1-based index as shown by `list` or an event ID
+    pub fn find_event(&self, reference: &str) -> Option<usize> {
+        let reference = reference.trim();
+        if let Ok(index) = reference.parse::<usize>() {
+            return index.checked_sub(1).filter(|i| *i < self.events.len());
+        }
+        self.events
+            .iter()
+            .position(|event| event.event_id == reference)
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // Moves every event in category `from` or its subcategories to `to`, or takes them out of
+    // it without `to`. Returns how many events changed.
+    pub fn recategorize(&mut self, from: &str, to: Option<&str>) -> usize {
+        let mut changed = 0;
+        for index in 0..self.events.len() {
+            if let Some(categories) = recategorized(&self.events[index].categories, from, to) {
+                let mut event = self.events[index].clone();
+                event.categories = categories;
+                event.updated_at = Local::now();
+                self.replace_event(index, event);
+                changed += 1;
+            }
         }
+        changed
     }
 
-    pub fn get_event(&mut self, x: usize) -> Option<&Event> {
-        Some(&self.events[x])
+    // Gives the attendees linked to a contact its new name and email, or unlinks them when
+    // `contact` is None because it was removed. Returns how many events changed.
+    pub fn relink_contact(&mut self, contact_id: &str, contact: Option<&Contact>) -> usize {
+        let mut changed = 0;
+        for index in 0..self.events.len() {
+            if let Some(attendees) = relinked(&self.events[index].attendees, contact_id, contact) {
+                let mut event = self.events[index].clone();
+                event.attendees = attendees;
+                event.updated_at = Local::now();
+                self.replace_event(index, event);
+                changed += 1;
+            }
+        }
+        changed
     }
 
-    pub fn get_event_mut(&mut self, x: usize) -> Option<&mut Event> {
-        Some(&mut self.events[x])
+    // Overlapping busy events in [from, to), see conflicts.rs
+    pub fn conflicts_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<Conflict> {
+        conflicts_between(&self.events, from, to)
     }
 
+    // Events `event` would overlap, `skip` being its own index if it is already in the list
+    pub fn conflicts_with(&self, event: &Event, skip: Option<usize>) -> Vec<Conflict> {
+        conflicts_with(&self.events, event, skip)
+    }
+
+    // Open slots of at least `length` within the working hours, see conflicts.rs
+    pub fn free_slots(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        length: Duration,
+        hours: &WorkingHours,
+        blocks: impl Fn(&Event) -> bool,
+    ) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        free_slots(&self.events, from, to, length, hours, blocks)
+    }
+
+    // Only for fields that aren't searched, like the notification state, see replace_event
     pub fn iter_events_mut(&mut self) -> impl Iterator<Item = &mut Event> {
         self.events.iter_mut()
     }
 
     pub fn add_event(&mut self, mut event: Event) -> isize {
         if EventManagerMode::Active == self.mode {
-            if event.event_id.is_empty() {
-                event.event_id = format!("#{}", self.events.len() + 1);
+            if event.event_id.is_empty() || self.find_event(&event.event_id).is_some() {
+                event.event_id = self.next_event_id();
             }
+            self.index.push(&event);
             self.events.push(event);
             if self.auto_save {
-                self.save_events();
+                if let Err(e) = self.save_events() {
+                    eprintln!("{}", e);
+                }
             }
             (self.events.len() - 1) as isize
         } else {
@@ -155,73 +328,105 @@ impl EventManager {
         }
     }
 
+    // IDs are "#<n>", counting up from the highest one in use so removed IDs are never reused
+    fn next_event_id(&self) -> String {
+        let highest = self
+            .events
+            .iter()
+            .filter_map(|event| event.event_id.strip_prefix('#')?.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("#{}", highest.max(self.events.len()) + 1)
+    }
+
     pub fn remove_event(&mut self, x: usize) -> Option<Event> {
         if x < self.events.len() {
+            self.index.remove(x);
             Some(self.events.remove(x))
         } else {
             None
         }
     }
 
-    pub fn add_event_from_str(&mut self, string: &str) -> isize {
-        let data = parse_data(string, 0);
-        data.print(0);
-        let event = Event::from_data(data);
-        match event {
-            Ok(e) => {
-                // println!("{}", e);
-                self.add_event(e)
-            }
-            Err(_e) => {
-                // eprintln!("{}", e);
-                -1
-            }
-        }
+    pub fn event_from_str(string: &str, default_length: Duration) -> Result<Event, String> {
+        Event::from_data(parse_data(string)?, default_length)
     }
 
-    #[allow(dead_code)]
-    pub fn sort_events_by(&mut self, sort_by: SearchType) -> Vec<Event> {
-        let mut result: Vec<Event> = self.events.clone();
-        match sort_by {
-            SearchType::Title => result.sort_by(|a, b| a.title.cmp(&b.title)),
-            SearchType::Date => result.sort_by(|a, b| a.start_time.cmp(&b.start_time)),
-            SearchType::Location => result.sort_by(|a, b| a.location.cmp(&b.location)),
-            _ => todo!(),
-        }
-        result
+    // Events from a JSON or YAML document holding one event or a list of them. Times may also
+    // be written like "2026-10-20 14:00", a missing end_time means `length` after the start.
+    pub fn events_from_document(
+        input: &str,
+        yaml: bool,
+        length: Duration,
+    ) -> Result<Vec<Event>, String> {
+        let document: Value = if yaml {
+            serde_yaml::from_str(input).map_err(|e| e.to_string())?
+        } else {
+            serde_json::from_str(input).map_err(|e| e.to_string())?
+        };
+        let values = match document {
+            Value::Array(values) => values,
+            Value::Object(_) => vec![document],
+            _ => return Err("expected an event or a list of events".to_string()),
+        };
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                event_from_value(value, length).map_err(|e| format!("event {}: {}", i + 1, e))
+            })
+            .collect()
     }
 
-    pub fn search_event(&self, search_string: &str, search_type: SearchType) -> Vec<&Event> {
-        let mut result: Vec<&Event> = Vec::new();
-        for event in self.events.iter() {
-            match search_type {
-                SearchType::Title => {
-                    if event.title.contains(search_string) {
-                        result.push(event);
-                    }
-                }
-                SearchType::Date => {
-                    if event
-                        .start_time
-                        .format("%Y-%m-&d %H:%M")
-                        .to_string()
-                        .contains(search_string)
-                    {
-                        result.push(event);
-                    }
-                }
-                SearchType::FullText => {
-                    if serde_json::to_string(event)
-                        .expect("Failed to convert to JSON")
-                        .contains(search_string)
-                    {
-                        result.push(event);
-                    }
-                }
-                _ => todo!(),
+    // Every occurrence overlapping [from, to) sorted by start time, each as a copy of its
+    // event moved to the occurrence, together with the event's index in the event list
+    pub fn occurrences_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Vec<(usize, Event)> {
+        let mut result: Vec<(usize, Event)> = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            for (start_time, end_time) in event.occurrences_between(from, to) {
+                let mut occurrence = event.clone();
+                occurrence.start_time = start_time;
+                occurrence.end_time = end_time;
+                result.push((index, occurrence));
             }
         }
+        result.sort_by_key(|(_, event)| event.start_time);
+        result
+    }
+
+    // Sorts by the field case-insensitively, events with the same value by start time
+    pub fn sort_events_by(events: &mut [(usize, &Event)], sort_by: SearchType) {
+        events.sort_by_cached_key(|(_, event)| (sort_by.sort_key(event), event.start_time));
+    }
+
+    // Returns the matching events together with their index in the event list, the best
+    // full-text matches first and otherwise in list order
+    pub fn search_event(&self, query: &Query) -> Vec<(usize, &Event)> {
+        let hits: FullTextHits = query
+            .full_text()
+            .into_iter()
+            .map(|text| {
+                (
+                    text.to_string(),
+                    self.index.search(text).into_iter().collect(),
+                )
+            })
+            .collect();
+        let mut result: Vec<(f32, usize, &Event)> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter_map(|(index, event)| Some((query.score(index, event, &hits)?, index, event)))
+            .collect();
+        result.sort_by(|a, b| b.0.total_cmp(&a.0));
         result
+            .into_iter()
+            .map(|(_, index, event)| (index, event))
+            .collect()
     }
 }
 
@@ -257,9 +462,98 @@ async fn async_watch(event_manager: Arc<Mutex<EventManager>>, path: PathBuf) ->
                     event_manager.lock().unwrap().read_events_from_file();
                 }
             }
-            Err(e) => println!("watch error: {:?}", e),
+            Err(e) => eprintln!("watch error: {:?}", e),
         }
     }
 
     Ok(())
 }
+
+fn event_from_value(mut value: Value, length: Duration) -> Result<Event, String> {
+    let Some(fields) = value.as_object_mut() else {
+        return Err("expected an object with the event's fields".to_string());
+    };
+    // Unknown keys would otherwise be dropped silently by serde
+    if let Some(key) = fields
+        .keys()
+        .find(|key| !STORED_FIELDS.contains(&key.as_str()))
+    {
+        return Err(match did_you_mean(key, &STORED_FIELDS) {
+            Some(suggestion) => format!("unknown field '{}', did you mean '{}'?", key, suggestion),
+            None => format!("unknown field '{}'", key),
+        });
+    }
+    // Times without an offset are wall-clock times in the event's zone
+    let zone = match fields.get("time_zone") {
+        Some(Value::String(name)) => parse_time_zone(name)?,
+        _ => None,
+    };
+    let now = Local::now();
+    for key in ["start_time", "end_time"] {
+        if let Some(Value::String(text)) = fields.get(key) {
+            if DateTime::parse_from_rfc3339(text).is_err() {
+                let time =
+                    parse_datetime(text, now, zone).map_err(|e| format!("{}: {}", key, e))?;
+                fields.insert(key.to_string(), Value::String(time.to_rfc3339()));
+            }
+        }
+    }
+    let has_end = fields.contains_key("end_time");
+
+    let mut event: Event = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    if !has_end {
+        event.end_time = event.start_time + length;
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miscs::natural::parse_datetime;
+
+    fn at(input: &str) -> DateTime<Local> {
+        parse_datetime(input, Local::now(), None).unwrap()
+    }
+
+    #[test]
+    fn reads_json_and_yaml_documents() {
+        let json = r#"{"title": "Lunch", "start_time": "2026-10-20 12:00", "location": "Cafe"}"#;
+        let events = EventManager::events_from_document(json, false, Duration::hours(1)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].location, "Cafe");
+        assert_eq!(events[0].start_time, at("2026-10-20 12:00"));
+        assert_eq!(events[0].end_time, at("2026-10-20 13:00"));
+
+        let yaml = "
+- title: Standup
+  start_time: 2026-10-20T09:00:00+00:00
+  end_time: 2026-10-20T09:15:00+00:00
+- title: Review
+  start_time: 2026-10-21 15:00
+  categories: [work]
+";
+        let events = EventManager::events_from_document(yaml, true, Duration::hours(1)).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].end_time - events[0].start_time,
+            Duration::minutes(15)
+        );
+        assert_eq!(events[1].categories, ["work"]);
+    }
+
+    #[test]
+    fn rejects_documents_it_cannot_use() {
+        let error = |input: &str| {
+            EventManager::events_from_document(input, false, Duration::hours(1)).unwrap_err()
+        };
+        assert_eq!(error("3"), "expected an event or a list of events");
+        assert_eq!(
+            error(r#"[{"title": "A", "start_time": "2026-10-20 12:00"}, {"titel": "B"}]"#),
+            "event 2: unknown field 'titel', did you mean 'title'?"
+        );
+        assert!(error(r#"{"title": "A", "start_time": "someday"}"#)
+            .starts_with("event 1: start_time: "));
+        assert!(error(r#"{"title": "A", "#).contains("EOF"));
+    }
+}
@@ -0,0 +1,771 @@
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+use super::contact::contact_with_email;
+use super::event::{
+    parse_weekday, Attendee, Event, ParticipationStatus, Priority, Recurrence, RecurrenceFrequency,
+    Role, Transparency, MAX_INTERVAL,
+};
+use crate::miscs::natural::pinned_recurrence;
+use crate::miscs::utils::{
+    local_from_naive, parse_time_zone, wall_time, zoned_from_naive, MAX_DURATION_DAYS,
+};
+
+const PRODID: &str = "-//RustyPlanner//RustyPlanner//EN";
+
+// The events of an iCalendar file and its METHOD, e.g. REQUEST or REPLY for invitations
+#[derive(Debug)]
+pub struct Calendar {
+    pub method: Option<String>,
+    pub events: Vec<Event>,
+}
+
+// A VCALENDAR with a VEVENT for each event, the times in UTC
+pub fn write_calendar(events: &[&Event], method: Option<&str>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+    ];
+    if let Some(method) = method {
+        lines.push(format!("METHOD:{}", method));
+    }
+    let now = Local::now();
+    for event in events {
+        write_event(&mut lines, event, method, now);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+fn write_event(lines: &mut Vec<String>, event: &Event, method: Option<&str>, now: DateTime<Local>) {
+    lines.push("BEGIN:VEVENT".to_string());
+    lines.push(format!("UID:{}", event.uid()));
+    lines.push(format!("DTSTAMP:{}", utc(now)));
+    if event.sequence > 0 {
+        lines.push(format!("SEQUENCE:{}", event.sequence));
+    }
+    if method == Some("CANCEL") {
+        lines.push("STATUS:CANCELLED".to_string());
+    }
+    lines.push(format!("CREATED:{}", utc(event.created_at)));
+    lines.push(format!("LAST-MODIFIED:{}", utc(event.updated_at)));
+    if event.is_all_day {
+        let (first, last) = event.all_day_dates();
+        lines.push(format!("DTSTART;VALUE=DATE:{}", first.format("%Y%m%d")));
+        let after = last + Duration::days(1);
+        lines.push(format!("DTEND;VALUE=DATE:{}", after.format("%Y%m%d")));
+    } else {
+        lines.push(format!("DTSTART:{}", utc(event.start_time)));
+        lines.push(format!("DTEND:{}", utc(event.end_time)));
+    }
+    lines.push(format!("SUMMARY:{}", escape(&event.title)));
+    if !event.description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&event.description)));
+    }
+    if !event.location.is_empty() {
+        lines.push(format!("LOCATION:{}", escape(&event.location)));
+    }
+    if !event.categories.is_empty() {
+        let categories: Vec<String> = event.categories.iter().map(|c| escape(c)).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    // 1 is the highest, 9 the lowest and 0 undefined
+    match event.priority {
+        Priority::Urgent => lines.push("PRIORITY:1".to_string()),
+        Priority::High => lines.push("PRIORITY:3".to_string()),
+        Priority::Normal => {}
+        Priority::Low => lines.push("PRIORITY:9".to_string()),
+    }
+    match event.transparency {
+        Some(Transparency::Busy) => lines.push("TRANSP:OPAQUE".to_string()),
+        Some(Transparency::Free) => lines.push("TRANSP:TRANSPARENT".to_string()),
+        None => {}
+    }
+    if let (Some(recurrence), true) = (&event.recurrence, event.is_recurring) {
+        let frequency = format!("{:?}", recurrence.frequency).to_uppercase();
+        let mut rule = format!("RRULE:FREQ={}", frequency);
+        if recurrence.interval > 1 {
+            rule += &format!(";INTERVAL={}", recurrence.interval);
+        }
+        if let Some(end_date) = recurrence.end_date {
+            // The last start there may be, the end date is the first there may not
+            rule += &format!(";UNTIL={}", utc(end_date - Duration::seconds(1)));
+        }
+        if !recurrence.week_days.is_empty() {
+            let days: Vec<String> = recurrence
+                .week_days
+                .iter()
+                .map(|day| day.to_string()[..2].to_uppercase())
+                .collect();
+            rule += &format!(";BYDAY={}", days.join(","));
+        }
+        lines.push(rule);
+    }
+    if let Some(organizer) = &event.organizer {
+        lines.push(person("ORGANIZER", organizer, false));
+    }
+    for attendee in &event.attendees {
+        lines.push(person("ATTENDEE", attendee, true));
+    }
+    lines.push("END:VEVENT".to_string());
+}
+
+// ORGANIZER or ATTENDEE with the name, and for attendees their role and answer
+fn person(property: &str, person: &Attendee, with_answer: bool) -> String {
+    let mut line = property.to_string();
+    // Someone known by their email alone has no name to give
+    if !person.name.is_empty() && person.name != person.email {
+        line += &format!(";CN={}", param_value(&person.name));
+    }
+    if with_answer {
+        line += &format!(
+            ";ROLE={};PARTSTAT={}",
+            person.role.ical(),
+            person.status.ical()
+        );
+        if person.status == ParticipationStatus::NeedsAction {
+            line += ";RSVP=TRUE";
+        }
+    }
+    format!("{}:mailto:{}", line, person.email)
+}
+
+fn utc(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+// Parameter values with ':', ';' or ',' are quoted, quotes can't be escaped so they are dropped
+fn param_value(value: &str) -> String {
+    let value = value.replace('"', "");
+    match value.contains([':', ';', ',']) {
+        true => format!("\"{}\"", value),
+        false => value,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+// Lines are at most 75 bytes, longer ones continue on lines starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+// A content line like `ATTENDEE;CN="Smith, Alice";PARTSTAT=ACCEPTED:mailto:alice@example.org`
+// split into its upper-case name, parameters and value
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // Colons and semicolons inside quoted parameter values don't count
+        let mut quoted = false;
+        let mut parts = vec![];
+        let mut current = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => parts.push(std::mem::take(&mut current)),
+                ':' if !quoted => {
+                    parts.push(current);
+                    let mut parts = parts.into_iter();
+                    let name = parts.next()?.to_uppercase();
+                    let params = parts
+                        .filter_map(|param| {
+                            let (key, value) = param.split_once('=')?;
+                            Some((key.to_uppercase(), value.to_string()))
+                        })
+                        .collect();
+                    return Some(Self {
+                        name,
+                        params,
+                        value: chars.collect(),
+                    });
+                }
+                c => current.push(c),
+            }
+        }
+        None
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn text(&self) -> String {
+        unescape(&self.value)
+    }
+
+    // A DATE, a DATE-TIME in UTC, in a TZID or floating in the local zone. The bool tells
+    // whether it was a date alone.
+    fn time(&self) -> Result<(DateTime<Local>, Option<Tz>, bool), String> {
+        let value = self.value.trim();
+        if self.param("VALUE") == Some("DATE") || value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map_err(|_| format!("{}: '{}' is not a date", self.name, value))?;
+            return Ok((local_from_naive(date.and_time(NaiveTime::MIN))?, None, true));
+        }
+        let (value, is_utc) = match value.strip_suffix('Z') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .map_err(|_| format!("{}: '{}' is not a date and time", self.name, value))?;
+        if is_utc {
+            return Ok((naive.and_utc().with_timezone(&Local), None, false));
+        }
+        // Zones other programs name differently, like Windows ones, are taken as local
+        let zone = self
+            .param("TZID")
+            .and_then(|zone| parse_time_zone(zone.trim_matches('"')).ok())
+            .flatten();
+        Ok((zoned_from_naive(naive, zone)?, zone, false))
+    }
+
+    // ORGANIZER or ATTENDEE, linked to the contact with that email
+    fn person(&self) -> Attendee {
+        let value = self.value.trim();
+        let email = match value.get(..7) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &value[7..],
+            _ => value,
+        };
+        let name = self
+            .param("CN")
+            .map(|name| name.trim_matches('"').to_string())
+            .unwrap_or_else(|| email.to_string());
+        Attendee {
+            attendee_id: contact_with_email(email)
+                .map(|contact| contact.contact_id)
+                .unwrap_or_default(),
+            name,
+            email: email.to_string(),
+            role: self.param("ROLE").map(Role::from_ical).unwrap_or_default(),
+            status: self
+                .param("PARTSTAT")
+                .map(ParticipationStatus::from_ical)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// The events of an iCalendar file. Alarms, to-dos and time zone definitions are skipped.
+pub fn parse_calendar(text: &str) -> Result<Calendar, String> {
+    // Long lines are folded onto following lines that start with a space or tab
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut method = None;
+    let mut events = vec![];
+    let mut found_calendar = false;
+    let mut components: Vec<String> = vec![]; // BEGIN/END nesting, e.g. VCALENDAR, VEVENT
+    let mut properties: Vec<Property> = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = Property::parse(line)
+            .ok_or_else(|| format!("line {}: expected NAME:value", number + 1))?;
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_uppercase();
+                found_calendar |= component == "VCALENDAR";
+                components.push(component);
+            }
+            "END" => {
+                let component = property.value.trim().to_uppercase();
+                if components.pop().as_deref() != Some(component.as_str()) {
+                    return Err(format!("line {}: unexpected END:{}", number + 1, component));
+                }
+                if component == "VEVENT" {
+                    let event = event_from(&properties)
+                        .map_err(|e| format!("event ending on line {}: {}", number + 1, e))?;
+                    events.push(event);
+                    properties.clear();
+                }
+            }
+            "METHOD" if components.last().map(String::as_str) == Some("VCALENDAR") => {
+                method = Some(property.value.trim().to_uppercase());
+            }
+            _ if components.last().map(String::as_str) == Some("VEVENT") => {
+                properties.push(property)
+            }
+            _ => {}
+        }
+    }
+    if !found_calendar {
+        return Err("no BEGIN:VCALENDAR found, this is not an iCalendar file".to_string());
+    }
+    if let Some(component) = components.last() {
+        return Err(format!("BEGIN:{} has no END", component));
+    }
+    Ok(Calendar { method, events })
+}
+
+fn event_from(properties: &[Property]) -> Result<Event, String> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+    let start = find("DTSTART").ok_or("no DTSTART")?;
+    let (start_time, zone, is_all_day) = start.time()?;
+    let end_time = match (find("DTEND"), find("DURATION")) {
+        (Some(end), _) => end.time()?.0,
+        (None, Some(duration)) => start_time
+            .checked_add_signed(parse_ical_duration(&duration.value)?)
+            .ok_or_else(|| format!("the duration '{}' is too long", duration.value.trim()))?,
+        // A date alone lasts the day, a time alone takes no time
+        (None, None) if is_all_day => start_time + Duration::days(1),
+        (None, None) => start_time,
+    };
+
+    let text = |name: &str| find(name).map(Property::text).unwrap_or_default();
+    let mut event = Event {
+        uid: text("UID"),
+        title: text("SUMMARY"),
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        start_time,
+        end_time,
+        time_zone: zone,
+        is_all_day,
+        ..Default::default()
+    };
+    for property in properties {
+        match property.name.as_str() {
+            "CATEGORIES" => {
+                event.categories.extend(
+                    split_unescaped(&property.value)
+                        .into_iter()
+                        .filter(|category| !category.is_empty()),
+                );
+            }
+            "ATTENDEE" => event.attendees.push(property.person()),
+            "ORGANIZER" => event.organizer = Some(property.person()),
+            _ => {}
+        }
+    }
+    event.priority = match find("PRIORITY").map(|priority| priority.value.trim()) {
+        Some("1") => Priority::Urgent,
+        Some("2" | "3" | "4") => Priority::High,
+        Some("6" | "7" | "8" | "9") => Priority::Low,
+        _ => Priority::Normal,
+    };
+    event.transparency = match find("TRANSP").map(|transp| transp.value.trim().to_uppercase()) {
+        Some(transp) if transp == "TRANSPARENT" => Some(Transparency::Free),
+        Some(transp) if transp == "OPAQUE" => Some(Transparency::Busy),
+        _ => None,
+    };
+    if let Some(sequence) = find("SEQUENCE") {
+        event.sequence = sequence.value.trim().parse().unwrap_or(0);
+    }
+    if let Some(created) = find("CREATED").and_then(|created| created.time().ok()) {
+        event.created_at = created.0;
+    }
+    if let Some(rule) = find("RRULE") {
+        let mut recurrence = parse_rule(&rule.value, start_time, zone)?;
+        recurrence.start_date = start_time;
+        event.recurrence = Some(recurrence);
+        event.is_recurring = true;
+    }
+    event.snap_all_day()?;
+    Ok(event)
+}
+
+// FREQ, INTERVAL, UNTIL, COUNT and plain weekdays in BYDAY. Other parts that would change which
+// days the event is on can't be represented and are rejected.
+fn parse_rule(rule: &str, start: DateTime<Local>, zone: Option<Tz>) -> Result<Recurrence, String> {
+    let unsupported = |part: &str| format!("RRULE: {} is not supported", part.trim());
+    let wall = wall_time(start, zone);
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut until = None;
+    let mut count: Option<usize> = None;
+    let mut week_days: Vec<Weekday> = vec![];
+    for part in rule.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.trim().to_uppercase().as_str() {
+                    "HOURLY" => RecurrenceFrequency::Hourly,
+                    "DAILY" => RecurrenceFrequency::Daily,
+                    "WEEKLY" => RecurrenceFrequency::Weekly,
+                    "MONTHLY" => RecurrenceFrequency::Monthly,
+                    "YEARLY" => RecurrenceFrequency::Yearly,
+                    other => return Err(format!("RRULE: FREQ={} is not supported", other)),
+                })
+            }
+            "INTERVAL" => {
+                interval = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("RRULE: '{}' is not an interval", value))?;
+                if !(1..=MAX_INTERVAL).contains(&interval) {
+                    return Err(format!(
+                        "RRULE: INTERVAL={} is out of range (1-{})",
+                        interval, MAX_INTERVAL
+                    ));
+                }
+            }
+            "UNTIL" => {
+                let property = Property {
+                    name: "UNTIL".to_string(),
+                    params: vec![],
+                    value: value.to_string(),
+                };
+                until = Some(property.time()?.0);
+            }
+            "COUNT" => {
+                count = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .ok_or_else(|| format!("RRULE: '{}' is not a count", value))?,
+                )
+            }
+            // Like MO or FR, "every 2nd MO" (2MO) and the like can't be repeated
+            "BYDAY" => {
+                for day in value.split(',') {
+                    let day = parse_weekday(&weekday_name(day.trim()))
+                        .ok_or_else(|| unsupported(part))?;
+                    week_days.push(day);
+                }
+            }
+            // Only when they say what the start says already
+            "BYMONTHDAY" if value.trim().parse() == Ok(wall.day()) => {}
+            "BYMONTH" if value.trim().parse() == Ok(wall.month()) => {}
+            "WKST" => {}
+            _ => return Err(unsupported(part)),
+        }
+    }
+    let mut frequency = frequency.ok_or("RRULE without FREQ")?;
+    // Every weekday is FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR, a weekly series on those days here
+    if frequency == RecurrenceFrequency::Daily && !week_days.is_empty() && interval == 1 {
+        frequency = RecurrenceFrequency::Weekly;
+    }
+    if frequency != RecurrenceFrequency::Weekly && !week_days.is_empty() {
+        let frequency = format!("{:?}", frequency).to_uppercase();
+        return Err(unsupported(&format!("BYDAY with FREQ={}", frequency)));
+    }
+    if week_days == [wall.weekday()] {
+        week_days.clear();
+    }
+    let mut recurrence = pinned_recurrence(frequency, interval, week_days, start, zone);
+    // UNTIL is the last start there may be, the end date the first there may not
+    recurrence.end_date = match (until, count) {
+        (Some(_), Some(_)) => return Err("RRULE: UNTIL and COUNT can't both be set".to_string()),
+        (Some(until), None) => Some(until + Duration::seconds(1)),
+        (None, Some(count)) => {
+            let horizon = start + Duration::days(MAX_DURATION_DAYS);
+            let starts = recurrence.occurrences(start, start, horizon, zone);
+            let last = starts
+                .get(count - 1)
+                .ok_or_else(|| format!("RRULE: COUNT={} is too many", count))?;
+            Some(*last + Duration::seconds(1))
+        }
+        (None, None) => None,
+    };
+    Ok(recurrence)
+}
+
+// "MO" to "mon" and so on, as taken by parse_weekday
+fn weekday_name(day: &str) -> String {
+    match day.to_uppercase().as_str() {
+        "MO" => "mon",
+        "TU" => "tue",
+        "WE" => "wed",
+        "TH" => "thu",
+        "FR" => "fri",
+        "SA" => "sat",
+        "SU" => "sun",
+        _ => "",
+    }
+    .to_string()
+}
+
+// Like P1D, PT1H30M or P2W
+fn parse_ical_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest
+        .strip_prefix('P')
+        .ok_or_else(|| format!("'{}' is not a duration", value))?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            'T' => {}
+            c if c.is_ascii_digit() => number.push(c),
+            unit => {
+                let amount: i64 = number
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a duration", value))?;
+                number.clear();
+                let part = match unit {
+                    'W' => Duration::try_weeks(amount),
+                    'D' => Duration::try_days(amount),
+                    'H' => Duration::try_hours(amount),
+                    'M' => Duration::try_minutes(amount),
+                    'S' => Duration::try_seconds(amount),
+                    _ => return Err(format!("'{}' is not a duration", value)),
+                };
+                total = part
+                    .and_then(|part| total.checked_add(&part))
+                    .filter(|total| total.num_days() <= MAX_DURATION_DAYS)
+                    .ok_or_else(|| format!("the duration '{}' is too long", value))?;
+            }
+        }
+    }
+    Ok(if negative { -total } else { total })
+}
+
+// Splits a list value like CATEGORIES at the commas that aren't escaped
+fn split_unescaped(value: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ',' => items.push(unescape(&std::mem::take(&mut current))),
+            c => current.push(c),
+        }
+    }
+    items.push(unescape(&current));
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miscs::utils::datetime_from_str;
+
+    fn at(text: &str) -> DateTime<Local> {
+        datetime_from_str(text, None).unwrap()
+    }
+
+    fn person(name: &str, email: &str, role: Role, status: ParticipationStatus) -> Attendee {
+        Attendee {
+            name: name.to_string(),
+            email: email.to_string(),
+            role,
+            status,
+            ..Default::default()
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        let start = at("2026-10-05 09:30");
+        let mut meeting = Event {
+            uid: "meeting-1@example.org".to_string(),
+            sequence: 3,
+            title: "Planning; Q4, \"roadmap\"".to_string(),
+            description: "Agenda:\n1. Budget\n2. Hiring \\ onboarding".to_string(),
+            location: "Room 4, 2nd floor".to_string(),
+            start_time: start,
+            end_time: start + Duration::minutes(90),
+            created_at: at("2026-09-01 08:00"),
+            categories: vec!["Work".to_string(), "Team, Core".to_string()],
+            priority: Priority::High,
+            transparency: Some(Transparency::Busy),
+            is_recurring: true,
+            organizer: Some(person(
+                "Smith, Alice",
+                "alice@example.org",
+                Role::Required,
+                ParticipationStatus::NeedsAction,
+            )),
+            attendees: vec![
+                person(
+                    "Bob",
+                    "bob@example.org",
+                    Role::Required,
+                    ParticipationStatus::Accepted,
+                ),
+                person(
+                    "carol@example.org",
+                    "carol@example.org",
+                    Role::Optional,
+                    ParticipationStatus::NeedsAction,
+                ),
+            ],
+            ..Default::default()
+        };
+        let mut recurrence = pinned_recurrence(
+            RecurrenceFrequency::Weekly,
+            2,
+            vec![Weekday::Mon, Weekday::Thu],
+            start,
+            None,
+        );
+        recurrence.end_date = Some(at("2026-12-01 00:00"));
+        meeting.recurrence = Some(recurrence);
+
+        let holiday = Event {
+            title: "Holidays in the mountains with a title long enough to be folded – twice, ü"
+                .to_string(),
+            start_time: at("2026-12-24 00:00"),
+            end_time: at("2026-12-27 00:00"),
+            created_at: at("2026-09-02 10:00"),
+            is_all_day: true,
+            priority: Priority::Low,
+            transparency: Some(Transparency::Free),
+            ..Default::default()
+        };
+        vec![meeting, holiday]
+    }
+
+    // What an export keeps: everything but the ID, the last change and the reminders
+    fn kept(event: &Event) -> serde_json::Value {
+        let mut value = serde_json::to_value(event).unwrap();
+        let object = value.as_object_mut().unwrap();
+        for field in ["event_id", "updated_at", "notification_settings"] {
+            object.remove(field);
+        }
+        object.insert("uid".to_string(), event.uid().into());
+        value
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let events = events();
+        let text = write_calendar(&events.iter().collect::<Vec<_>>(), Some("REQUEST"));
+        assert!(text.lines().all(|line| line.len() <= 75));
+        assert!(text.ends_with("END:VCALENDAR\r\n"));
+
+        let calendar = parse_calendar(&text).unwrap();
+        assert_eq!(calendar.method.as_deref(), Some("REQUEST"));
+        assert_eq!(calendar.events.len(), events.len());
+        for (imported, event) in calendar.events.iter().zip(&events) {
+            assert_eq!(kept(imported), kept(event));
+        }
+        // Exporting what was imported gives the same file, but for the time stamps
+        let again = write_calendar(&calendar.events.iter().collect::<Vec<_>>(), Some("REQUEST"));
+        let without_stamps = |text: &str| -> Vec<String> {
+            text.lines()
+                .filter(|line| !line.starts_with("DTSTAMP") && !line.starts_with("LAST-MODIFIED"))
+                .map(str::to_string)
+                .collect()
+        };
+        assert_eq!(without_stamps(&again), without_stamps(&text));
+    }
+
+    #[test]
+    fn recurrence_ends_where_the_rule_says() {
+        let starts = |rule: &str| -> Result<Vec<String>, String> {
+            let text = format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART:20261005T070000Z\r\nDURATION:PT30M\r\n\
+                 SUMMARY:Run\r\nRRULE:{}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                rule
+            );
+            let event = parse_calendar(&text)?.events.remove(0);
+            Ok(event
+                .occurrences_between(at("2026-10-01 00:00"), at("2027-01-01 00:00"))
+                .iter()
+                .map(|(start, _)| start.with_timezone(&Utc).format("%m-%d").to_string())
+                .collect())
+        };
+        assert_eq!(
+            starts("FREQ=DAILY;COUNT=3").unwrap(),
+            ["10-05", "10-06", "10-07"]
+        );
+        assert_eq!(
+            starts("FREQ=WEEKLY;UNTIL=20261019T070000Z").unwrap(),
+            ["10-05", "10-12", "10-19"]
+        );
+        assert_eq!(
+            starts("FREQ=DAILY;BYDAY=MO,WE,FR;COUNT=4").unwrap(),
+            ["10-05", "10-07", "10-09", "10-12"]
+        );
+        assert_eq!(
+            starts("FREQ=MONTHLY;BYDAY=2MO").unwrap_err(),
+            "event ending on line 7: RRULE: BYDAY=2MO is not supported"
+        );
+        assert!(starts("FREQ=DAILY;COUNT=2;UNTIL=20261019T070000Z").is_err());
+        assert!(starts("FREQ=DAILY;BYSETPOS=1").is_err());
+        assert_eq!(
+            starts("FREQ=DAILY;INTERVAL=2;COUNT=2").unwrap(),
+            ["10-05", "10-07"]
+        );
+        assert_eq!(
+            starts("FREQ=YEARLY;INTERVAL=999999999999").unwrap_err(),
+            "event ending on line 7: RRULE: INTERVAL=999999999999 is out of range (1-1000)"
+        );
+    }
+
+    #[test]
+    fn rejects_durations_too_long() {
+        assert_eq!(
+            parse_ical_duration("PT1H30M").unwrap(),
+            Duration::minutes(90)
+        );
+        assert_eq!(parse_ical_duration("-P1W").unwrap(), Duration::weeks(-1));
+        assert_eq!(
+            parse_ical_duration("P99999999999W").unwrap_err(),
+            "the duration 'P99999999999W' is too long"
+        );
+        assert!(parse_ical_duration("1H").is_err());
+    }
+}
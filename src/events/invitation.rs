@@ -0,0 +1,471 @@
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use super::event::{Attendee, Event, Role};
+use super::ical::write_calendar;
+use crate::miscs::config::Config;
+use crate::miscs::output::span_text;
+
+// Invitations are emailed the iMIP way (RFC 6047): a text part for people, the event as a
+// text/calendar part with the METHOD for calendar applications, and the same as an .ics file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Request, // Invites to the event, or updates it
+    Cancel,  // Takes the event off the attendees' calendars
+}
+
+impl Method {
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::Request => "REQUEST",
+            Method::Cancel => "CANCEL",
+        }
+    }
+}
+
+// One email about an event to some of its attendees
+#[derive(Debug, Clone)]
+pub struct Invitation {
+    pub method: Method,
+    pub event: Event,
+    pub to: Vec<Attendee>,
+}
+
+pub fn organized_by(event: &Event, email: &str) -> bool {
+    !email.is_empty()
+        && event
+            .organizer
+            .as_ref()
+            .is_some_and(|organizer| organizer.email.eq_ignore_ascii_case(email))
+}
+
+// Whether attendees need to hear about the change: the time, place or what it is about
+fn details_changed(old: &Event, new: &Event) -> bool {
+    old.title != new.title
+        || old.description != new.description
+        || old.location != new.location
+        || old.start_time != new.start_time
+        || old.end_time != new.end_time
+        || old.is_all_day != new.is_all_day
+        || old.time_zone != new.time_zone
+        || old.is_recurring != new.is_recurring
+        || serde_json::to_value(&old.recurrence).ok() != serde_json::to_value(&new.recurrence).ok()
+}
+
+// Whether an edit outdates the invitations sent before, so it needs a new SEQUENCE: the
+// details changed, or attendees were taken off and get a cancellation
+pub fn raises_sequence(old: &Event, new: &Event) -> bool {
+    let removed = old
+        .attendees
+        .iter()
+        .any(|attendee| new.find_attendee(&attendee.email).is_none());
+    removed || !new.attendees.is_empty() && details_changed(old, new)
+}
+
+// The emails an event calls for after it was added (`old` None), edited, or removed (`new`
+// None). Attendees taken off the event get a cancellation, the others the event again when its
+// details changed, and new attendees their invitation. Nobody is sent their own event.
+pub fn invitations(old: Option<&Event>, new: Option<&Event>) -> Vec<Invitation> {
+    let invited =
+        |event: &Event, attendee: &Attendee| event.find_attendee(&attendee.email).is_some();
+    let recipients = |event: &Event, keep: &dyn Fn(&Attendee) -> bool| -> Vec<Attendee> {
+        event
+            .attendees
+            .iter()
+            .filter(|attendee| {
+                keep(attendee)
+                    && !event.organizer.as_ref().is_some_and(|organizer| {
+                        organizer.email.eq_ignore_ascii_case(&attendee.email)
+                    })
+            })
+            .cloned()
+            .collect()
+    };
+    let mut result = vec![];
+    let mut push = |method: Method, event: &Event, to: Vec<Attendee>| {
+        if !to.is_empty() {
+            result.push(Invitation {
+                method,
+                event: event.clone(),
+                to,
+            });
+        }
+    };
+    match (old, new) {
+        (None, Some(new)) => push(Method::Request, new, recipients(new, &|_| true)),
+        (Some(old), None) => {
+            let mut cancelled = old.clone();
+            cancelled.sequence += 1;
+            push(Method::Cancel, &cancelled, recipients(old, &|_| true));
+        }
+        (Some(old), Some(new)) => {
+            // A cancellation only names the attendees it is sent to
+            let mut removed = new.clone();
+            removed.attendees = recipients(old, &|attendee| !invited(new, attendee));
+            let to = removed.attendees.clone();
+            push(Method::Cancel, &removed, to);
+            let to = match details_changed(old, new) {
+                true => recipients(new, &|_| true),
+                false => recipients(new, &|attendee| {
+                    old.find_attendee(&attendee.email)
+                        .is_none_or(|known| known.role != attendee.role)
+                }),
+            };
+            push(Method::Request, new, to);
+        }
+        (None, None) => {}
+    }
+    result
+}
+
+// Whether a REPLY or CANCEL names the same organizer as the event it is about
+pub fn from_organizer(event: &Event, message: &Event) -> bool {
+    match (&event.organizer, &message.organizer) {
+        (Some(own), Some(theirs)) => own.email.eq_ignore_ascii_case(&theirs.email),
+        _ => false,
+    }
+}
+
+// Why a REPLY or CANCEL is out of date: it is about an older revision than the one here
+pub fn outdated(event: &Event, message: &Event) -> Option<String> {
+    (message.sequence < event.sequence).then(|| {
+        format!(
+            "it is about revision {} but the event is at revision {}",
+            message.sequence, event.sequence
+        )
+    })
+}
+
+// Answers in a REPLY applied to the invited event, returns the attendees whose answer changed.
+// Only people on the event can answer for themselves.
+pub fn apply_reply(event: &mut Event, reply: &Event) -> Result<Vec<Attendee>, String> {
+    let mut changed = vec![];
+    for answer in &reply.attendees {
+        let Some(attendee) = event.find_attendee_mut(&answer.email) else {
+            return Err(format!(
+                "{} answered but is not an attendee of {}",
+                answer.email, event.event_id
+            ));
+        };
+        if attendee.status != answer.status {
+            attendee.status = answer.status;
+            changed.push(attendee.clone());
+        }
+    }
+    Ok(changed)
+}
+
+// An SMTP connection as set up in the config
+pub struct Mailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let server = config.smtp_server.trim();
+        if server.is_empty() {
+            return Err("no SMTP server, set one with `config set smtp_server <host>`".to_string());
+        }
+        let from = mailbox(&config.user_name, &config.user_email)
+            .map_err(|e| format!("config user_email: {}", e))?;
+        let builder = match config.smtp_security.trim().to_lowercase().as_str() {
+            "starttls" => SmtpTransport::starttls_relay(server).map_err(|e| e.to_string())?,
+            "tls" => SmtpTransport::relay(server).map_err(|e| e.to_string())?,
+            "none" => SmtpTransport::builder_dangerous(server),
+            other => {
+                return Err(format!(
+                    "config smtp_security: '{}' is not one of starttls, tls or none",
+                    other
+                ))
+            }
+        };
+        let builder = match config.smtp_port {
+            0 => builder,
+            port => builder.port(
+                u16::try_from(port)
+                    .map_err(|_| format!("config smtp_port: {} is not a port number", port))?,
+            ),
+        };
+        let builder = match config.smtp_user.is_empty() {
+            true => builder,
+            false => builder.credentials(Credentials::new(
+                config.smtp_user.clone(),
+                config.smtp_password.clone(),
+            )),
+        };
+        let transport = builder
+            .timeout(Some(std::time::Duration::from_secs(30)))
+            .build();
+        Ok(Self { transport, from })
+    }
+
+    pub fn send(&self, invitation: &Invitation) -> Result<(), String> {
+        let event = &invitation.event;
+        let method = invitation.method.name();
+        let calendar = write_calendar(&[event], Some(method));
+        let calendar_type = format!("text/calendar; charset=UTF-8; method={}", method);
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(subject(invitation));
+        for attendee in &invitation.to {
+            message = message.to(mailbox(&attendee.name, &attendee.email)
+                .map_err(|e| format!("{}: {}", attendee.email, e))?);
+        }
+        let message = message
+            .multipart(
+                MultiPart::mixed()
+                    .multipart(
+                        MultiPart::alternative()
+                            .singlepart(SinglePart::plain(body(invitation)))
+                            .singlepart(
+                                SinglePart::builder()
+                                    .header(ContentType::parse(&calendar_type).unwrap())
+                                    .body(calendar.clone()),
+                            ),
+                    )
+                    .singlepart(
+                        Attachment::new("invite.ics".to_string())
+                            .body(calendar, ContentType::parse("application/ics").unwrap()),
+                    ),
+            )
+            .map_err(|e| e.to_string())?;
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    // A reminder of an event, sent to yourself
+    pub fn remind(&self, event: &Event) -> Result<(), String> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(self.from.clone())
+            .subject(format!("Reminder: {} @ {}", event.title, when(event)))
+            .header(ContentType::TEXT_PLAIN)
+            .body(reminder_body(event))
+            .map_err(|e| e.to_string())?;
+        self.transport
+            .send(&message)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn mailbox(name: &str, email: &str) -> Result<Mailbox, String> {
+    let address = email.trim().parse().map_err(|e| format!("{}", e))?;
+    let name = name.trim();
+    Ok(Mailbox::new(
+        (!name.is_empty() && name != email).then(|| name.to_string()),
+        address,
+    ))
+}
+
+fn when(event: &Event) -> String {
+    match event.is_all_day {
+        true => {
+            let (first, last) = event.all_day_dates();
+            match first == last {
+                true => format!("{} (all day)", first.format("%a %Y-%m-%d")),
+                false => format!(
+                    "{} - {} (all day)",
+                    first.format("%a %Y-%m-%d"),
+                    last.format("%a %Y-%m-%d")
+                ),
+            }
+        }
+        false => format!(
+            "{} (UTC{})",
+            span_text(event.start_time, event.end_time),
+            event.start_time.format("%:z")
+        ),
+    }
+}
+
+fn subject(invitation: &Invitation) -> String {
+    let event = &invitation.event;
+    let prefix = match (invitation.method, event.sequence) {
+        (Method::Cancel, _) => "Cancelled",
+        (Method::Request, 0) => "Invitation",
+        (Method::Request, _) => "Updated invitation",
+    };
+    format!("{}: {} @ {}", prefix, event.title, when(event))
+}
+
+fn body(invitation: &Invitation) -> String {
+    let event = &invitation.event;
+    let organizer = event
+        .organizer
+        .as_ref()
+        .map(|organizer| organizer.name.as_str())
+        .unwrap_or("The organizer");
+    let mut text = match (invitation.method, event.sequence) {
+        (Method::Cancel, _) => format!("{} has cancelled this event:\n\n", organizer),
+        (Method::Request, 0) => format!("{} invites you to:\n\n", organizer),
+        (Method::Request, _) => format!("{} has updated this event:\n\n", organizer),
+    };
+    text += &details_head(event);
+    if invitation.method == Method::Request {
+        let attendees: Vec<String> = event
+            .attendees
+            .iter()
+            .map(|attendee| match attendee.role {
+                Role::Optional => format!("{} (optional)", attendee.name),
+                _ => attendee.name.clone(),
+            })
+            .collect();
+        text += &format!("Attendees: {}\n", attendees.join(", "));
+    }
+    if !event.description.is_empty() {
+        text += &format!("\n{}\n", event.description);
+    }
+    text
+}
+
+// The title, time and place of an event
+fn details_head(event: &Event) -> String {
+    let mut text = format!("{}\nWhen:      {}\n", event.title, when(event));
+    if !event.location.is_empty() {
+        text += &format!("Where:     {}\n", event.location);
+    }
+    text
+}
+
+fn reminder_body(event: &Event) -> String {
+    let mut text = details_head(event);
+    if !event.description.is_empty() {
+        text += &format!("\n{}\n", event.description);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event::ParticipationStatus;
+    use crate::miscs::utils::datetime_from_str;
+
+    fn person(name: &str, role: Role) -> Attendee {
+        Attendee {
+            name: name.to_string(),
+            email: format!("{}@example.org", name.to_lowercase()),
+            role,
+            ..Default::default()
+        }
+    }
+
+    fn meeting() -> Event {
+        let start = datetime_from_str("2026-10-20 14:00", None).unwrap();
+        Event {
+            event_id: "#1".to_string(),
+            title: "Planning".to_string(),
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            organizer: Some(person("Alice", Role::Chair)),
+            attendees: vec![
+                person("Alice", Role::Chair),
+                person("Bob", Role::Required),
+                person("Carol", Role::Optional),
+            ],
+            ..Default::default()
+        }
+    }
+
+    // Method and recipient names of each email
+    fn emails(old: Option<&Event>, new: Option<&Event>) -> Vec<(Method, Vec<String>)> {
+        invitations(old, new)
+            .into_iter()
+            .map(|invitation| {
+                let names = invitation.to.iter().map(|to| to.name.clone()).collect();
+                (invitation.method, names)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn raises_sequence_for_changes_attendees_hear_about() {
+        let old = meeting();
+        let mut renamed = old.clone();
+        renamed.title = "Roadmap".to_string();
+        assert!(raises_sequence(&old, &renamed));
+
+        // Taking someone off the event cancels it for them
+        let mut fewer = old.clone();
+        fewer.attendees.pop();
+        assert!(raises_sequence(&old, &fewer));
+        let mut nobody = old.clone();
+        nobody.attendees.clear();
+        assert!(raises_sequence(&old, &nobody));
+
+        // Without attendees nobody is told, and a new role or tag isn't a new revision
+        let mut alone = renamed.clone();
+        alone.attendees.clear();
+        assert!(!raises_sequence(&nobody, &alone));
+        let mut optional = old.clone();
+        optional.attendees[1].role = Role::Optional;
+        optional.tags = vec!["q4".to_string()];
+        assert!(!raises_sequence(&old, &optional));
+    }
+
+    #[test]
+    fn emails_who_is_affected() {
+        let old = meeting();
+        let both = ["Bob".to_string(), "Carol".to_string()];
+        // The organizer isn't sent their own event
+        assert_eq!(emails(None, Some(&old)), [(Method::Request, both.to_vec())]);
+        assert_eq!(emails(Some(&old), None), [(Method::Cancel, both.to_vec())]);
+        let cancelled = invitations(Some(&old), None).remove(0);
+        assert_eq!(cancelled.event.sequence, 1);
+
+        let mut edited = old.clone();
+        edited.attendees.retain(|attendee| attendee.name != "Carol");
+        edited.attendees.push(person("Dave", Role::Required));
+        assert_eq!(
+            emails(Some(&old), Some(&edited)),
+            [
+                (Method::Cancel, vec!["Carol".to_string()]),
+                (Method::Request, vec!["Dave".to_string()]),
+            ]
+        );
+        edited.location = "Room 4".to_string();
+        assert_eq!(
+            emails(Some(&old), Some(&edited))[1],
+            (Method::Request, vec!["Bob".to_string(), "Dave".to_string()])
+        );
+        assert!(emails(Some(&old), Some(&old)).is_empty());
+    }
+
+    #[test]
+    fn applies_replies_from_attendees() {
+        let mut event = meeting();
+        event.sequence = 2;
+        let mut reply = meeting();
+        reply.attendees = vec![Attendee {
+            status: ParticipationStatus::Accepted,
+            ..person("Bob", Role::Required)
+        }];
+        assert!(from_organizer(&event, &reply));
+        assert_eq!(
+            outdated(&event, &reply),
+            Some("it is about revision 0 but the event is at revision 2".to_string())
+        );
+        reply.sequence = 2;
+        assert_eq!(outdated(&event, &reply), None);
+
+        let changed = apply_reply(&mut event, &reply).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(event.attendees[1].status, ParticipationStatus::Accepted);
+        // The same answer again changes nothing
+        assert!(apply_reply(&mut event, &reply).unwrap().is_empty());
+
+        reply.attendees = vec![person("Mallory", Role::Required)];
+        assert_eq!(
+            apply_reply(&mut event, &reply).unwrap_err(),
+            "mallory@example.org answered but is not an attendee of #1"
+        );
+        reply.organizer = None;
+        assert!(!from_organizer(&event, &reply));
+    }
+}
@@ -1,4 +1,12 @@
+pub mod category;
+pub mod conflicts;
+pub mod contact;
 #[allow(dead_code)]
 pub mod event;
 //#[allow(dead_code)]
 pub mod event_manager;
+pub mod ical;
+pub mod invitation;
+pub mod query;
+pub mod search_index;
+pub mod validation;
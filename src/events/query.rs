@@ -0,0 +1,668 @@
+use chrono::{DateTime, Local};
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+
+use crate::miscs::arg_parsing::did_you_mean;
+use crate::miscs::natural::parse_datetime;
+use crate::miscs::utils::parse_range;
+
+use super::event::{Event, PriorityFilter};
+use super::event_manager::SearchType;
+
+// Field names understood before the `:` of a term, besides the search types
+pub const DATE_FIELDS: [&str; 3] = ["after", "before", "range"];
+
+// How the values of a query are compared with the event's fields
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryOptions {
+    pub ignore_case: bool,
+    pub regex: bool, // Values are regular expressions instead of plain text
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Text(String),      // Substring, compared as given
+    LowerText(String), // Substring, lowercased like the text it is compared with
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn new(value: &str, options: QueryOptions) -> Result<Self, String> {
+        if options.regex {
+            RegexBuilder::new(value)
+                .case_insensitive(options.ignore_case)
+                .build()
+                .map(Pattern::Regex)
+                .map_err(|e| format!("invalid regular expression '{}': {}", value, e))
+        } else if options.ignore_case {
+            Ok(Pattern::LowerText(value.to_lowercase()))
+        } else {
+            Ok(Pattern::Text(value.to_string()))
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Text(needle) => text.contains(needle.as_str()),
+            Pattern::LowerText(needle) => text.to_lowercase().contains(needle.as_str()),
+            Pattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+
+    fn matches_any(&self, event: &Event) -> bool {
+        SearchType::FullText
+            .texts(event)
+            .iter()
+            .any(|text| self.matches(text))
+    }
+}
+
+// Events found by the search index for each full-text term, with their score
+pub type FullTextHits = HashMap<String, HashMap<usize, f32>>;
+
+#[derive(Debug, Clone)]
+enum Node {
+    All, // The empty query
+    Field(SearchType, Pattern),
+    FullText(String), // Looked up in the search index, see search_index.rs
+    After(DateTime<Local>),
+    Before(DateTime<Local>),
+    Range(DateTime<Local>, DateTime<Local>), // Has an occurrence in [start, end)
+    Priority(PriorityFilter),
+    Not(Box<Node>),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+}
+
+impl Node {
+    // None if the event doesn't match, otherwise the relevance of its full-text matches
+    fn score(&self, position: usize, event: &Event, hits: &FullTextHits) -> Option<f32> {
+        let score = match self {
+            Node::FullText(text) => return hits.get(text)?.get(&position).copied(),
+            Node::Not(node) => return node.score(position, event, hits).xor(Some(0.0)),
+            Node::And(nodes) => {
+                return nodes
+                    .iter()
+                    .map(|node| node.score(position, event, hits))
+                    .sum()
+            }
+            Node::Or(nodes) => {
+                return nodes
+                    .iter()
+                    .filter_map(|node| node.score(position, event, hits))
+                    .reduce(|a, b| a + b)
+            }
+            _ => self.matches(event),
+        };
+        score.then_some(0.0)
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        match self {
+            Node::All => true,
+            Node::FullText(text) => Pattern::LowerText(text.to_lowercase()).matches_any(event),
+            Node::Field(field, pattern) => {
+                field.texts(event).iter().any(|text| pattern.matches(text))
+            }
+            // Repeating events count as long as they have occurrences left
+            Node::After(time) => {
+                event.end_time > *time
+                    || event.is_recurring
+                        && event.recurrence.as_ref().is_some_and(|recurrence| {
+                            recurrence.end_date.is_none_or(|end| end > *time)
+                        })
+            }
+            Node::Before(time) => event.start_time < *time,
+            Node::Range(start, end) => !event.occurrences_between(*start, *end).is_empty(),
+            Node::Priority(filter) => filter.matches(event.priority),
+            Node::Not(node) => !node.matches(event),
+            Node::And(nodes) => nodes.iter().all(|node| node.matches(event)),
+            Node::Or(nodes) => nodes.iter().any(|node| node.matches(event)),
+        }
+    }
+
+    fn full_text<'a>(&'a self, texts: &mut Vec<&'a str>) {
+        match self {
+            Node::FullText(text) => texts.push(text),
+            Node::Not(node) => node.full_text(texts),
+            Node::And(nodes) | Node::Or(nodes) => {
+                for node in nodes {
+                    node.full_text(texts);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// A search like `title:standup AND category:Work AND after:2026-10-01 NOT location:remote`
+#[derive(Debug, Clone)]
+pub struct Query(Node);
+
+impl Query {
+    pub fn parse(input: &str, options: QueryOptions) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Ok(Query(Node::All));
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            options,
+            now: Local::now(),
+        };
+        let node = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(Query(node)),
+            Some(Token::Close) => Err("unexpected ')' without a matching '('".to_string()),
+            Some(token) => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    // Searches a single field for the value as it is, like `search title team sync`
+    pub fn field(field: SearchType, value: &str, options: QueryOptions) -> Result<Self, String> {
+        if field == SearchType::FullText && !options.regex {
+            return Ok(Query(Node::FullText(value.to_string())));
+        }
+        Ok(Query(Node::Field(field, Pattern::new(value, options)?)))
+    }
+
+    // Full-text search for input that isn't a valid query
+    pub fn text(value: &str) -> Self {
+        Query(Node::FullText(value.to_string()))
+    }
+
+    // The texts to look up in the search index before calling `score`
+    pub fn full_text(&self) -> Vec<&str> {
+        let mut texts = vec![];
+        self.0.full_text(&mut texts);
+        texts
+    }
+
+    // None if the event at `position` doesn't match, otherwise how well it matches
+    pub fn score(&self, position: usize, event: &Event, hits: &FullTextHits) -> Option<f32> {
+        self.0.score(position, event, hits)
+    }
+}
+
+// Joins words split by the shell back into a query. Words with spaces are quoted, like
+// `title:"team sync"`, unless they already are a whole query like "category:Work range:week".
+pub fn join_words(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|word| {
+            if !word.contains(char::is_whitespace) || is_query(word) {
+                return word.clone();
+            }
+            let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\"));
+            match word.split_once(':') {
+                Some((field, value)) if is_field_name(field) => {
+                    format!("{}:{}", field, quote(value))
+                }
+                _ => quote(word),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_query(text: &str) -> bool {
+    text.contains(['"', '(', ')'])
+        || text.split_whitespace().skip(1).any(|word| {
+            matches!(word, "AND" | "OR" | "NOT")
+                || word
+                    .split_once(':')
+                    .is_some_and(|(field, _)| is_field_name(field))
+        })
+}
+
+fn is_field_name(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_alphabetic() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word {
+        text: String,
+        quoted: bool,         // Quoted words are never operators
+        colon: Option<usize>, // Byte offset of the first `:` outside quotes
+    },
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word { text, .. } => write!(f, "'{}'", text),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+impl Token {
+    fn is_operator(&self, operator: &str) -> bool {
+        matches!(self, Token::Word { text, quoted: false, .. } if text == operator)
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                let mut colon = None;
+                // Parentheses inside a word belong to it, like in `title:^(Lunch|Dinner)$`
+                let mut depth = 0;
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == ')' && depth == 0 {
+                        break;
+                    }
+                    chars.next();
+                    match c {
+                        '(' => {
+                            depth += 1;
+                            text.push(c);
+                        }
+                        ')' => {
+                            depth -= 1;
+                            text.push(c);
+                        }
+                        '"' => {
+                            quoted = true;
+                            loop {
+                                match chars.next() {
+                                    Some('"') => break,
+                                    Some('\\') => match chars.next() {
+                                        Some(escaped) => text.push(escaped),
+                                        None => break,
+                                    },
+                                    Some(c) => text.push(c),
+                                    None => {
+                                        return Err(format!(
+                                            "missing closing quote after \"{}",
+                                            text
+                                        ))
+                                    }
+                                }
+                            }
+                        }
+                        ':' if colon.is_none() => {
+                            colon = Some(text.len());
+                            text.push(c);
+                        }
+                        c => text.push(c),
+                    }
+                }
+                tokens.push(Token::Word {
+                    text,
+                    quoted,
+                    colon,
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// or := and (OR and)*, and := not ([AND] not)*, not := NOT not | ( or ) | term
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    options: QueryOptions,
+    now: DateTime<Local>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![self.parse_and()?];
+        while self.peek().is_some_and(|token| token.is_operator("OR")) {
+            self.next();
+            nodes.push(self.parse_and()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::Or(nodes)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![self.parse_not()?];
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(token) if token.is_operator("OR") => break,
+                Some(token) if token.is_operator("AND") => {
+                    self.next();
+                    nodes.push(self.parse_not()?);
+                }
+                // Terms next to each other must all match
+                Some(_) => nodes.push(self.parse_not()?),
+            }
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::And(nodes)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(token) if token.is_operator("NOT") => Ok(Node::Not(Box::new(self.parse_not()?))),
+            Some(Token::Open) => {
+                let node = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(node),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(token) if token.is_operator("AND") || token.is_operator("OR") => {
+                Err(format!("expected a search term before {}", token))
+            }
+            Some(Token::Word {
+                text,
+                quoted: _,
+                colon,
+            }) => self.term(&text, colon),
+            Some(Token::Close) => Err("expected a search term before ')'".to_string()),
+            None => Err("expected a search term at the end of the query".to_string()),
+        }
+    }
+
+    fn term(&self, text: &str, colon: Option<usize>) -> Result<Node, String> {
+        let field = colon
+            .map(|colon| (&text[..colon], &text[colon + 1..]))
+            .filter(|(field, _)| is_field_name(field));
+        // A bare word searches every text field, so does "12:30" and the like
+        let Some((field, value)) = field else {
+            return self.full_text(text);
+        };
+        if value.is_empty() {
+            return Err(format!("'{}:' needs a value", field));
+        }
+        match field.to_lowercase().as_str() {
+            "after" => Ok(Node::After(self.time(field, value)?)),
+            "before" => Ok(Node::Before(self.time(field, value)?)),
+            // Relative ranges like `range:week` are resolved whenever the query is parsed
+            "range" => {
+                let (start, end) =
+                    parse_range(value, self.now).map_err(|e| format!("{}: {}", field, e))?;
+                Ok(Node::Range(start, end))
+            }
+            name => match SearchType::from(name) {
+                Some(SearchType::FullText) => self.full_text(value),
+                // `priority:high` is that priority, `priority:high+` it or higher
+                Some(SearchType::Priority) if !self.options.regex => {
+                    Ok(Node::Priority(PriorityFilter::parse(value)?))
+                }
+                Some(search_type) => {
+                    Ok(Node::Field(search_type, Pattern::new(value, self.options)?))
+                }
+                None => {
+                    let known: Vec<&str> = SearchType::NAMES
+                        .iter()
+                        .chain(DATE_FIELDS.iter())
+                        .copied()
+                        .collect();
+                    Err(match did_you_mean(field, &known) {
+                        Some(suggestion) => format!(
+                            "unknown search field '{}', did you mean '{}'?",
+                            field, suggestion
+                        ),
+                        None => format!(
+                            "unknown search field '{}', expected one of {}",
+                            field,
+                            known.join(", ")
+                        ),
+                    })
+                }
+            },
+        }
+    }
+
+    // The search index doesn't know regular expressions, those are matched against the texts
+    fn full_text(&self, value: &str) -> Result<Node, String> {
+        if self.options.regex {
+            Ok(Node::Field(
+                SearchType::FullText,
+                Pattern::new(value, self.options)?,
+            ))
+        } else {
+            Ok(Node::FullText(value.to_string()))
+        }
+    }
+
+    fn time(&self, field: &str, value: &str) -> Result<DateTime<Local>, String> {
+        parse_datetime(value, self.now, None).map_err(|e| format!("{}: {}", field, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event::Priority;
+    use crate::miscs::utils::datetime_from_str;
+
+    fn event(title: &str, category: &str, location: &str, start: &str) -> Event {
+        let start_time = datetime_from_str(start, None).unwrap();
+        Event {
+            title: title.to_string(),
+            categories: vec![category.to_string()],
+            location: location.to_string(),
+            start_time,
+            end_time: start_time + chrono::Duration::hours(1),
+            ..Default::default()
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        vec![
+            event("Standup", "Work", "remote", "2026-10-05 09:00"),
+            event("Planning", "Work", "Office", "2026-10-12 14:00"),
+            event("Lunch", "Private", "Cafe", "2026-10-20 12:00"),
+        ]
+    }
+
+    // Titles of the events the query matches, full-text terms found in every title
+    fn titles(input: &str, options: QueryOptions) -> Vec<String> {
+        let query = Query::parse(input, options).unwrap();
+        let events = events();
+        let hits: FullTextHits = query
+            .full_text()
+            .into_iter()
+            .map(|text| {
+                let found = events
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, event)| event.title.to_lowercase().contains(&text.to_lowercase()))
+                    .map(|(position, _)| (position, 1.0))
+                    .collect();
+                (text.to_string(), found)
+            })
+            .collect();
+        events
+            .iter()
+            .enumerate()
+            .filter(|(position, event)| query.score(*position, event, &hits).is_some())
+            .map(|(_, event)| event.title.clone())
+            .collect()
+    }
+
+    fn matches(input: &str) -> Vec<String> {
+        titles(input, QueryOptions::default())
+    }
+
+    #[test]
+    fn and_or_not() {
+        assert_eq!(matches(""), ["Standup", "Planning", "Lunch"]);
+        assert_eq!(matches("category:Work"), ["Standup", "Planning"]);
+        assert_eq!(
+            matches("category:Work AND NOT location:remote"),
+            ["Planning"]
+        );
+        // Terms next to each other must all match
+        assert_eq!(matches("category:Work location:remote"), ["Standup"]);
+        assert_eq!(
+            matches("title:Lunch OR title:Standup"),
+            ["Standup", "Lunch"]
+        );
+        // AND binds tighter than OR
+        assert_eq!(
+            matches("title:Lunch OR category:Work AND location:Office"),
+            ["Planning", "Lunch"]
+        );
+        assert_eq!(
+            matches("(title:Lunch OR category:Work) AND NOT location:Office"),
+            ["Standup", "Lunch"]
+        );
+        assert_eq!(matches("NOT NOT category:Private"), ["Lunch"]);
+        // Quoted operators are plain words
+        assert!(matches("\"OR\"").is_empty());
+    }
+
+    #[test]
+    fn dates_and_fields() {
+        assert_eq!(matches("after:2026-10-15"), ["Lunch"]);
+        assert_eq!(matches("before:2026-10-06"), ["Standup"]);
+        assert_eq!(
+            matches("after:2026-10-01 before:2026-10-15"),
+            ["Standup", "Planning"]
+        );
+        assert_eq!(matches("location:\"Off\""), ["Planning"]);
+        assert!(matches("location:office").is_empty());
+        let ignore_case = QueryOptions {
+            ignore_case: true,
+            regex: false,
+        };
+        assert_eq!(titles("location:office", ignore_case), ["Planning"]);
+        let regex = QueryOptions {
+            ignore_case: false,
+            regex: true,
+        };
+        assert_eq!(
+            titles("title:^(Lunch|Planning)$", regex),
+            ["Planning", "Lunch"]
+        );
+        assert_eq!(titles("^Stand", regex), ["Standup"]);
+    }
+
+    #[test]
+    fn scores_add_up_full_text_hits() {
+        let events = events();
+        let score = |input: &str, position: usize| {
+            let hits: FullTextHits = HashMap::from([
+                ("lunch".to_string(), HashMap::from([(2, 2.0)])),
+                ("cafe".to_string(), HashMap::from([(2, 0.5)])),
+            ]);
+            Query::parse(input, QueryOptions::default()).unwrap().score(
+                position,
+                &events[position],
+                &hits,
+            )
+        };
+        assert_eq!(score("lunch", 2), Some(2.0));
+        assert_eq!(score("lunch", 0), None);
+        assert_eq!(score("lunch cafe", 2), Some(2.5));
+        assert_eq!(score("lunch OR cafe", 2), Some(2.5));
+        assert_eq!(score("lunch OR category:Work", 0), Some(0.0));
+        assert_eq!(score("lunch AND category:Work", 2), None);
+        // A NOT matches without adding to the score
+        assert_eq!(score("NOT lunch", 0), Some(0.0));
+        assert_eq!(score("NOT lunch", 2), None);
+        assert_eq!(score("lunch NOT category:Work", 2), Some(2.0));
+    }
+
+    #[test]
+    fn priority_terms() {
+        let mut urgent = events();
+        urgent[1].priority = Priority::Urgent;
+        let query = Query::parse("priority:high+", QueryOptions::default()).unwrap();
+        let found: Vec<usize> = (0..3)
+            .filter(|position| {
+                query
+                    .score(*position, &urgent[*position], &FullTextHits::new())
+                    .is_some()
+            })
+            .collect();
+        assert_eq!(found, [1]);
+        assert!(Query::parse("priority:hihg", QueryOptions::default())
+            .unwrap_err()
+            .contains("did you mean 'high'?"));
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        let error = |input: &str| Query::parse(input, QueryOptions::default()).unwrap_err();
+        assert_eq!(error("AND lunch"), "expected a search term before 'AND'");
+        assert_eq!(
+            error("lunch OR"),
+            "expected a search term at the end of the query"
+        );
+        assert_eq!(error("(lunch"), "missing ')'");
+        assert_eq!(error("lunch)"), "unexpected ')' without a matching '('");
+        assert_eq!(error("title:"), "'title:' needs a value");
+        assert_eq!(
+            error("title:\"lunch"),
+            "missing closing quote after \"title:lunch"
+        );
+        assert_eq!(
+            error("titel:lunch"),
+            "unknown search field 'titel', did you mean 'title'?"
+        );
+        assert!(error("after:someday").starts_with("after: "));
+        let regex = QueryOptions {
+            ignore_case: false,
+            regex: true,
+        };
+        assert!(Query::parse("title:(", regex)
+            .unwrap_err()
+            .starts_with("invalid regular expression '('"));
+    }
+
+    #[test]
+    fn joins_shell_words() {
+        let words = |words: &[&str]| {
+            join_words(
+                &words
+                    .iter()
+                    .map(|word| word.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(
+            words(&["title:team sync", "OR", "lunch break"]),
+            "title:\"team sync\" OR \"lunch break\""
+        );
+        assert_eq!(
+            words(&["category:Work range:week"]),
+            "category:Work range:week"
+        );
+    }
+}
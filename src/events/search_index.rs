@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::miscs::arg_parsing::edit_distance;
+
+use super::event::Event;
+
+// How much a word counts depending on the field it was found in
+const TITLE_WEIGHT: f32 = 3.0;
+const CATEGORY_WEIGHT: f32 = 2.0;
+const TAG_WEIGHT: f32 = 2.0;
+const LOCATION_WEIGHT: f32 = 1.5;
+const ATTENDEE_WEIGHT: f32 = 1.5;
+const DESCRIPTION_WEIGHT: f32 = 1.0;
+
+// A word found as the start of a longer one or with a typo counts less than an exact match
+const PREFIX_FACTOR: f32 = 0.7;
+const FUZZY_FACTOR: f32 = 0.4;
+
+type DocId = u32;
+
+// Inverted index over the text fields of the events, from each word to the events containing
+// it. Events are numbered internally, so removing one doesn't renumber the others.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    postings: BTreeMap<String, HashMap<DocId, f32>>, // Word -> event -> summed field weights
+    words: HashMap<DocId, Vec<String>>,              // Words of each event, to remove them again
+    slots: Vec<DocId>,                               // Event at each position of the event list
+    next_doc: DocId,
+}
+
+impl SearchIndex {
+    pub fn build(events: &[Event]) -> Self {
+        let mut index = Self::default();
+        for event in events {
+            index.push(event);
+        }
+        index
+    }
+
+    pub fn push(&mut self, event: &Event) {
+        let doc = self.next_doc;
+        self.next_doc += 1;
+        self.slots.push(doc);
+        self.insert_words(doc, event);
+    }
+
+    pub fn remove(&mut self, position: usize) {
+        if position < self.slots.len() {
+            let doc = self.slots.remove(position);
+            self.remove_words(doc);
+        }
+    }
+
+    pub fn replace(&mut self, position: usize, event: &Event) {
+        if let Some(&doc) = self.slots.get(position) {
+            self.remove_words(doc);
+            self.insert_words(doc, event);
+        }
+    }
+
+    // Positions of the events containing every word of `text`, best matches first. Words match
+    // exactly, as the start of a longer word, or with a typo if nothing else matches.
+    pub fn search(&self, text: &str) -> Vec<(usize, f32)> {
+        let query = tokenize(text);
+        if query.is_empty() {
+            return vec![];
+        }
+        let count = self.slots.len() as f32;
+        let mut scores: Option<HashMap<DocId, f32>> = None;
+        for word in &query {
+            let mut word_scores: HashMap<DocId, f32> = HashMap::new();
+            for (term, factor) in self.matching_terms(word) {
+                let postings = &self.postings[term];
+                // Rare words say more about an event than common ones
+                let idf = (1.0 + count / postings.len() as f32).ln();
+                for (&doc, &weight) in postings {
+                    let score = word_scores.entry(doc).or_default();
+                    *score = score.max(weight * factor * idf);
+                }
+            }
+            scores = Some(match scores {
+                None => word_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(doc, score)| Some((doc, score + word_scores.get(&doc)?)))
+                    .collect(),
+            });
+        }
+        let scores = scores.unwrap_or_default();
+        let mut result: Vec<(usize, f32)> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(position, doc)| Some((position, *scores.get(doc)?)))
+            .collect();
+        result.sort_by(|a, b| b.1.total_cmp(&a.1));
+        result
+    }
+
+    // Indexed words matching a query word, with how much the match counts
+    fn matching_terms(&self, word: &str) -> Vec<(&String, f32)> {
+        let mut terms: Vec<(&String, f32)> = self
+            .postings
+            .range(word.to_string()..)
+            .take_while(|(term, _)| term.starts_with(word))
+            .map(|(term, _)| (term, if term == word { 1.0 } else { PREFIX_FACTOR }))
+            .collect();
+        if terms.is_empty() {
+            let allowed = match word.chars().count() {
+                0..=3 => 0,
+                4..=7 => 1,
+                _ => 2,
+            };
+            let length = word.chars().count();
+            terms = self
+                .postings
+                .keys()
+                .filter(|term| term.chars().count().abs_diff(length) <= allowed)
+                .filter(|term| allowed > 0 && edit_distance(word, term) <= allowed)
+                .map(|term| (term, FUZZY_FACTOR))
+                .collect();
+        }
+        terms
+    }
+
+    fn insert_words(&mut self, doc: DocId, event: &Event) {
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        let mut add = |text: &str, weight: f32| {
+            for word in tokenize(text) {
+                *weights.entry(word).or_default() += weight;
+            }
+        };
+        add(&event.title, TITLE_WEIGHT);
+        add(&event.description, DESCRIPTION_WEIGHT);
+        add(&event.location, LOCATION_WEIGHT);
+        for category in &event.categories {
+            add(category, CATEGORY_WEIGHT);
+        }
+        for tag in &event.tags {
+            add(tag, TAG_WEIGHT);
+        }
+        for attendee in &event.attendees {
+            add(&attendee.name, ATTENDEE_WEIGHT);
+            add(&attendee.email, ATTENDEE_WEIGHT);
+        }
+        let mut words = Vec::with_capacity(weights.len());
+        for (word, weight) in weights {
+            self.postings
+                .entry(word.clone())
+                .or_default()
+                .insert(doc, weight);
+            words.push(word);
+        }
+        self.words.insert(doc, words);
+    }
+
+    fn remove_words(&mut self, doc: DocId) {
+        for word in self.words.remove(&doc).unwrap_or_default() {
+            if let Some(postings) = self.postings.get_mut(&word) {
+                postings.remove(&doc);
+                if postings.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+}
+
+// Lowercase words of letters and digits, "ann@example.org" gives "ann", "example" and "org"
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str, description: &str) -> Event {
+        Event {
+            title: title.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn index() -> SearchIndex {
+        SearchIndex::build(&[
+            event("Team meeting", "Weekly sync"),
+            event("Dentist", "Checkup, bring the insurance card"),
+            event("Lunch with the team", ""),
+            event("Meetup", "Rust user group"),
+        ])
+    }
+
+    fn positions(index: &SearchIndex, text: &str) -> Vec<usize> {
+        let mut positions: Vec<usize> = index.search(text).into_iter().map(|(p, _)| p).collect();
+        positions.sort();
+        positions
+    }
+
+    #[test]
+    fn exact_words_rank_by_field() {
+        let index = index();
+        assert_eq!(positions(&index, "TEAM"), [0, 2]);
+        assert_eq!(positions(&index, "card"), [1]);
+        // Found in a title counts more than in a description
+        assert!(index.search("dentist")[0].1 > index.search("weekly")[0].1);
+        // Every word has to be found
+        assert_eq!(positions(&index, "team lunch"), [2]);
+        assert!(index.search("team dentist").is_empty());
+        assert!(index.search("  ,  ").is_empty());
+    }
+
+    #[test]
+    fn prefixes_match_longer_words() {
+        let index = index();
+        assert_eq!(positions(&index, "meet"), [0, 3]);
+        assert_eq!(positions(&index, "dent"), [1]);
+        assert_eq!(positions(&index, "meetup"), [3]);
+        // The whole word ranks above a word it starts
+        let found = index.search("meet");
+        let score = |position| found.iter().find(|(p, _)| *p == position).unwrap().1;
+        assert_eq!(score(0), score(3));
+        assert!(index.search("meetup")[0].1 > score(3));
+    }
+
+    #[test]
+    fn typos_match_when_nothing_else_does() {
+        let index = index();
+        assert_eq!(positions(&index, "dentsit"), [1]);
+        assert_eq!(positions(&index, "insurence"), [1]);
+        assert_eq!(positions(&index, "lucnh"), [2]);
+        assert_eq!(positions(&index, "rsut"), [3]);
+        // Words up to three letters have to be exact
+        assert!(index.search("tem").is_empty());
+        // A typo counts less than the word itself
+        assert!(index.search("dentsit")[0].1 < index.search("dentist")[0].1);
+    }
+
+    #[test]
+    fn follows_changes_to_the_events() {
+        let mut index = index();
+        index.remove(1);
+        assert!(index.search("dentist").is_empty());
+        // The events after the removed one moved up
+        assert_eq!(positions(&index, "lunch"), [1]);
+        index.replace(1, &event("Dinner with the team", ""));
+        assert!(index.search("lunch").is_empty());
+        assert_eq!(positions(&index, "dinner"), [1]);
+        index.push(&event("Dentist again", ""));
+        assert_eq!(positions(&index, "dentist"), [3]);
+        assert_eq!(
+            tokenize("ann@example.org, Ann!"),
+            ["ann", "example", "org", "ann"]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::fmt;
+
+use crate::miscs::arg_parsing::did_you_mean;
+
+use super::category::category_names;
+use super::contact::contact_list;
+use super::event::{Event, Recurrence, MAX_INTERVAL};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,   // The event is rejected on add, edit and import
+    Warning, // Probably a mistake, but the event is still saved
+}
+
+// A problem with one field of an event, e.g. `error: recurrence.hour: 99 is out of range (0-23)`
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.field, self.message)
+    }
+}
+
+impl Issue {
+    fn error(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+// Every problem found in the event, errors first
+pub fn validate_event(event: &Event) -> Vec<Issue> {
+    let mut issues = vec![];
+    if event.title.trim().is_empty() {
+        issues.push(Issue::error("title", "is empty"));
+    }
+    if event.end_time < event.start_time {
+        issues.push(Issue::error(
+            "end_time",
+            format!(
+                "{} is before the start {}",
+                event.end_time.format("%Y-%m-%d %H:%M"),
+                event.start_time.format("%Y-%m-%d %H:%M")
+            ),
+        ));
+    }
+
+    match &event.recurrence {
+        Some(recurrence) => {
+            validate_recurrence(recurrence, &mut issues);
+            if !event.is_recurring {
+                issues.push(Issue::warning(
+                    "is_recurring",
+                    "is false although a recurrence is set",
+                ));
+            }
+        }
+        // Reminders of a repeating event go by its recurrence, there has to be one
+        None if event.is_recurring => {
+            issues.push(Issue::error(
+                "is_recurring",
+                "is true but no recurrence is set",
+            ));
+        }
+        None => {}
+    }
+
+    let contacts = contact_list();
+    for (i, attendee) in event.attendees.iter().enumerate() {
+        let field = format!("attendees[{}]", i + 1);
+        if let Some(id) = attendee.contact_id() {
+            if !contacts.iter().any(|contact| contact.contact_id == id) {
+                issues.push(Issue::warning(
+                    format!("{}.attendee_id", field),
+                    format!("unknown contact '{}'", id),
+                ));
+            }
+        }
+        if !is_valid_email(&attendee.email) {
+            issues.push(Issue::error(
+                format!("{}.email", field),
+                format!("'{}' is not a valid email address", attendee.email),
+            ));
+        }
+        if attendee.name.trim().is_empty() {
+            issues.push(Issue::warning(format!("{}.name", field), "is empty"));
+        }
+    }
+
+    for (i, notification) in event.notification_settings.iter().enumerate() {
+        if notification.notify_before < 0 {
+            issues.push(Issue::warning(
+                format!("notification_settings[{}]", i + 1),
+                format!(
+                    "reminds {} minutes after the start",
+                    -notification.notify_before
+                ),
+            ));
+        }
+    }
+
+    let known = category_names();
+    let known: Vec<&str> = known.iter().map(|category| category.as_str()).collect();
+    for category in &event.categories {
+        if category.trim().is_empty() {
+            issues.push(Issue::error("categories", "contains an empty name"));
+        } else if !known.contains(&category.as_str()) {
+            let hint = match did_you_mean(category, &known) {
+                Some(suggestion) => format!(", did you mean '{}'?", suggestion),
+                // Quoted so names with spaces or commas stay one argument
+                None => format!(
+                    ", add it with `add category {}`",
+                    shlex::try_quote(category).unwrap_or(category.into())
+                ),
+            };
+            issues.push(Issue::warning(
+                "categories",
+                format!("unknown category '{}'{}", category, hint),
+            ));
+        }
+    }
+
+    if event.tags.iter().any(|tag| tag.trim().is_empty()) {
+        issues.push(Issue::error("tags", "contains an empty tag"));
+    }
+
+    issues.sort_by_key(|issue| !issue.is_error());
+    issues
+}
+
+fn validate_recurrence(recurrence: &Recurrence, issues: &mut Vec<Issue>) {
+    if !(1..=MAX_INTERVAL).contains(&recurrence.interval) {
+        issues.push(Issue::error(
+            "recurrence.interval",
+            format!(
+                "{} is out of range (1-{})",
+                recurrence.interval, MAX_INTERVAL
+            ),
+        ));
+    }
+    let ranges = [
+        ("minute", recurrence.minute, 0, 59),
+        ("hour", recurrence.hour, 0, 23),
+        ("day", recurrence.day, 1, 31),
+        ("month", recurrence.month, 1, 12),
+        ("year", recurrence.year, 1, 9999),
+    ];
+    for (name, value, low, high) in ranges {
+        if let Some(value) = value.filter(|value| !(low..=high).contains(value)) {
+            issues.push(Issue::error(
+                format!("recurrence.{}", name),
+                format!("{} is out of range ({}-{})", value, low, high),
+            ));
+        }
+    }
+    if let Some(end_date) = recurrence.end_date {
+        if end_date < recurrence.start_date {
+            issues.push(Issue::error(
+                "recurrence.end_date",
+                format!(
+                    "{} is before the start {}",
+                    end_date.format("%Y-%m-%d"),
+                    recurrence.start_date.format("%Y-%m-%d")
+                ),
+            ));
+        }
+    }
+}
+
+// Deliberately loose: one @, something before it and a dotted domain after it
+pub fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && !email.chars().any(char::is_whitespace)
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(host, rest)| !host.is_empty() && !rest.is_empty())
+                && !domain.ends_with('.')
+        }
+        None => false,
+    }
+}
+
+// Errors stop the event, joined into one message; the warnings are returned to be shown
+pub fn check_event(event: &Event) -> Result<Vec<Issue>, String> {
+    let (errors, warnings): (Vec<Issue>, Vec<Issue>) = validate_event(event)
+        .into_iter()
+        .partition(|issue| issue.is_error());
+    if errors.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(errors
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect::<Vec<_>>()
+            .join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event::{Attendee, RecurrenceFrequency};
+    use chrono::{Duration, Local};
+
+    fn recurrence(interval: i64) -> Recurrence {
+        Recurrence {
+            frequency: RecurrenceFrequency::Yearly,
+            interval,
+            start_date: Local::now(),
+            end_date: None,
+            minute: None,
+            hour: None,
+            day: None,
+            week_day: None,
+            week_days: vec![],
+            month: None,
+            year: None,
+        }
+    }
+
+    fn errors(event: &Event) -> String {
+        check_event(event).unwrap_err()
+    }
+
+    #[test]
+    fn accepts_a_plain_event() {
+        let event = Event {
+            title: "Lunch".to_string(),
+            ..Default::default()
+        };
+        assert!(check_event(&event).unwrap().is_empty());
+    }
+
+    #[test]
+    fn names_every_error() {
+        let mut event = Event {
+            title: " ".to_string(),
+            ..Default::default()
+        };
+        event.end_time = event.start_time - Duration::hours(1);
+        event.is_recurring = true;
+        let message = errors(&event);
+        assert!(message.starts_with("title: is empty; end_time: "));
+        assert!(message.ends_with("; is_recurring: is true but no recurrence is set"));
+    }
+
+    #[test]
+    fn bounds_recurrences() {
+        let mut event = Event {
+            title: "Yearly".to_string(),
+            is_recurring: true,
+            recurrence: Some(recurrence(400_000_000)),
+            ..Default::default()
+        };
+        assert_eq!(
+            errors(&event),
+            "recurrence.interval: 400000000 is out of range (1-1000)"
+        );
+        let mut yearly = recurrence(0);
+        yearly.hour = Some(24);
+        yearly.year = Some(30000);
+        event.recurrence = Some(yearly);
+        assert_eq!(
+            errors(&event),
+            "recurrence.interval: 0 is out of range (1-1000); \
+             recurrence.hour: 24 is out of range (0-23); \
+             recurrence.year: 30000 is out of range (1-9999)"
+        );
+        event.recurrence = Some(recurrence(MAX_INTERVAL));
+        assert!(check_event(&event).is_ok());
+        event.is_recurring = false;
+        let warnings = check_event(&event).unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "warning: is_recurring: is false although a recurrence is set"
+        );
+    }
+
+    #[test]
+    fn checks_emails_loosely() {
+        assert!(is_valid_email("ann@example.org"));
+        assert!(is_valid_email("a.b+c@mail.example.org"));
+        for email in [
+            "",
+            "ann",
+            "@example.org",
+            "ann@",
+            "ann@org",
+            "ann@example.",
+            "a n@x.org",
+        ] {
+            assert!(!is_valid_email(email), "{}", email);
+        }
+        let event = Event {
+            title: "Call".to_string(),
+            attendees: vec![Attendee {
+                attendee_id: String::new(),
+                name: "Ann".to_string(),
+                email: "ann@".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            errors(&event),
+            "attendees[1].email: 'ann@' is not a valid email address"
+        );
+
+        // Still invited, but the contact it was linked to is gone
+        let event = Event {
+            attendees: vec![Attendee {
+                attendee_id: "c-removed".to_string(),
+                email: "ann@example.org".to_string(),
+                ..event.attendees[0].clone()
+            }],
+            ..event
+        };
+        let warnings = check_event(&event).unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "warning: attendees[1].attendee_id: unknown contact 'c-removed'"
+        );
+    }
+}
@@ -1,278 +1,1730 @@
 mod background_service;
 mod events;
 mod miscs;
+mod tui;
 
 use background_service::service_main;
 use chrono::DateTime;
+use chrono::Duration;
 use chrono::Local;
+use chrono::{Months, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use clap::{CommandFactory, Parser};
 use events::{
+    category::{
+        add_category, category_duration, category_reminders, check_color, find_category, is_within,
+        load_categories, normalize_name, remove_category, rename_category, save_categories,
+        update_category, Category, CATEGORIES,
+    },
+    conflicts::WorkingHours,
+    contact::{
+        add_contact, contact_list, find_contact, load_contacts, parse_vcards, remove_contact,
+        resolve_attendee, save_contacts, update_contact, Contact,
+    },
     event::{
-        load_categories, save_categories, Attendee, Event, Notification, NotificationMethod,
-        CATEGORIES,
+        Attendee, Event, Notification, NotificationMethod, ParticipationStatus, Priority,
+        PriorityFilter, Role, Transparency,
     },
     event_manager::{EventManager, EventManagerMode, SearchType},
+    ical::{parse_calendar, write_calendar},
+    invitation::{
+        apply_reply, from_organizer, invitations, organized_by, outdated, raises_sequence,
+        Invitation, Mailer, Method,
+    },
+    query::{join_words, Query, QueryOptions},
+    validation::{check_event, validate_event},
 };
 use miscs::{
-    help::{
-        print_add_help, print_clear_help, print_cls_help, print_edit_help, print_help,
-        print_list_help, print_remove_help, print_save_help, print_search_help,
+    arg_parsing::is_data_syntax,
+    calendar::{month_start, week_start, write_day, write_month, write_week},
+    cli::{
+        AddTarget, CalView, CategoryAction, CategorySettings, Cli, Commands, ConfigAction,
+        ContactAction, ContactDetails, EventArgs, ListTarget, ServiceAction, ViewAction,
     },
-    utils::{clear_screen, date_from_str, get_path, time_from_str},
+    completion::ReplHelper,
+    config::Config,
+    error::AppError,
+    natural::{parse_datetime, parse_reminder},
+    output::{
+        print_categories, print_conflicts, print_contacts, print_event, print_events,
+        print_free_slots, span_text, write_event_details, ExportFormat, OutputFormat,
+    },
+    render::Style,
+    utils::{
+        clear_screen, date_from_str, get_path, local_from_naive, parse_bounds, parse_duration,
+        parse_range, parse_time_zone, split_command_line, time_from_str, wall_time,
+        zoned_from_naive,
+    },
+    views::{View, Views},
 };
+use rustyline::{error::ReadlineError, history::FileHistory, CompletionType, Editor};
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, ExitCode};
 use std::sync::{Arc, Mutex};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+struct App {
+    event_manager: Arc<Mutex<EventManager>>,
+    data_dir: PathBuf,
+    config: Config,
+    interactive: bool, // true while running the interactive loop
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
 
-    let path = get_path();
+    // Completion scripts go to stdout, so nothing else may be printed before them
+    if let Some(Commands::Completions { shell }) = cli.command {
+        print_completions(shell);
+        return ExitCode::SUCCESS;
+    }
 
-    let event_manager: Arc<Mutex<EventManager>>;
-    let data_file_path: PathBuf;
-    let category_file_path: PathBuf;
+    let data_dir = match get_path() {
+        Some(data_dir) => data_dir,
+        None => {
+            let error = AppError::Io("can't create config directory".to_string());
+            eprintln!("Error: {}", error);
+            return error.into();
+        }
+    };
+    let data_file_path = data_dir.join("dates.json");
 
-    if let Some(fp) = &path {
-        data_file_path = fp.clone().join("dates.json");
-        category_file_path = fp.clone().join("categories.txt");
+    let config = match Config::load(&data_dir) {
+        Ok(config) => config,
+        Err(e) => {
+            let error = AppError::Invalid(e);
+            eprintln!("Error: {}", error);
+            return error.into();
+        }
+    };
 
-        event_manager = EventManager::new(data_file_path.clone(), false, EventManagerMode::Active);
-        load_categories(&category_file_path);
-    } else {
-        eprintln!("error cant create Config file");
-        return;
+    // Categories and contacts come first, new events read their defaults and attendees
+    if let Err(e) = load_categories(&data_dir).and_then(|_| load_contacts(&data_dir)) {
+        let error = AppError::Invalid(e);
+        eprintln!("Error: {}", error);
+        return error.into();
     }
+    let event_manager = EventManager::new(data_file_path, false, EventManagerMode::Active);
 
-    event_manager.lock().unwrap().read_events_from_file();
+    let mut app = App {
+        event_manager,
+        data_dir,
+        config,
+        interactive: cli.command.is_none(),
+    };
 
-    if args.len() > 1 {
-        if args[1] == "service" {
-            if args.len() > 2 {
-                match args[2].as_str() {
-                    "start" => {
-                        service_start();
-                    }
-                    "stop" => {
-                        service_stop();
-                    }
-                    "restart" => {
-                        service_restart();
-                    }
-                    _ => {
-                        eprintln!("Unknown service command: {}", args[2]);
-                    }
-                }
-            } else {
-                eprintln!("Service command required");
+    let result = match cli.command {
+        Some(command) => run_command(command, &mut app),
+        None if !cli.repl && io::stdin().is_terminal() && io::stdout().is_terminal() => {
+            tui::run(Arc::clone(&app.event_manager)).map_err(|e| AppError::Io(e.to_string()))
+        }
+        None => {
+            if let Err(error) = list_events(
+                &app.event_manager,
+                OutputFormat::Table,
+                &ListFilter::default(),
+            ) {
+                eprintln!("Error: {}", error);
             }
-        } else {
-            command_mode(&event_manager, &args[1..]);
+            loop_mode(&mut app);
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            error.into()
         }
-    } else {
-        event_manager.lock().unwrap().list_events();
-        loop_mode(&event_manager);
     }
-    save_categories(&category_file_path);
 }
 
-fn service_start() {
-    service_main().expect("Failed to start background service");
+fn print_completions(shell: clap_complete::Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
 }
 
-fn service_stop() {
-    let pid = fs::read_to_string("/tmp/RustyPlannerDaemon.pid").expect("Failed to read PID file");
-    let _output = Command::new("kill")
+fn service_start(interactive: bool) -> Result<(), AppError> {
+    if interactive {
+        // Daemonizing would end the interactive session, so start a separate process instead
+        let exe = env::current_exe().map_err(|e| AppError::Service(e.to_string()))?;
+        Command::new(exe)
+            .args(["service", "start"])
+            .spawn()
+            .map_err(|e| AppError::Service(format!("failed to start background service: {}", e)))?;
+        println!("Service started");
+        return Ok(());
+    }
+    service_main()
+        .map_err(|e| AppError::Service(format!("failed to start background service: {}", e)))
+}
+
+fn service_stop() -> Result<(), AppError> {
+    let pid = fs::read_to_string("/tmp/RustyPlannerDaemon.pid")
+        .map_err(|e| AppError::Service(format!("service is not running ({})", e)))?;
+    let output = Command::new("kill")
         .arg(pid.trim())
         .output()
-        .expect("Failed to stop background service");
-    println!("Service stopped, output: {:?}", _output);
-    let err_msg = format!("error trying to kill background service. pid: {}", pid);
-    fs::remove_file("/tmp/RustyPlannerDaemon.pid").expect(&err_msg);
+        .map_err(|e| AppError::Service(format!("failed to stop background service: {}", e)))?;
+    if !output.status.success() {
+        return Err(AppError::Service(format!(
+            "error trying to kill background service. pid: {}",
+            pid.trim()
+        )));
+    }
+    println!("Service stopped");
+    fs::remove_file("/tmp/RustyPlannerDaemon.pid").map_err(|e| AppError::Io(e.to_string()))
 }
 
-fn service_restart() {
-    service_stop();
-    service_start();
+fn service_restart(interactive: bool) -> Result<(), AppError> {
+    service_stop()?;
+    service_start(interactive)
 }
 
-fn loop_mode(event_manager: &Arc<Mutex<EventManager>>) {
+fn loop_mode(app: &mut App) {
+    let history_path = app.data_dir.join("history.txt");
+    let config = rustyline::Config::builder()
+        .max_history_size(1000)
+        .and_then(|builder| builder.history_ignore_dups(true))
+        .map(|builder| {
+            builder
+                .completion_type(CompletionType::List)
+                .auto_add_history(false)
+                .build()
+        });
+    let mut editor = match config.and_then(Editor::<ReplHelper, FileHistory>::with_config) {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("Failed to start the interactive mode: {}", e);
+            return;
+        }
+    };
+    editor.set_helper(Some(ReplHelper::new(
+        app.event_manager.clone(),
+        app.data_dir.clone(),
+    )));
+    // A missing history file just means this is the first session
+    let _ = editor.load_history(&history_path);
+
     loop {
-        let mut input = String::new();
+        let input = match editor.readline("RustyPlanner> ") {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("Failed to read line: {}", e);
+                break;
+            }
+        };
 
-        // Read from standard input
-        print!("Please enter some input: ");
-        io::stdout().flush().unwrap();
+        let trimmed = input.trim();
 
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
+        if trimmed.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(trimmed);
+        if matches!(trimmed.to_lowercase().as_str(), "exit" | "quit") {
+            break;
+        }
 
-        let trimmed = input.trim();
+        let Some(words) = split_command_line(trimmed) else {
+            eprintln!("Unbalanced quotes in: {}", trimmed);
+            continue;
+        };
+        match Cli::try_parse_from(std::iter::once("RustyPlanner".to_string()).chain(words)) {
+            Ok(Cli {
+                command: Some(command),
+                ..
+            }) => {
+                if let Err(error) = run_command(command, app) {
+                    eprintln!("Error: {}", error);
+                }
+            }
+            Ok(Cli { command: None, .. }) => {}
+            Err(e) => {
+                let _ = e.print();
+            }
+        }
+    }
 
-        //println!("You entered: {}", trimmed);
+    if let Err(e) = editor.save_history(&history_path) {
+        eprintln!("Failed to save history: {}", e);
+    }
+    if let Err(error) = save(&app.event_manager) {
+        eprintln!("Error: {}", error);
+    }
+}
 
-        if trimmed.to_lowercase() == "exit" {
-            break;
-        } else {
-            parse_commands(trimmed, event_manager);
+fn save(event_manager: &Arc<Mutex<EventManager>>) -> Result<(), AppError> {
+    event_manager
+        .lock()
+        .unwrap()
+        .save_events()
+        .map_err(AppError::Io)?;
+    eprintln!("Events saved successfully.");
+    Ok(())
+}
+
+fn find_event(
+    event_manager: &Arc<Mutex<EventManager>>,
+    reference: &str,
+) -> Result<usize, AppError> {
+    event_manager
+        .lock()
+        .unwrap()
+        .find_event(reference)
+        .ok_or_else(|| AppError::NotFound(format!("no event with index or ID '{}'", reference)))
+}
+
+fn list_categories(format: OutputFormat) -> Result<(), AppError> {
+    // A copy, rendering looks up inherited colours and icons itself
+    let categories = CATEGORIES.lock().unwrap().clone();
+    print_categories(&categories, format).map_err(|e| AppError::Io(e.to_string()))
+}
+
+fn new_category(name: &str, settings: &CategorySettings, app: &App) -> Result<(), AppError> {
+    let name = normalize_name(name).map_err(AppError::Invalid)?;
+    let mut category = Category::new(&name);
+    apply_category_settings(&mut category, settings)?;
+    add_category(category).map_err(AppError::Invalid)?;
+    save_categories(&app.data_dir).map_err(AppError::Io)?;
+    println!("Category added: {}", name);
+    Ok(())
+}
+
+// Empty values clear a setting so it's inherited from the parent category again
+fn apply_category_settings(
+    category: &mut Category,
+    settings: &CategorySettings,
+) -> Result<(), AppError> {
+    let cleared = |value: &String| (!value.trim().is_empty()).then(|| value.trim().to_string());
+    if let Some(color) = &settings.color {
+        category.color = cleared(color);
+        if let Some(color) = &category.color {
+            check_color(color).map_err(AppError::Invalid)?;
+        }
+    }
+    if let Some(icon) = &settings.icon {
+        category.icon = cleared(icon);
+    }
+    if let Some(duration) = &settings.duration {
+        category.duration = cleared(duration);
+        if let Some(duration) = &category.duration {
+            let length = parse_duration(duration).map_err(AppError::Invalid)?;
+            if length <= Duration::zero() {
+                return Err(AppError::Invalid(format!(
+                    "duration '{}' must be positive",
+                    duration
+                )));
+            }
         }
     }
+    if let Some(transparency) = &settings.transparency {
+        category.transparency = match transparency.as_str() {
+            "" => None,
+            value => Some(Transparency::parse(value).map_err(AppError::Invalid)?),
+        };
+    }
+    if settings.no_notify {
+        category.notification_settings.clear();
+    } else if !settings.notify.is_empty() {
+        category.notification_settings = settings
+            .notify
+            .iter()
+            .map(|reminder| parse_reminder(reminder).map_err(AppError::Invalid))
+            .collect::<Result<_, _>>()?;
+    }
+    Ok(())
 }
 
-fn command_mode(event_manager: &Arc<Mutex<EventManager>>, commands: &[String]) {
-    let command = commands.join(" ");
+// Renames or merges a category in the list and in every event that uses it
+fn move_category(from: &str, to: &str, merge: bool, app: &App) -> Result<(), AppError> {
+    let from = normalize_name(from).map_err(AppError::Invalid)?;
+    let to = normalize_name(to).map_err(AppError::Invalid)?;
+    rename_category(&from, &to, merge).map_err(AppError::Invalid)?;
+    let count = app
+        .event_manager
+        .lock()
+        .unwrap()
+        .recategorize(&from, Some(&to));
+    save_categories(&app.data_dir).map_err(AppError::Io)?;
+    if count > 0 {
+        save(&app.event_manager)?;
+    }
+    let verb = if merge { "merged into" } else { "renamed to" };
+    eprintln!(
+        "Category {} {} {} ({} events updated)",
+        from, verb, to, count
+    );
+    Ok(())
+}
+
+// Emails given replace all of them, an empty phone or notes removes them
+fn apply_contact_details(contact: &mut Contact, details: &ContactDetails) {
+    if !details.emails.is_empty() {
+        contact.emails = details
+            .emails
+            .iter()
+            .map(|email| email.trim().to_string())
+            .collect();
+    }
+    if let Some(phone) = &details.phone {
+        contact.phone = phone.trim().to_string();
+    }
+    if let Some(notes) = &details.notes {
+        contact.notes = notes.trim().to_string();
+    }
+}
 
-    parse_commands(&command, event_manager);
+// Saves the contacts and brings the attendees linked to them, or having their emails, up to date
+fn relink_contacts(contacts: &[Contact], app: &App) -> Result<(), AppError> {
+    save_contacts(&app.data_dir).map_err(AppError::Io)?;
+    let count: usize = {
+        let mut manager = app.event_manager.lock().unwrap();
+        contacts
+            .iter()
+            .map(|contact| manager.relink_contact(&contact.contact_id, Some(contact)))
+            .sum()
+    };
+    if count > 0 {
+        save(&app.event_manager)?;
+        eprintln!("{} events updated", count);
+    }
+    Ok(())
 }
 
-// #[allow(unused_mut)]
-fn parse_commands(command: &str, event_manager: &Arc<Mutex<EventManager>>) {
-    match command {
-        _ if command.starts_with("add") => {
-            let input = command.strip_prefix("add").unwrap_or("").trim();
-            match input {
-                _ if input.starts_with("event") => {
-                    let string = input.strip_prefix("event").unwrap_or(command).trim();
-                    add_event_loop(string, event_manager);
-                }
-                _ if input.starts_with("category") => {
-                    let category = input.strip_prefix("category").unwrap_or("").trim();
-                    if !category.is_empty() {
-                        CATEGORIES.lock().unwrap().push(category.to_string());
-                        println!("Category added: {}", category);
-                    } else {
-                        eprintln!("Invalid category name");
+// Adds the contacts of vCard files. A card with the email of a known contact adds its other
+// emails, and the phone and notes if the contact has none, to that contact instead.
+fn import_contacts(files: &[PathBuf], app: &App) -> Result<(), AppError> {
+    let mut changed = vec![];
+    let (mut added, mut updated) = (0, 0);
+    for file in files {
+        let data = fs::read_to_string(file)
+            .map_err(|e| AppError::Io(format!("{}: {}", file.display(), e)))?;
+        let cards = parse_vcards(&data)
+            .map_err(|e| AppError::Invalid(format!("{}: {}", file.display(), e)))?;
+        for card in cards {
+            let known = contact_list()
+                .into_iter()
+                .find(|contact| card.emails.iter().any(|email| contact.has_email(email)));
+            let result = match known {
+                Some(mut contact) => {
+                    for email in &card.emails {
+                        if !contact.has_email(email) {
+                            contact.emails.push(email.clone());
+                        }
+                    }
+                    if contact.phone.is_empty() {
+                        contact.phone = card.phone.clone();
+                    }
+                    if contact.notes.is_empty() {
+                        contact.notes = card.notes.clone();
                     }
+                    update_contact(contact.clone()).map(|_| {
+                        updated += 1;
+                        contact
+                    })
                 }
-                _ => print_add_help(),
-            }
-        }
-        _ if command.starts_with("edit") => {
-            let index = command.strip_prefix("edit").unwrap_or("").trim();
-            let index = index.parse::<usize>().unwrap_or(0);
-            if index > 0 {
-                update_event(
-                    event_manager
-                        .lock()
-                        .unwrap()
-                        .get_event_mut(index - 1)
-                        .unwrap(),
-                );
-                event_manager.lock().unwrap().save_events();
-            } else {
-                eprintln!("Invalid index: {}", index);
+                None => add_contact(card.clone()).map(|contact_id| {
+                    added += 1;
+                    Contact {
+                        contact_id,
+                        ..card.clone()
+                    }
+                }),
+            };
+            match result {
+                Ok(contact) => changed.push(contact),
+                Err(e) => eprintln!("warning: {}: {}: {}", file.display(), card.name, e),
             }
         }
-        _ if command.starts_with("remove") => {
-            let index = command.strip_prefix("remove").unwrap_or("").trim();
-            let index = index.parse::<usize>().unwrap_or(0);
-            if index > 0 {
-                event_manager.lock().unwrap().remove_event(index - 1);
-                event_manager.lock().unwrap().save_events();
+    }
+    eprintln!("Contacts imported: {} added, {} updated", added, updated);
+    relink_contacts(&changed, app)
+}
+
+// `list --tag ... --priority ...`, an event needs every tag given, ignoring case
+#[derive(Default)]
+struct ListFilter {
+    tags: Vec<String>,
+    priority: Option<PriorityFilter>,
+}
+
+impl ListFilter {
+    fn new(tags: Vec<String>, priority: Option<&str>) -> Result<Self, AppError> {
+        let priority = priority
+            .map(PriorityFilter::parse)
+            .transpose()
+            .map_err(AppError::Usage)?;
+        Ok(Self { tags, priority })
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        self.tags
+            .iter()
+            .all(|tag| event.tags.iter().any(|own| own.eq_ignore_ascii_case(tag)))
+            && self
+                .priority
+                .is_none_or(|priority| priority.matches(event.priority))
+    }
+}
+
+fn list_events(
+    event_manager: &Arc<Mutex<EventManager>>,
+    format: OutputFormat,
+    filter: &ListFilter,
+) -> Result<(), AppError> {
+    let manager = event_manager.lock().unwrap();
+    let events: Vec<(usize, &Event)> = manager
+        .events()
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| filter.matches(event))
+        .collect();
+    print_events(&events, format).map_err(|e| AppError::Io(e.to_string()))
+}
+
+fn run_command(command: Commands, app: &mut App) -> Result<(), AppError> {
+    let event_manager = app.event_manager.clone();
+    match command {
+        Commands::Add(args) => match args.target {
+            Some(AddTarget::Event(event_args)) => add_event(&event_args, app),
+            Some(AddTarget::Category { name, settings }) => new_category(&name, &settings, app),
+            None => add_event(&args.event, app),
+        },
+        Commands::Edit { event, set } => {
+            let index = find_event(&event_manager, &event)?;
+            // Apply all changes to a copy so an invalid field or result leaves the event untouched
+            let mut updated = event_manager
+                .lock()
+                .unwrap()
+                .get_event(index)
+                .unwrap()
+                .clone();
+            let was_urgent = updated.priority == Priority::Urgent;
+            if set.is_empty() {
+                update_event(&mut updated);
             } else {
-                eprintln!("Invalid index: {}", index);
-            }
-        }
-        _ if command.starts_with("help") => {
-            let command_help = command.strip_prefix("help ").unwrap_or("");
-            match command_help {
-                "add" => print_add_help(), // Assuming print_help() provides help for the "add" command
-                "save" => print_save_help(),
-                "remove" => print_remove_help(),
-                "edit" => print_edit_help(),
-                "cls" => print_cls_help(),
-                "list" => print_list_help(),
-                "clear" => print_clear_help(),
-                "search" => print_search_help(),
-                "" => print_help(), // Default help message
-                _ => print_help(),  // Fallback for unrecognized commands
-            }
-        }
-        _ if command.starts_with("search") => {
-            let (search_type_str, search_query) = command
-                .strip_prefix("search")
-                .unwrap_or("")
-                .trim()
-                .split_once(" ")
-                .unwrap_or(("", ""));
-
-            for (i, event) in event_manager
+                for assignment in &set {
+                    let (key, value) = assignment.split_once('=').ok_or_else(|| {
+                        AppError::Usage(format!("expected FIELD=VALUE, got '{}'", assignment))
+                    })?;
+                    updated
+                        .set_field(key.trim(), value)
+                        .map_err(AppError::Invalid)?;
+                }
+            }
+            if !was_urgent {
+                add_urgent_reminder(&mut updated, &app.config)?;
+            }
+            check_events("", std::slice::from_ref(&updated))?;
+            warn_conflicts(&event_manager.lock().unwrap(), "", &updated, Some(index));
+            let old = event_manager
                 .lock()
                 .unwrap()
-                .search_event(search_query, SearchType::from(search_type_str))
+                .get_event(index)
+                .unwrap()
+                .clone();
+            if raises_sequence(&old, &updated) {
+                updated.sequence += 1;
+            }
+            event_manager.lock().unwrap().replace_event(index, updated);
+            save(&event_manager)?;
+            send_invitations(app, Some(&old), Some(index));
+            Ok(())
+        }
+        Commands::Remove { event } => {
+            let index = find_event(&event_manager, &event)?;
+            let old = event_manager.lock().unwrap().remove_event(index);
+            save(&event_manager)?;
+            if let Some(old) = old {
+                send_invitations(app, Some(&old), None);
+            }
+            Ok(())
+        }
+        Commands::Invite { event } => {
+            let index = find_event(&event_manager, &event)?;
+            let event = event_manager
+                .lock()
+                .unwrap()
+                .get_event(index)
+                .unwrap()
+                .clone();
+            if !organized_by(&event, app.config.user_email.trim()) {
+                return Err(AppError::Usage(format!(
+                    "only the organizer sends invitations, and {} is organized by {}",
+                    event.event_id,
+                    event
+                        .organizer
+                        .as_ref()
+                        .map(|organizer| organizer.email.as_str())
+                        .unwrap_or("nobody, set one with --set organizer=<you>")
+                )));
+            }
+            let invitation = invitations(None, Some(&pin_uid(app, index)?))
+                .pop()
+                .ok_or_else(|| {
+                    AppError::Usage(format!("{} has no attendees to invite", event.event_id))
+                })?;
+            let mailer = Mailer::new(&app.config).map_err(AppError::Service)?;
+            mailer
+                .send(&invitation)
+                .map_err(|e| AppError::Service(format!("sending the invitation failed: {}", e)))?;
+            report_sent(&invitation);
+            Ok(())
+        }
+        Commands::List {
+            range,
+            from,
+            to,
+            tags,
+            priority,
+            format,
+            target,
+        } => match target {
+            None if range.len() == 1 && range[0].starts_with('@') => {
+                let filter = ListFilter::new(tags, priority.as_deref())?;
+                let manager = event_manager.lock().unwrap();
+                let mut results = view_results(app, &manager, &range[0])?;
+                results.retain(|(_, event)| filter.matches(event));
+                print_events(&results, format).map_err(|e| AppError::Io(e.to_string()))
+            }
+            None if !range.is_empty() || from.is_some() || to.is_some() => {
+                let now = Local::now();
+                let (start, end) = if range.is_empty() {
+                    parse_bounds(from.as_deref(), to.as_deref(), now)
+                } else {
+                    parse_range(&range.join(" "), now)
+                }
+                .map_err(AppError::Invalid)?;
+                let filter = ListFilter::new(tags, priority.as_deref())?;
+                let manager = event_manager.lock().unwrap();
+                let occurrences = manager.occurrences_between(start, end);
+                let events: Vec<(usize, &Event)> = occurrences
+                    .iter()
+                    .filter(|(_, event)| filter.matches(event))
+                    .map(|(index, event)| (*index, event))
+                    .collect();
+                print_events(&events, format).map_err(|e| AppError::Io(e.to_string()))
+            }
+            None | Some(ListTarget::Events) | Some(ListTarget::Event { event: None }) => {
+                list_events(
+                    &event_manager,
+                    format,
+                    &ListFilter::new(tags, priority.as_deref())?,
+                )
+            }
+            Some(ListTarget::Event { event: Some(event) }) => {
+                let index = find_event(&event_manager, &event)?;
+                let manager = event_manager.lock().unwrap();
+                print_event(index, manager.get_event(index).unwrap(), format)
+                    .map_err(|e| AppError::Io(e.to_string()))
+            }
+            Some(ListTarget::Categories) => list_categories(format),
+        },
+        Commands::Conflicts {
+            range,
+            from,
+            to,
+            format,
+        } => {
+            let now = Local::now();
+            let (start, end) = match (range.is_empty(), &from, &to) {
+                (true, None, None) => parse_range("next 30d", now),
+                (true, _, _) => parse_bounds(from.as_deref(), to.as_deref(), now),
+                (false, _, _) => parse_range(&range.join(" "), now),
+            }
+            .map_err(AppError::Invalid)?;
+            let manager = event_manager.lock().unwrap();
+            let conflicts = manager.conflicts_between(start, end);
+            print_conflicts(manager.events(), &conflicts, format)
+                .map_err(|e| AppError::Io(e.to_string()))
+        }
+        Commands::Free {
+            duration,
+            range,
+            from,
+            to,
+            between,
+            days,
+            categories,
+            format,
+        } => {
+            let length = parse_duration(&duration).map_err(AppError::Invalid)?;
+            if length <= Duration::zero() {
+                return Err(AppError::Invalid(format!(
+                    "'{}' is not a length of time",
+                    duration
+                )));
+            }
+            let hours = WorkingHours::parse(
+                between.as_deref().unwrap_or(&app.config.working_hours),
+                days.as_deref().unwrap_or(&app.config.working_days),
+            )
+            .map_err(AppError::Invalid)?;
+            let categories = categories
                 .iter()
-                .enumerate()
+                .map(|category| normalize_name(category))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(AppError::Invalid)?;
+            let now = Local::now();
+            let (start, end) = match (range.is_empty(), &from, &to) {
+                (true, None, None) => parse_range("next 7d", now),
+                (true, _, _) => parse_bounds(from.as_deref(), to.as_deref(), now),
+                (false, _, _) => parse_range(&range.join(" "), now),
+            }
+            .map_err(AppError::Invalid)?;
+            // Slots in the past are no use, the first one starts on the next quarter hour
+            let quarter = 15 * 60;
+            let next_quarter =
+                DateTime::from_timestamp((now.timestamp() + quarter - 1) / quarter * quarter, 0)
+                    .unwrap()
+                    .with_timezone(&Local);
+            let manager = event_manager.lock().unwrap();
+            let slots = manager.free_slots(start.max(next_quarter), end, length, &hours, |event| {
+                categories.is_empty()
+                    || event
+                        .categories
+                        .iter()
+                        .any(|category| categories.iter().any(|wanted| is_within(category, wanted)))
+            });
+            print_free_slots(&slots, format).map_err(|e| AppError::Io(e.to_string()))
+        }
+        Commands::Cal { view } => {
+            let now = Local::now();
+            let view = view.unwrap_or(CalView::Month { date: None });
+            let (CalView::Month { date } | CalView::Week { date } | CalView::Day { date }) = &view;
+            let date = match date {
+                Some(date) => parse_datetime(date, now, None)
+                    .or_else(|_| parse_range(date, now).map(|(start, _)| start))
+                    .map_err(AppError::Invalid)?
+                    .date_naive(),
+                None => now.date_naive(),
+            };
+            let (first, last) = match view {
+                CalView::Month { .. } => {
+                    let first = month_start(date);
+                    (first, first + Months::new(1))
+                }
+                CalView::Week { .. } => {
+                    let first = week_start(date);
+                    (first, first + Duration::weeks(1))
+                }
+                CalView::Day { .. } => (date, date + Duration::days(1)),
+            };
+            let to_local = |date: NaiveDate| local_from_naive(date.and_time(NaiveTime::MIN));
+            let start = to_local(first).map_err(AppError::Invalid)?;
+            let end = to_local(last).map_err(AppError::Invalid)?;
+            let occurrences = event_manager
+                .lock()
+                .unwrap()
+                .occurrences_between(start, end);
+
+            let style = Style::detect();
+            let mut out = io::stdout().lock();
+            match view {
+                CalView::Month { .. } => write_month(&mut out, &occurrences, date, &style),
+                CalView::Week { .. } => write_week(&mut out, &occurrences, date, &style),
+                CalView::Day { .. } => write_day(&mut out, &occurrences, date, &style),
+            }
+            .map_err(|e| AppError::Io(e.to_string()))
+        }
+        Commands::Search {
+            query,
+            ignore_case,
+            regex,
+            sort,
+            format,
+        } => {
+            let options = QueryOptions { ignore_case, regex };
+            // `search title team sync` searches one field for everything after it
+            let query = match query.as_slice() {
+                [field, rest @ ..] if !rest.is_empty() => match SearchType::from(field) {
+                    Some(search_type) => Query::field(search_type, &rest.join(" "), options),
+                    None => Query::parse(&join_words(&query), options),
+                },
+                _ => Query::parse(&join_words(&query), options),
+            }
+            .map_err(AppError::Invalid)?;
+            let manager = event_manager.lock().unwrap();
+            let results = search_sorted(&manager, &query, sort.as_deref());
+            print_events(&results, format).map_err(|e| AppError::Io(e.to_string()))
+        }
+        Commands::View {
+            name,
+            format,
+            action,
+        } => match (action, name) {
+            (
+                Some(ViewAction::Save {
+                    name,
+                    query,
+                    ignore_case,
+                    regex,
+                    sort,
+                }),
+                _,
+            ) => {
+                let mut views = Views::load(&app.data_dir).map_err(AppError::Invalid)?;
+                let view = View {
+                    query: join_words(&query),
+                    ignore_case,
+                    regex,
+                    sort,
+                };
+                views.insert(&name, view).map_err(AppError::Invalid)?;
+                views.save(&app.data_dir).map_err(AppError::Io)?;
+                eprintln!("View saved: {}", name);
+                Ok(())
+            }
+            (Some(ViewAction::List), _) | (None, None) => {
+                let views = Views::load(&app.data_dir).map_err(AppError::Invalid)?;
+                for (name, view) in views.iter() {
+                    let mut flags = vec![];
+                    if view.ignore_case {
+                        flags.push("-i".to_string());
+                    }
+                    if view.regex {
+                        flags.push("-r".to_string());
+                    }
+                    if let Some(sort) = &view.sort {
+                        flags.push(format!("--sort {}", sort));
+                    }
+                    match flags.is_empty() {
+                        true => println!("{} = {}", name, view.query),
+                        false => println!("{} = {} ({})", name, view.query, flags.join(" ")),
+                    }
+                }
+                Ok(())
+            }
+            (Some(ViewAction::Remove { name }), _) => {
+                let mut views = Views::load(&app.data_dir).map_err(AppError::Invalid)?;
+                views.remove(&name).map_err(AppError::NotFound)?;
+                views.save(&app.data_dir).map_err(AppError::Io)?;
+                eprintln!("View removed: {}", name);
+                Ok(())
+            }
+            (None, Some(name)) => show_view(app, &name, format),
+        },
+        Commands::Category { action } => match action {
+            CategoryAction::List => list_categories(OutputFormat::Table),
+            CategoryAction::Add { name, settings } => new_category(&name, &settings, app),
+            CategoryAction::Edit { name, settings } => {
+                let name = normalize_name(&name).map_err(AppError::Invalid)?;
+                let mut category = find_category(&name).map_err(AppError::NotFound)?;
+                apply_category_settings(&mut category, &settings)?;
+                update_category(category).map_err(AppError::NotFound)?;
+                save_categories(&app.data_dir).map_err(AppError::Io)?;
+                eprintln!("Category updated: {}", name);
+                Ok(())
+            }
+            CategoryAction::Rename { from, to } => move_category(&from, &to, false, app),
+            CategoryAction::Merge { from, into } => move_category(&from, &into, true, app),
+            CategoryAction::Remove { name } => {
+                let name = normalize_name(&name).map_err(AppError::Invalid)?;
+                remove_category(&name).map_err(AppError::NotFound)?;
+                let count = event_manager.lock().unwrap().recategorize(&name, None);
+                save_categories(&app.data_dir).map_err(AppError::Io)?;
+                if count > 0 {
+                    save(&event_manager)?;
+                }
+                eprintln!("Category removed: {} ({} events updated)", name, count);
+                Ok(())
+            }
+        },
+        Commands::Contact { action } => match action {
+            ContactAction::List { format } => {
+                print_contacts(&contact_list(), format).map_err(|e| AppError::Io(e.to_string()))
+            }
+            ContactAction::Add { name, details } => {
+                let mut contact = Contact::new(name.trim());
+                apply_contact_details(&mut contact, &details);
+                contact.contact_id = add_contact(contact.clone()).map_err(AppError::Invalid)?;
+                eprintln!("Contact added: {} {}", contact.contact_id, contact.name);
+                relink_contacts(&[contact], app)
+            }
+            ContactAction::Edit {
+                contact,
+                name,
+                details,
+            } => {
+                let mut contact = find_contact(&contact).map_err(AppError::NotFound)?;
+                if let Some(name) = name {
+                    contact.name = name.trim().to_string();
+                }
+                apply_contact_details(&mut contact, &details);
+                update_contact(contact.clone()).map_err(AppError::Invalid)?;
+                eprintln!("Contact updated: {} {}", contact.contact_id, contact.name);
+                relink_contacts(&[contact], app)
+            }
+            ContactAction::Remove { contact } => {
+                let contact = find_contact(&contact).map_err(AppError::NotFound)?;
+                remove_contact(&contact.contact_id).map_err(AppError::NotFound)?;
+                let count = event_manager
+                    .lock()
+                    .unwrap()
+                    .relink_contact(&contact.contact_id, None);
+                save_contacts(&app.data_dir).map_err(AppError::Io)?;
+                if count > 0 {
+                    save(&event_manager)?;
+                }
+                eprintln!(
+                    "Contact removed: {} {} ({} events updated)",
+                    contact.contact_id, contact.name, count
+                );
+                Ok(())
+            }
+            ContactAction::Import { files } => import_contacts(&files, app),
+        },
+        Commands::Import { file, replace } => {
+            let data = fs::read_to_string(&file)
+                .map_err(|e| AppError::Io(format!("{}: {}", file.display(), e)))?;
+            let name = file.display().to_string();
+            let events: Vec<Event> = match data.trim_start().starts_with("BEGIN:VCALENDAR") {
+                true => {
+                    let calendar = parse_calendar(&data)
+                        .map_err(|e| AppError::Invalid(format!("{}: {}", name, e)))?;
+                    match calendar.method.as_deref() {
+                        // Answers to and cancellations of invitations, not events to add
+                        Some("REPLY") => return import_replies(&name, &calendar.events, app),
+                        Some("CANCEL") => {
+                            return import_cancellations(&name, &calendar.events, app)
+                        }
+                        _ => calendar.events,
+                    }
+                }
+                false => serde_json::from_str(&data)
+                    .map_err(|e| AppError::Invalid(format!("{}: {}", name, e)))?,
+            };
+            check_events(&format!("{}: ", file.display()), &events)?;
+            let (mut added, mut updated) = (0, 0);
             {
-                println!("{}, {}", i + 1, event);
-            }
-        }
-        _ if command.starts_with("list") => {
-            let input = command.strip_prefix("list").unwrap_or("").trim();
-            match input {
-                _ if input.starts_with("event") => {
-                    let index = command
-                        .strip_prefix("event")
-                        .unwrap_or("")
-                        .trim()
-                        .parse::<usize>();
-                    if let Ok(index) = index {
-                        if index > 0 {
-                            if let Some(event) = event_manager.lock().unwrap().get_event(index - 1)
-                            {
-                                println!("{}", event);
-                            } else {
-                                eprintln!("No event found at index {}", index);
-                            }
-                        } else {
-                            eprintln!("Invalid index: {}", index);
+                let mut manager = event_manager.lock().unwrap();
+                if replace {
+                    manager.clear();
+                }
+                for mut event in events {
+                    // Importing an exported or updated event again replaces it
+                    let known = (!event.uid.is_empty())
+                        .then(|| {
+                            manager
+                                .events()
+                                .iter()
+                                .position(|known| known.uid() == event.uid)
+                        })
+                        .flatten();
+                    match known {
+                        Some(index) => {
+                            event.event_id = manager.get_event(index).unwrap().event_id.clone();
+                            manager.replace_event(index, event);
+                            updated += 1;
+                        }
+                        None => {
+                            manager.add_event(event);
+                            added += 1;
                         }
-                    } else {
-                        event_manager.lock().unwrap().list_events();
                     }
                 }
-                _ if input.starts_with("categories") => {
-                    println!("Categories:");
-                    for category in CATEGORIES.lock().unwrap().iter() {
-                        println!("\t{}", category);
+            }
+            save(&event_manager)?;
+            match updated {
+                0 => println!("Imported {} events", added),
+                _ => println!("Imported {} events, updated {}", added, updated),
+            }
+            Ok(())
+        }
+        Commands::Export { file, view, format } => {
+            let manager = event_manager.lock().unwrap();
+            let events: Vec<&Event> = match view {
+                Some(name) => view_results(app, &manager, &name)?
+                    .into_iter()
+                    .map(|(_, event)| event)
+                    .collect(),
+                None => manager.events().iter().collect(),
+            };
+            let is_ics = file
+                .as_ref()
+                .and_then(|file| file.extension())
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"));
+            let text = match format.unwrap_or(match is_ics {
+                true => ExportFormat::Ics,
+                false => ExportFormat::Json,
+            }) {
+                ExportFormat::Json => serde_json::to_string_pretty(&events)
+                    .map_err(|e| AppError::Invalid(e.to_string()))?,
+                ExportFormat::Ics => write_calendar(&events, None),
+            };
+            match file {
+                Some(file) => fs::write(&file, text)
+                    .map_err(|e| AppError::Io(format!("{}: {}", file.display(), e))),
+                None => {
+                    print!("{}", text);
+                    if !text.ends_with('\n') {
+                        println!();
                     }
+                    Ok(())
                 }
-                _ => print_list_help(),
             }
         }
-        "save" => {
-            event_manager.lock().unwrap().save_events();
+        Commands::Respond {
+            event,
+            answer,
+            attendee,
+        } => {
+            let status = ParticipationStatus::parse(&answer).map_err(AppError::Usage)?;
+            let email = match attendee {
+                Some(attendee) => resolve_attendee(&attendee)
+                    .map_err(AppError::NotFound)?
+                    .email,
+                None => user_attendee(&app.config)
+                    .ok_or_else(|| {
+                        AppError::Usage(
+                            "set your email with `config set user_email <email>`, or name the attendee with --as".to_string(),
+                        )
+                    })?
+                    .email,
+            };
+            let index = find_event(&event_manager, &event)?;
+            let mut manager = event_manager.lock().unwrap();
+            let mut event = manager.get_event(index).unwrap().clone();
+            let event_id = event.event_id.clone();
+            let attendee = event.find_attendee_mut(&email).ok_or_else(|| {
+                AppError::NotFound(format!("{} is not an attendee of {}", email, event_id))
+            })?;
+            attendee.status = status;
+            let name = attendee.name.clone();
+            event.updated_at = Local::now();
+            eprintln!("{} {} {} ({})", name, status, event.title, event.event_id);
+            manager.replace_event(index, event);
+            drop(manager);
+            save(&event_manager)
+        }
+        Commands::Service { action } => match action {
+            ServiceAction::Start => service_start(app.interactive),
+            ServiceAction::Stop => service_stop(),
+            ServiceAction::Restart => service_restart(app.interactive),
+        },
+        Commands::Config { action } => match action {
+            ConfigAction::List => {
+                for (key, value) in app.config.entries() {
+                    println!("{} = {}", key, value);
+                }
+                Ok(())
+            }
+            ConfigAction::Get { key } => {
+                let value = app
+                    .config
+                    .get(&key)
+                    .ok_or_else(|| AppError::NotFound(format!("unknown config key '{}'", key)))?;
+                println!("{}", value);
+                Ok(())
+            }
+            ConfigAction::Set { key, value } => {
+                app.config.set(&key, &value).map_err(AppError::Invalid)?;
+                app.config.save(&app.data_dir).map_err(AppError::Io)
+            }
+            ConfigAction::Path => {
+                println!("{}", Config::path(&app.data_dir).display());
+                Ok(())
+            }
+        },
+        Commands::Check => {
+            let manager = event_manager.lock().unwrap();
+            let (mut errors, mut warnings) = (0, 0);
+            for event in manager.events() {
+                for issue in validate_event(event) {
+                    if issue.is_error() {
+                        errors += 1;
+                    } else {
+                        warnings += 1;
+                    }
+                    println!("{} ({}): {}", event.event_id, event.title, issue);
+                }
+            }
+            eprintln!(
+                "Checked {} events: {} errors, {} warnings",
+                manager.events().len(),
+                errors,
+                warnings
+            );
+            if errors > 0 {
+                return Err(AppError::Invalid(format!("{} errors found", errors)));
+            }
+            Ok(())
         }
-        "clear" => {
+        Commands::Save => save(&event_manager),
+        Commands::Clear => {
             event_manager.lock().unwrap().clear();
-            event_manager.lock().unwrap().save_events();
+            save(&event_manager)
         }
-        "cls" => {
+        Commands::Cls => {
             clear_screen();
+            Ok(())
         }
-        _ => {
-            eprintln!("Unknown command: {}", command);
-            print_help(); // Suggest help for valid commands
+        Commands::Completions { shell } => {
+            print_completions(shell);
+            Ok(())
         }
     }
 }
 
-fn add_event_loop(input: &str, event_manager: &Arc<Mutex<EventManager>>) {
-    let index = event_manager.lock().unwrap().add_event_from_str(input);
-    if index < 0 {
-        eprintln!("Error when trying to add the Event");
+// Builds an event from the `[key: value, ...]` data, then applies the flags on top of it
+fn event_from_args(args: &EventArgs, config: &Config) -> Result<Event, AppError> {
+    let length = parse_duration(&config.default_duration).map_err(AppError::Invalid)?;
+    let mut event = base_event(args, length)?;
+    // Events in a category with a duration of its own are read again with that as the default
+    if !args.json {
+        let categories: Vec<String> = event
+            .categories
+            .iter()
+            .chain(&args.categories)
+            .cloned()
+            .collect();
+        if let Some(duration) = category_duration(&categories) {
+            let category_length = parse_duration(&duration)
+                .map_err(|e| AppError::Invalid(format!("category duration: {}", e)))?;
+            if category_length != length {
+                event = base_event(args, category_length)?;
+            }
+        }
+    }
+
+    if let Some(title) = &args.title {
+        event = event.set_title(title.clone());
+    }
+    if let Some(description) = &args.description {
+        event = event.set_description(description.clone());
+    }
+    if let Some(location) = &args.location {
+        event = event.set_location(location.clone());
+    }
+
+    let mut length = event.end_time - event.start_time;
+    if let Some(duration) = &args.duration {
+        length = parse_duration(duration).map_err(AppError::Invalid)?;
+    }
+    if let Some(zone) = &args.time_zone {
+        // The times given so far are wall-clock times in that zone
+        event
+            .set_field("time_zone", zone)
+            .map_err(AppError::Invalid)?;
+    }
+    let zone = event.time_zone;
+    if let Some(at) = &args.at {
+        event.start_time = parse_datetime(at, Local::now(), zone).map_err(AppError::Invalid)?;
+    }
+    if args.all_day && !event.is_all_day && args.duration.is_none() {
+        length = Duration::days(1);
+    }
+    event.end_time = event.start_time + length;
+    if args.all_day {
+        event = event.set_is_all_day(true);
+    }
+    // All-day events always cover whole days in their zone
+    event.snap_all_day().map_err(AppError::Invalid)?;
+
+    if !args.categories.is_empty() {
+        let mut categories = event.categories.clone();
+        categories.extend(args.categories.iter().cloned());
+        event = event.set_categories(categories);
+    }
+    if !args.tags.is_empty() {
+        let mut tags = event.tags.clone();
+        tags.extend(args.tags.iter().cloned());
+        event
+            .set_field("tags", &tags.join(","))
+            .map_err(AppError::Invalid)?;
+    }
+    if let Some(priority) = &args.priority {
+        event = event.set_priority(Priority::parse(priority).map_err(AppError::Invalid)?);
+    }
+    if let Some(transparency) = &args.transparency {
+        event = event.set_transparency(Some(
+            Transparency::parse(transparency).map_err(AppError::Invalid)?,
+        ));
+    }
+    for attendee in &args.attendees {
+        event.add_attendee(resolve_attendee(attendee).map_err(AppError::Invalid)?);
+    }
+    for attendee in &args.optional {
+        event.add_attendee(Attendee {
+            role: Role::Optional,
+            ..resolve_attendee(attendee).map_err(AppError::Invalid)?
+        });
+    }
+    if let Some(organizer) = &args.organizer {
+        event.organizer = Some(resolve_attendee(organizer).map_err(AppError::Invalid)?);
+    }
+    if event.organizer.is_none() && !event.attendees.is_empty() {
+        event.organizer = user_attendee(config);
+    }
+    if !args.notify.is_empty() {
+        let notifications = args
+            .notify
+            .iter()
+            .map(|reminder| parse_reminder(reminder))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::Invalid)?;
+        event = event.set_notification_settings(notifications);
+    }
+    if event.notification_settings.is_empty() {
+        event.notification_settings = default_reminders(&event, config)?;
+    }
+    add_urgent_reminder(&mut event, config)?;
+    Ok(event)
+}
+
+// The event as given by --json, the data syntax or quick-add, `length` being the duration of
+// events that don't give one
+fn base_event(args: &EventArgs, length: Duration) -> Result<Event, AppError> {
+    let event = if args.json {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        serde_json::from_str::<Event>(&input)
+            .map_err(|e| AppError::Invalid(format!("event JSON: {}", e)))?
+    } else if is_data_syntax(&args.data.join(" ")) {
+        EventManager::event_from_str(&args.data.join(" "), length).map_err(AppError::Invalid)?
+    } else {
+        // `add "Standup" mon-fri 09:15 30m @Room4 #Work !10m`, the title comes first
+        let mut words = args.data.clone();
+        match &args.title {
+            Some(title) => words.insert(0, title.clone()),
+            None if words.is_empty() => {
+                return Err(AppError::Usage(
+                    "an event needs a --title or event data, see `add --help`".to_string(),
+                ))
+            }
+            None => {}
+        }
+        let zone = match &args.time_zone {
+            Some(zone) => parse_time_zone(zone).map_err(AppError::Invalid)?,
+            None => None,
+        };
+        Event::from_quick_add(&words, Local::now(), length, zone).map_err(AppError::Invalid)?
+    };
+    Ok(event)
+}
+
+// The event at `index` with its UID stored, so replies still find it if its ID changes
+fn pin_uid(app: &App, index: usize) -> Result<Event, AppError> {
+    let mut manager = app.event_manager.lock().unwrap();
+    let mut event = manager.get_event(index).unwrap().clone();
+    if event.uid.is_empty() {
+        event.uid = event.uid();
+        manager.replace_event(index, event.clone());
+        drop(manager);
+        save(&app.event_manager)?;
+    }
+    Ok(event)
+}
+
+// Emails the attendees of an event you organize, when send_invitations is on, about it being
+// added (`index` alone), edited (`old` and `index`) or removed (`old` alone). The event is
+// already saved, so failures are only warned about.
+fn send_invitations(app: &App, old: Option<&Event>, index: Option<usize>) {
+    if !app.config.send_invitations {
         return;
     }
-    println!(
-        "{}",
-        event_manager
+    let new = index.map(|index| {
+        app.event_manager
             .lock()
             .unwrap()
-            .get_event(index as usize)
+            .get_event(index)
             .unwrap()
-    );
+            .clone()
+    });
+    let Some(event) = new.as_ref().or(old) else {
+        return;
+    };
+    if !organized_by(event, app.config.user_email.trim())
+        || invitations(old, new.as_ref()).is_empty()
+    {
+        return;
+    }
+    let sent = index
+        .map(|index| pin_uid(app, index))
+        .transpose()
+        .and_then(|new| {
+            let mailer = Mailer::new(&app.config).map_err(AppError::Service)?;
+            Ok((new, mailer))
+        });
+    let (new, mailer) = match sent {
+        Ok(ready) => ready,
+        Err(e) => {
+            eprintln!("warning: invitations not sent: {}", e);
+            return;
+        }
+    };
+    for invitation in invitations(old, new.as_ref()) {
+        match mailer.send(&invitation) {
+            Ok(()) => report_sent(&invitation),
+            Err(e) => eprintln!("warning: {} not sent: {}", what_was_sent(&invitation), e),
+        }
+    }
+}
+
+fn what_was_sent(invitation: &Invitation) -> String {
+    let emails: Vec<&str> = invitation
+        .to
+        .iter()
+        .map(|attendee| attendee.email.as_str())
+        .collect();
+    format!(
+        "{} of {} ({}) to {}",
+        match invitation.method {
+            Method::Request => "invitation",
+            Method::Cancel => "cancellation",
+        },
+        invitation.event.title,
+        invitation.event.event_id,
+        emails.join(", ")
+    )
+}
+
+fn report_sent(invitation: &Invitation) {
+    eprintln!("Sent the {}", what_was_sent(invitation));
+}
+
+// Answers of attendees from a REPLY file, applied to the events with their UIDs. Every reply
+// is checked before any is applied, replies to an older revision or another organizer are
+// skipped.
+fn import_replies(name: &str, replies: &[Event], app: &App) -> Result<(), AppError> {
+    let mut manager = app.event_manager.lock().unwrap();
+    let mut updates: Vec<(usize, Event, Vec<Attendee>)> = vec![];
+    for reply in replies {
+        let index = manager
+            .events()
+            .iter()
+            .position(|event| !reply.uid.is_empty() && event.uid() == reply.uid)
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "{}: the reply is to an event that isn't here (UID {})",
+                    name, reply.uid
+                ))
+            })?;
+        // Several replies to one event build on each other
+        let position = match updates.iter().position(|(known, _, _)| *known == index) {
+            Some(position) => position,
+            None => {
+                updates.push((index, manager.get_event(index).unwrap().clone(), vec![]));
+                updates.len() - 1
+            }
+        };
+        let (_, event, changed) = &mut updates[position];
+        let skipped = match outdated(event, reply) {
+            Some(reason) => Some(reason),
+            None if reply.organizer.is_some() && !from_organizer(event, reply) => {
+                Some("it is addressed to another organizer".to_string())
+            }
+            None => None,
+        };
+        if let Some(reason) = skipped {
+            eprintln!(
+                "warning: {}: skipping a reply to {} ({}): {}",
+                name, event.title, event.event_id, reason
+            );
+            continue;
+        }
+        for attendee in
+            apply_reply(event, reply).map_err(|e| AppError::Invalid(format!("{}: {}", name, e)))?
+        {
+            changed.retain(|known: &Attendee| !known.email.eq_ignore_ascii_case(&attendee.email));
+            changed.push(attendee);
+        }
+    }
+    let mut count = 0;
+    for (index, mut event, changed) in updates {
+        for attendee in &changed {
+            eprintln!(
+                "{} {} {} ({})",
+                attendee.name, attendee.status, event.title, event.event_id
+            );
+        }
+        if !changed.is_empty() {
+            count += changed.len();
+            event.updated_at = Local::now();
+            manager.replace_event(index, event);
+        }
+    }
+    drop(manager);
+    save(&app.event_manager)?;
+    println!("Updated {} answers", count);
+    Ok(())
+}
+
+// Events in a CANCEL file are removed, their organizer called them off. Cancellations of your
+// own events, from someone else than the organizer or of an older revision are skipped.
+fn import_cancellations(name: &str, cancelled: &[Event], app: &App) -> Result<(), AppError> {
+    let mut manager = app.event_manager.lock().unwrap();
+    let mut indices = vec![];
+    for event in cancelled {
+        match manager
+            .events()
+            .iter()
+            .position(|known| !event.uid.is_empty() && known.uid() == event.uid)
+        {
+            Some(index) => {
+                let known = manager.get_event(index).unwrap();
+                let skipped = if organized_by(known, app.config.user_email.trim()) {
+                    Some("you organize it, remove it with `remove`".to_string())
+                } else if !from_organizer(known, event) {
+                    Some("it doesn't come from the event's organizer".to_string())
+                } else {
+                    outdated(known, event)
+                };
+                match skipped {
+                    Some(reason) => eprintln!(
+                        "warning: {}: skipping the cancellation of {} ({}): {}",
+                        name, known.title, known.event_id, reason
+                    ),
+                    None => indices.push(index),
+                }
+            }
+            None => eprintln!(
+                "warning: {}: {} was cancelled but isn't here (UID {})",
+                name, event.title, event.uid
+            ),
+        }
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    for index in indices.iter().rev() {
+        if let Some(event) = manager.remove_event(*index) {
+            eprintln!("Cancelled {} ({})", event.title, event.event_id);
+        }
+    }
+    drop(manager);
+    save(&app.event_manager)?;
+    println!("Removed {} cancelled events", indices.len());
+    Ok(())
+}
+
+// Yourself as set in the config, None without user_email
+fn user_attendee(config: &Config) -> Option<Attendee> {
+    let email = config.user_email.trim();
+    if email.is_empty() {
+        return None;
+    }
+    Some(Attendee {
+        name: match config.user_name.trim() {
+            "" => email.to_string(),
+            name => name.to_string(),
+        },
+        email: email.to_string(),
+        ..Default::default()
+    })
+}
+
+// The reminders of the event's category, otherwise all-day events are reminded the evening
+// before and others some minutes before
+fn default_reminders(event: &Event, config: &Config) -> Result<Vec<Notification>, AppError> {
+    let reminders = category_reminders(&event.categories);
+    if !reminders.is_empty() {
+        return Ok(reminders);
+    }
+    if !event.is_all_day {
+        return Ok(vec![Notification {
+            notify_before: config.default_notify_before,
+            ..Default::default()
+        }]);
+    }
+    if config.all_day_reminder.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let reminder = parse_reminder(&config.all_day_reminder)
+        .map_err(|e| AppError::Invalid(format!("config all_day_reminder: {}", e)))?;
+    Ok(vec![reminder])
+}
+
+// Urgent events get the configured extra reminder on top of their own, once
+fn add_urgent_reminder(event: &mut Event, config: &Config) -> Result<(), AppError> {
+    if event.priority != Priority::Urgent || config.urgent_reminder.trim().is_empty() {
+        return Ok(());
+    }
+    let reminder = parse_reminder(&config.urgent_reminder)
+        .map_err(|e| AppError::Invalid(format!("config urgent_reminder: {}", e)))?;
+    let due = reminder.due_time(event.start_time, event.time_zone);
+    if !event
+        .notification_settings
+        .iter()
+        .any(|notification| notification.due_time(event.start_time, event.time_zone) == due)
+    {
+        event.notification_settings.push(reminder);
+    }
+    Ok(())
+}
+
+// Matching events, best full-text matches first unless sorted by a search field
+fn search_sorted<'a>(
+    manager: &'a EventManager,
+    query: &Query,
+    sort: Option<&str>,
+) -> Vec<(usize, &'a Event)> {
+    let mut results = manager.search_event(query);
+    if let Some(sort) = sort.and_then(SearchType::from) {
+        EventManager::sort_events_by(&mut results, sort);
+    }
+    results
+}
+
+// Events of the saved view `name`, which may be written `@name` as in `list @name`
+fn view_results<'a>(
+    app: &App,
+    manager: &'a EventManager,
+    name: &str,
+) -> Result<Vec<(usize, &'a Event)>, AppError> {
+    let views = Views::load(&app.data_dir).map_err(AppError::Invalid)?;
+    let view = views.get(name).map_err(AppError::NotFound)?;
+    let query = view
+        .parse()
+        .map_err(|e| AppError::Invalid(format!("view {}: {}", name, e)))?;
+    Ok(search_sorted(manager, &query, view.sort.as_deref()))
+}
+
+fn show_view(app: &App, name: &str, format: OutputFormat) -> Result<(), AppError> {
+    let manager = app.event_manager.lock().unwrap();
+    let results = view_results(app, &manager, name)?;
+    print_events(&results, format).map_err(|e| AppError::Io(e.to_string()))
+}
+
+fn add_event(args: &EventArgs, app: &mut App) -> Result<(), AppError> {
+    let source = match (&args.from_file, args.data.as_slice()) {
+        (Some(file), _) => Some(file.clone()),
+        (None, [word]) if word == "-" => Some(PathBuf::from("-")),
+        _ => None,
+    };
+    if let Some(source) = source {
+        return add_events_from_file(&source, args, app);
+    }
+    let event = event_from_args(args, &app.config)?;
+    check_events("", std::slice::from_ref(&event))?;
+    warn_conflicts(&app.event_manager.lock().unwrap(), "", &event, None);
+    let index = app.event_manager.lock().unwrap().add_event(event);
+    if index < 0 {
+        return Err(AppError::Usage(
+            "events can't be added in passive mode".to_string(),
+        ));
+    }
+    if args.yes {
+        // Show how a quick-add line was understood, stdout only gets the ID
+        if !args.json && !is_data_syntax(&args.data.join(" ")) {
+            let manager = app.event_manager.lock().unwrap();
+            let _ = write_event_details(
+                &mut io::stderr(),
+                index as usize,
+                &manager.events()[index as usize],
+            );
+        }
+        save(&app.event_manager)?;
+        println!(
+            "Event added: {}",
+            app.event_manager
+                .lock()
+                .unwrap()
+                .get_event(index as usize)
+                .unwrap()
+                .event_id
+        );
+        send_invitations(app, None, Some(index as usize));
+        return Ok(());
+    }
+    let event_id = app.event_manager.lock().unwrap().events()[index as usize]
+        .event_id
+        .clone();
+    add_event_loop(index as usize, &app.event_manager)?;
+    // Unless it was discarded
+    let kept = app
+        .event_manager
+        .lock()
+        .unwrap()
+        .events()
+        .iter()
+        .position(|event| event.event_id == event_id);
+    if kept.is_some() {
+        send_invitations(app, None, kept);
+    }
+    Ok(())
+}
+
+// `add --from-file events.yaml` and `add -`: every event is checked before any is added
+fn add_events_from_file(source: &PathBuf, args: &EventArgs, app: &mut App) -> Result<(), AppError> {
+    let from_stdin = source.as_os_str() == "-";
+    if args.title.is_some()
+        || args.at.is_some()
+        || args.duration.is_some()
+        || args.description.is_some()
+        || args.location.is_some()
+        || !args.categories.is_empty()
+        || !args.tags.is_empty()
+        || args.priority.is_some()
+        || args.transparency.is_some()
+        || !args.attendees.is_empty()
+        || !args.optional.is_empty()
+        || args.organizer.is_some()
+        || !args.notify.is_empty()
+        || args.all_day
+        || args.time_zone.is_some()
+        || args.json
+        || (args.from_file.is_some() && !args.data.is_empty())
+    {
+        return Err(AppError::Usage(
+            "events from a file can't be combined with other event flags or data".to_string(),
+        ));
+    }
+    let (name, input) = if from_stdin {
+        if app.interactive {
+            return Err(AppError::Usage(
+                "reading events from stdin is not available in the interactive mode".to_string(),
+            ));
+        }
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        ("stdin".to_string(), input)
+    } else {
+        let input = fs::read_to_string(source)
+            .map_err(|e| AppError::Io(format!("{}: {}", source.display(), e)))?;
+        (source.display().to_string(), input)
+    };
+    // The extension decides, otherwise anything not starting like JSON is read as YAML
+    let yaml = match source.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml" | "yml") => true,
+        Some("json") => false,
+        _ => !input.trim_start().starts_with(['{', '[']),
+    };
+    let length = parse_duration(&app.config.default_duration).map_err(AppError::Invalid)?;
+    let mut events = EventManager::events_from_document(&input, yaml, length)
+        .map_err(|e| AppError::Invalid(format!("{}: {}", name, e)))?;
+    for event in &mut events {
+        add_urgent_reminder(event, &app.config)?;
+    }
+    if events.is_empty() {
+        return Err(AppError::Invalid(format!("{}: no events given", name)));
+    }
+    check_events(&format!("{}: ", name), &events)?;
+    {
+        let manager = app.event_manager.lock().unwrap();
+        for (i, event) in events.iter().enumerate() {
+            let label = match events.len() {
+                1 => format!("{}: ", name),
+                _ => format!("{}: event {}: ", name, i + 1),
+            };
+            warn_conflicts(&manager, &label, event, None);
+        }
+    }
+
+    if !args.yes && !from_stdin && io::stdin().is_terminal() {
+        let first = app.event_manager.lock().unwrap().events().len();
+        let listed: Vec<(usize, &Event)> = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| (first + i, event))
+            .collect();
+        print_events(&listed, OutputFormat::Table).map_err(|e| AppError::Io(e.to_string()))?;
+        print!("Add these {} events? (y/n): ", events.len());
+        io::stdout().flush().unwrap();
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).unwrap();
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            eprintln!("No events added.");
+            return Ok(());
+        }
+    }
+
+    let mut ids = vec![];
+    {
+        let mut manager = app.event_manager.lock().unwrap();
+        for event in events {
+            let index = manager.add_event(event);
+            if index < 0 {
+                return Err(AppError::Usage(
+                    "events can't be added in passive mode".to_string(),
+                ));
+            }
+            ids.push((
+                index as usize,
+                manager.events()[index as usize].event_id.clone(),
+            ));
+        }
+    }
+    save(&app.event_manager)?;
+    for (index, id) in ids {
+        println!("Event added: {}", id);
+        send_invitations(app, None, Some(index));
+    }
+    Ok(())
+}
+
+// Points out the events that `event` overlaps, `skip` being its own index when it is edited.
+// Conflicts don't stop the event, double bookings can be intended.
+fn warn_conflicts(manager: &EventManager, label: &str, event: &Event, skip: Option<usize>) {
+    const SHOWN: usize = 5;
+    let own = skip.unwrap_or(manager.events().len());
+    let conflicts = manager.conflicts_with(event, skip);
+    for conflict in conflicts.iter().take(SHOWN) {
+        let other = match conflict.first.index == own {
+            true => conflict.second,
+            false => conflict.first,
+        };
+        let other_event = &manager.events()[other.index];
+        eprintln!(
+            "{}warning: conflicts with {} {} ({})",
+            label,
+            other_event.event_id,
+            other_event.title,
+            span_text(other.start, other.end)
+        );
+    }
+    if conflicts.len() > SHOWN {
+        eprintln!(
+            "{}warning: and {} more conflicts, see `conflicts`",
+            label,
+            conflicts.len() - SHOWN
+        );
+    }
+}
+
+// Shows the warnings of each event and fails with the errors of all of them, so that
+// a batch is only added when every event is valid
+fn check_events(prefix: &str, events: &[Event]) -> Result<(), AppError> {
+    let mut errors = vec![];
+    for (i, event) in events.iter().enumerate() {
+        let label = match events.len() {
+            1 => prefix.to_string(),
+            _ => format!("{}event {}: ", prefix, i + 1),
+        };
+        match check_event(event) {
+            Ok(warnings) => {
+                for warning in warnings {
+                    eprintln!("{}{}", label, warning);
+                }
+            }
+            Err(e) => errors.push(format!("{}{}", label, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Invalid(errors.join("\n")))
+    }
+}
+
+fn add_event_loop(index: usize, event_manager: &Arc<Mutex<EventManager>>) -> Result<(), AppError> {
+    {
+        let manager = event_manager.lock().unwrap();
+        print_event(
+            index,
+            manager.get_event(index).unwrap(),
+            OutputFormat::Table,
+        )
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    }
     let mut attempts = 0;
     loop {
         println!("What would you like to do with the event?");
@@ -287,31 +1739,40 @@ fn add_event_loop(input: &str, event_manager: &Arc<Mutex<EventManager>>) {
 
         match choice.trim() {
             "1" => {
-                event_manager.lock().unwrap().save_events();
-                break; // Exit the loop
+                let manager = event_manager.lock().unwrap();
+                match check_event(manager.get_event(index).unwrap()) {
+                    Ok(_) => {
+                        drop(manager);
+                        return save(event_manager);
+                    }
+                    Err(e) => println!("The event can't be kept like this: {}", e),
+                }
             }
             "2" => {
                 // Discard the event
-                event_manager.lock().unwrap().remove_event(index as usize);
+                event_manager.lock().unwrap().remove_event(index);
                 println!("Event has been discarded.");
-                break; // Exit the loop
+                return Ok(());
             }
             "3" => {
-                update_event(
-                    event_manager
-                        .lock()
-                        .unwrap()
-                        .get_event_mut(index as usize)
-                        .unwrap(),
-                );
+                let mut event = event_manager
+                    .lock()
+                    .unwrap()
+                    .get_event(index)
+                    .unwrap()
+                    .clone();
+                update_event(&mut event);
+                event_manager.lock().unwrap().replace_event(index, event);
                 attempts = 0; // Reset attempts after a successful edit
             }
             _ => {
                 attempts += 1; // Increment the invalid attempts counter
                 println!("Invalid choice. Please enter 1, 2, or 3.");
                 if attempts >= 10 {
-                    println!("Too many invalid attempts. The event will be discarded.");
-                    break; // Exit the loop after 10 invalid attempts
+                    event_manager.lock().unwrap().remove_event(index);
+                    return Err(AppError::Usage(
+                        "Too many invalid attempts. The event was discarded.".to_string(),
+                    ));
                 }
             }
         }
@@ -335,6 +1796,42 @@ fn ask_user(prompt: &str, default: &str) -> String {
     }
 }
 
+// Asks until the answer can be parsed, so a typo doesn't silently become a default
+fn ask_parsed<T>(prompt: &str, default: &str, parse: impl Fn(&str) -> Result<T, String>) -> T {
+    loop {
+        match parse(&ask_user(prompt, default)) {
+            Ok(value) => return value,
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
+
+// Asks for a date and time, suggesting `current` on the wall clock of `shown_in`
+fn ask_wall_time(
+    label: &str,
+    current: DateTime<Local>,
+    shown_in: Option<Tz>,
+    zone: Option<Tz>,
+) -> DateTime<Local> {
+    let wall = wall_time(current, shown_in);
+    loop {
+        let time = ask_parsed(
+            &format!("Enter new {} time", label),
+            &wall.format("%H:%M").to_string(),
+            time_from_str,
+        );
+        let date = ask_parsed(
+            &format!("Enter new {} date", label),
+            &wall.format("%Y-%m-%d").to_string(),
+            date_from_str,
+        );
+        match zoned_from_naive(date.and_time(time), zone) {
+            Ok(time) => return time,
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
+
 fn update_event(event: &mut Event) {
     // Update title
     let new_title = ask_user("Enter new title", &event.title);
@@ -348,35 +1845,17 @@ fn update_event(event: &mut Event) {
     let new_location = ask_user("Enter new location", &event.location);
     event.update_location(new_location);
 
-    // Update start time
-    {
-        let time = time_from_str(&ask_user(
-            "Enter new start time",
-            &event.start_time.time().format("%H:%M").to_string(),
-        ));
-        let date = date_from_str(&ask_user(
-            "Enter new start date",
-            &event.start_time.date_naive().format("%Y-%m-%d").to_string(),
-        ));
-
-        event.start_time =
-            DateTime::from_naive_utc_and_offset(date.and_time(time), *Local::now().offset());
-    }
-
-    // Update end time
-    {
-        let time = time_from_str(&ask_user(
-            "Enter new end time",
-            &event.start_time.time().format("%H:%M").to_string(),
-        ));
-        let date = date_from_str(&ask_user(
-            "Enter new end date",
-            &event.start_time.date_naive().format("%Y-%m-%d").to_string(),
-        ));
+    // Update time zone, the times below are read in the new one
+    let old_zone = event.time_zone;
+    event.time_zone = ask_parsed(
+        "Enter time zone (IANA name like Europe/Berlin, or local)",
+        &old_zone.map_or("local".to_string(), |zone| zone.to_string()),
+        parse_time_zone,
+    );
 
-        event.end_time =
-            DateTime::from_naive_utc_and_offset(date.and_time(time), *Local::now().offset());
-    }
+    // Update start and end time
+    event.start_time = ask_wall_time("start", event.start_time, old_zone, event.time_zone);
+    event.end_time = ask_wall_time("end", event.end_time, old_zone, event.time_zone);
 
     // Update is_recurring
     let new_is_recurring_str = ask_user(
@@ -389,6 +1868,15 @@ fn update_event(event: &mut Event) {
         println!("Invalid input for is_recurring. Keeping the original value.");
     }
 
+    // Update tags and priority
+    let new_tags = ask_user("Enter tags (comma separated)", &event.tags.join(", "));
+    let _ = event.set_field("tags", &new_tags); // any text is a valid list of tags
+    event.priority = ask_parsed(
+        "Enter priority (low/normal/high/urgent)",
+        &event.priority.to_string(),
+        Priority::parse,
+    );
+
     // Update attendees
     loop {
         println!("Current attendees:");
@@ -401,15 +1889,25 @@ fn update_event(event: &mut Event) {
         );
         match action.as_str() {
             "add" => {
-                let attendee_id = ask_user("Enter attendee ID", "");
-                let name = ask_user("Enter attendee name", "");
-                let email = ask_user("Enter attendee email", "");
-                let new_attendee = Attendee {
-                    attendee_id,
-                    name,
-                    email,
-                };
-                event.add_attendee(new_attendee);
+                let contacts = contact_list();
+                if !contacts.is_empty() {
+                    let names: Vec<String> = contacts
+                        .iter()
+                        .map(|contact| format!("{} {}", contact.contact_id, contact.name))
+                        .collect();
+                    println!("Contacts: {}", names.join(", "));
+                }
+                // The start of a name is enough when only one contact has it
+                let reference = ask_user(
+                    "Enter a contact ID, name or email, or \"Name <email>\" (empty to cancel)",
+                    "",
+                );
+                if !reference.is_empty() {
+                    match resolve_attendee(&reference) {
+                        Ok(attendee) => event.add_attendee(attendee),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                }
             }
             "remove" => {
                 let index_str = ask_user("Enter index of attendee to remove", "");
@@ -425,17 +1923,28 @@ fn update_event(event: &mut Event) {
                 let index_str = ask_user("Enter index of attendee to edit", "");
                 if let Ok(index) = index_str.parse::<usize>() {
                     if index < event.attendees.len() {
-                        let attendee_id =
-                            ask_user("Enter new attendee ID", &event.attendees[index].attendee_id);
-                        let name =
-                            ask_user("Enter new attendee name", &event.attendees[index].name);
-                        let email =
-                            ask_user("Enter new attendee email", &event.attendees[index].email);
-                        event.attendees[index] = Attendee {
-                            attendee_id,
-                            name,
-                            email,
-                        };
+                        let current = &event.attendees[index];
+                        let current = format!("{} <{}>", current.name, current.email);
+                        let reference = ask_user(
+                            "Enter a contact ID, name or email, or \"Name <email>\"",
+                            &current,
+                        );
+                        match resolve_attendee(&reference) {
+                            Ok(attendee) => {
+                                // The role stays, and the answer when it is the same person
+                                let old = &event.attendees[index];
+                                let status = match old.email.eq_ignore_ascii_case(&attendee.email) {
+                                    true => old.status,
+                                    false => ParticipationStatus::default(),
+                                };
+                                event.attendees[index] = Attendee {
+                                    role: old.role,
+                                    status,
+                                    ..attendee
+                                };
+                            }
+                            Err(e) => eprintln!("Error: {}", e),
+                        }
                     } else {
                         println!("No attendee found at index {}", index);
                     }
@@ -460,7 +1969,11 @@ fn update_event(event: &mut Event) {
         );
         match action.as_str() {
             "add" => {
-                let notify_before_str = ask_user("Enter notify before (in minutes)", "10");
+                let reminder = ask_parsed(
+                    "Enter reminder (minutes, 1h or \"the day before at 18:00\")",
+                    "10",
+                    parse_reminder,
+                );
                 let method_str = ask_user("Enter notification method (Email/SMS/Push)", "Email");
                 let method = match method_str.to_lowercase().as_str() {
                     "email" => NotificationMethod::Email,
@@ -471,12 +1984,7 @@ fn update_event(event: &mut Event) {
                         NotificationMethod::Email
                     }
                 };
-                let notify_before = notify_before_str.parse::<i64>().unwrap_or(10); // Default to 10 minutes if parsing fails
-                let new_notification = Notification {
-                    notify_before,
-                    method,
-                    has_notified: false,
-                };
+                let new_notification = Notification { method, ..reminder };
                 event.add_notification(new_notification);
             }
             "remove" => {
@@ -493,9 +2001,14 @@ fn update_event(event: &mut Event) {
                 let index_str = ask_user("Enter index of notification to edit", "");
                 if let Ok(index) = index_str.parse::<usize>() {
                     if index < event.notification_settings.len() {
-                        let notify_before_str = ask_user(
-                            "Enter new notify before (in minutes)",
-                            &event.notification_settings[index].notify_before.to_string(),
+                        let current = &event.notification_settings[index];
+                        let reminder = ask_parsed(
+                            "Enter new reminder (minutes, 1h or \"the day before at 18:00\")",
+                            &match current.on_day {
+                                Some(on_day) => on_day.to_string(),
+                                None => current.notify_before.to_string(),
+                            },
+                            parse_reminder,
                         );
                         let method_str = ask_user(
                             "Enter new notification method (Email/SMS/Push)",
@@ -516,14 +2029,7 @@ fn update_event(event: &mut Event) {
                             }
                         };
 
-                        let notify_before = notify_before_str
-                            .parse::<i64>()
-                            .unwrap_or(event.notification_settings[index].notify_before); // Keep original if parsing fails
-                        event.notification_settings[index] = Notification {
-                            notify_before,
-                            method,
-                            has_notified: false,
-                        };
+                        event.notification_settings[index] = Notification { method, ..reminder };
                     } else {
                         println!("No notification found at index {}", index);
                     }
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
+use crate::events::event::EVENT_FIELDS;
+
+// Nested lists and objects deeper than this are rejected instead of recursing further
+const MAX_DEPTH: usize = 32;
+
 #[derive(Debug)]
 
 pub enum Data {
-    None,
     String(String),
     Int(i64),
     Float(f64),
+    Bool(bool),
     List(Vec<Data>),
     Object(HashMap<String, Data>),
 }
@@ -32,6 +37,9 @@ impl Data {
             Data::Float(f) => {
                 output.push_str(&format!("{}Float: '{}'\n", indent, f));
             }
+            Data::Bool(b) => {
+                output.push_str(&format!("{}Bool: '{}'\n", indent, b));
+            }
             Data::List(list) => {
                 output.push_str(&format!("{}List:\n", indent));
                 for item in list {
@@ -49,38 +57,20 @@ impl Data {
                     output.push_str(&value.to_string(depth + 2));
                 }
             }
-            Data::None => {
-                output.push_str(&format!("{}Placeholder\n", indent));
-            }
         }
 
         output
     }
 
-    pub fn print(&self, depth: u64) {
-        match self {
-            Data::String(s) => println!("{}String: '{}'", "\t".repeat(depth as usize), s),
-            Data::Int(i) => println!("{}Int: '{}'", "\t".repeat(depth as usize), i),
-            Data::Float(f) => println!("{}Float: '{}'", "\t".repeat(depth as usize), f),
-            Data::List(list) => {
-                println!("{}List:", "\t".repeat(depth as usize));
-                for item in list {
-                    item.print(depth + 1);
-                }
-            }
-            Data::Object(obj) => {
-                println!("{}Object:", "\t".repeat(depth as usize));
-                for (key, value) in obj {
-                    println!("{}{}: ", "\t".repeat((depth + 1) as usize), key);
-                    value.print(depth + 2);
-                }
-            }
-            Data::None => println!("{}Placeholder", "\t".repeat(depth as usize)),
-        }
-    }
-
+    // Classifies an unquoted value, quoted values always stay strings
     pub fn from_string(input: &str) -> Data {
         let trimmed = input.trim();
+        match trimmed {
+            "true" => return Data::Bool(true),
+            "false" => return Data::Bool(false),
+            _ => {}
+        }
+
         // Try to parse as an integer
         if let Ok(int_value) = trimmed.parse::<i64>() {
             return Data::Int(int_value);
@@ -94,170 +84,481 @@ impl Data {
         // If parsing fails, return as a string
         Data::String(trimmed.to_string())
     }
+
+    // Scalars as text, so `title: 2026` is still a title
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Data::String(s) => Some(s.clone()),
+            Data::Int(i) => Some(i.to_string()),
+            Data::Float(f) => Some(f.to_string()),
+            Data::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Data::Int(i) => Some(*i),
+            Data::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Data::Bool(b) => Some(*b),
+            Data::Int(0) => Some(false),
+            Data::Int(1) => Some(true),
+            Data::String(s) => match s.to_lowercase().as_str() {
+                "yes" | "y" => Some(true),
+                "no" | "n" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Data::String(_) => "text",
+            Data::Int(_) => "a number",
+            Data::Float(_) => "a number",
+            Data::Bool(_) => "true or false",
+            Data::List(_) => "a list",
+            Data::Object(_) => "an object",
+        }
+    }
 }
 
-pub fn parse_data(input: &str, x: u64) -> Data {
-    //input = input.trim();
-    //input = input.strip_prefix("[").unwrap_or(input);
-    //input = input.trim();
-    //input = input.strip_suffix("]").unwrap_or(input);
-    //input = input.trim();
-
-    if x == 0 {
-        println!("first call: {}", input);
-    } else {
-        println!("call: {} {}", x, input);
+// True if the input is meant for `parse_data`: it is bracketed or starts with `<field>:`
+pub fn is_data_syntax(input: &str) -> bool {
+    let input = input.trim_start();
+    if input.starts_with('[') || input.starts_with('{') {
+        return true;
     }
-    if x > 100 {
-        return Data::from_string(input);
+    input.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim().to_lowercase();
+        let fields: Vec<&str> = EVENT_FIELDS.iter().map(|[field, _]| *field).collect();
+        fields.contains(&key.as_str()) || did_you_mean(&key, &fields).is_some()
+    })
+}
+
+// Closest candidate within a small edit distance, for "did you mean" hints
+pub fn did_you_mean<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = (word.chars().count() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Optimal string alignment distance, so a swapped pair like "titel" counts as one edit
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut current = vec![i];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j - 1] + cost)
+                .min(rows[i - 1][j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            current.push(distance);
+        }
+        rows.push(current);
     }
-    let input = input.trim();
-    let mut data: Data = Data::None;
-    let mut is_key = false;
-    let mut depth = 0;
-    let mut current_item = String::new();
-    let mut current_key = String::new();
-    let mut index = 0;
-    for c in input.chars() {
-        match c {
-            ':' if depth == 0 && current_key.is_empty() => {
-                is_key = true;
-                // println!("{}", current_item);
-                current_key = current_item.trim().to_string().clone();
-                current_item.clear();
+    rows[a.len()][b.len()]
+}
+
+// Parses the `[key: value, ...]` syntax: `title: Lunch, categories: [Work, "Food, Drinks"]`.
+// The outer brackets are optional. Values are quoted strings ("..." or '...' with \ escapes),
+// true/false, numbers, `[...]` lists, `[key: value]` or `{key: value}` objects, or bare text
+// running up to the next `,`, `]` or `}` (use \, to keep a comma in bare text).
+pub fn parse_data(input: &str) -> Result<Data, String> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    parser.skip_whitespace();
+    let data = match parser.peek() {
+        None => return Err("no event data given".to_string()),
+        Some(open @ ('[' | '{')) => {
+            parser.pos += 1;
+            let data = parser.parse_group(open)?;
+            parser.skip_whitespace();
+            if let Some(c) = parser.peek() {
+                return Err(parser.unexpected(c));
             }
-            ',' if depth == 0 => {
-                match data {
-                    Data::List(mut list) => {
-                        let mut _data: Data = Data::None;
-                        if current_item.contains(',')
-                            || current_item.contains('[')
-                            || current_item.contains(']')
-                        {
-                            _data = parse_data(&current_item, x + 1)
-                        } else if is_key {
-                            let mut object: HashMap<String, Data> = HashMap::new();
-                            if current_key.is_empty() {
-                                current_key = format!("{}", index);
-                                index += 1;
-                            }
-                            object.insert(current_key.clone(), Data::from_string(&current_item));
-                            _data = Data::Object(object);
-                        } else {
-                            _data = Data::from_string(current_item.trim());
-                        }
-                        list.push(_data);
-                        data = Data::List(list);
-                        current_item.clear();
-                        current_key.clear();
-                    }
-                    Data::Object(mut object) => {
-                        let mut _data: Data = Data::None;
-                        if current_item.contains(',')
-                            || current_item.contains('[')
-                            || current_item.contains(']')
-                        {
-                            _data = parse_data(&current_item, x + 1)
-                        } else {
-                            _data = Data::from_string(current_item.trim());
-                        }
-
-                        if current_key.is_empty() {
-                            current_key = format!("{}", index);
-                            index += 1;
-                        }
-                        object.insert(current_key.clone(), _data);
-                        data = Data::Object(object);
-                        current_key.clear();
-                        current_item.clear();
-                    }
-                    Data::None => {
-                        let mut _data: Data = Data::None;
-                        if current_item.contains(',')
-                            || current_item.contains('[')
-                            || current_item.contains(']')
-                        {
-                            _data = parse_data(&current_item, x + 1)
-                        } else {
-                            _data = Data::from_string(current_item.trim());
-                        }
-                        if is_key {
-                            if current_key.is_empty() {
-                                current_key = format!("{}", index);
-                                index += 1;
-                            }
-                            let mut hash_map: HashMap<String, Data> = HashMap::new();
-                            hash_map.insert(current_key.clone(), _data);
-                            data = Data::Object(hash_map);
-                        } else {
-                            let list: Vec<Data> = vec![_data];
-                            //list.push(_data);
-                            data = Data::List(list);
-                        }
-                        current_key.clear();
-                        current_item.clear();
-                    }
-                    _ => {
-                        unreachable!()
-                    }
+            data
+        }
+        Some(_) => parser.parse_object_body(None)?,
+    };
+    Ok(data)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn column(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected(&self, c: char) -> String {
+        format!("unexpected '{}' at column {}", c, self.column())
+    }
+
+    fn end_of_input(&self, close: Option<char>) -> String {
+        match close {
+            Some(close) => format!(
+                "missing '{}' at column {} (end of input)",
+                close,
+                self.column()
+            ),
+            None => format!("unexpected end of input at column {}", self.column()),
+        }
+    }
+
+    fn is_key_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '-'
+    }
+
+    // A field name followed by ':' starts here, used to tell objects from lists
+    fn at_key(&self) -> bool {
+        let mut pos = self.pos;
+        if !self
+            .chars
+            .get(pos)
+            .is_some_and(|c| c.is_alphabetic() || *c == '_')
+        {
+            return false;
+        }
+        while self.chars.get(pos).is_some_and(|c| Self::is_key_char(*c)) {
+            pos += 1;
+        }
+        while self.chars.get(pos).is_some_and(|c| c.is_whitespace()) {
+            pos += 1;
+        }
+        self.chars.get(pos) == Some(&':')
+    }
+
+    // Contents after an opening bracket up to and including its closing bracket
+    fn parse_group(&mut self, open: char) -> Result<Data, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            // The opening bracket was consumed already, point at it
+            return Err(format!("nesting too deep at column {}", self.pos));
+        }
+        let close = if open == '{' { '}' } else { ']' };
+        self.skip_whitespace();
+        let data = if open == '{' || self.at_key() {
+            self.parse_object_body(Some(close))?
+        } else {
+            self.parse_list_body(close)?
+        };
+        self.depth -= 1;
+        Ok(data)
+    }
+
+    // `key: value, ...` until `close` (consumed) or the end of input when there is none
+    fn parse_object_body(&mut self, close: Option<char>) -> Result<Data, String> {
+        let mut object = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None if close.is_none() => break,
+                None => return Err(self.end_of_input(close)),
+                Some(c) if Some(c) == close => {
+                    self.pos += 1;
+                    break;
                 }
-                is_key = false;
+                Some(_) => {}
             }
-            '[' => {
-                if depth > 0 {
-                    current_item.push(c);
-                }
-                depth += 1;
+
+            let key_column = self.column();
+            let start = self.pos;
+            while self.peek().is_some_and(Self::is_key_char) {
+                self.pos += 1;
+            }
+            let key: String = self.chars[start..self.pos].iter().collect();
+            if key.is_empty() {
+                return Err(match self.peek() {
+                    Some(c) => format!(
+                        "expected a field name at column {}, found '{}'",
+                        self.column(),
+                        c
+                    ),
+                    None => self.end_of_input(close),
+                });
             }
-            ']' => {
-                if depth > 0 {
-                    current_item.push(c);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(':') => self.pos += 1,
+                Some(c) => {
+                    return Err(format!(
+                        "expected ':' after '{}' at column {}, found '{}'",
+                        key,
+                        self.column(),
+                        c
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "expected ':' after '{}' at column {}",
+                        key,
+                        self.column()
+                    ))
                 }
-                depth -= 1;
             }
-            _ => {
-                current_item.push(c);
+
+            let value = self.parse_value(close)?;
+            if object.insert(key.to_lowercase(), value).is_some() {
+                return Err(format!(
+                    "field '{}' at column {} is given twice",
+                    key, key_column
+                ));
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if Some(c) == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) => return Err(self.unexpected(c)),
+                None if close.is_none() => break,
+                None => return Err(self.end_of_input(close)),
             }
         }
+        Ok(Data::Object(object))
     }
-    if !current_item.is_empty() {
-        let mut _data: Data = Data::None;
-        if current_item.contains(',') || current_item.contains('[') || current_item.contains(']') {
-            _data = parse_data(&current_item, x + 1)
-        } else {
-            _data = Data::from_string(current_item.trim());
-        }
 
-        match data {
-            Data::None => {
-                if is_key {
-                    if current_key.is_empty() {
-                        current_key = format!("{}", index);
-                    }
-                    let mut object: HashMap<String, Data> = HashMap::new();
-                    object.insert(current_key.clone(), _data);
-                    data = Data::Object(object);
-                } else {
-                    let list: Vec<Data> = vec![_data];
-                    data = Data::List(list);
+    fn parse_list_body(&mut self, close: char) -> Result<Data, String> {
+        let mut list = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Err(self.end_of_input(Some(close))),
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
                 }
+                Some(_) => {}
             }
-            Data::List(mut list) => {
-                list.push(_data);
-                data = Data::List(list)
+            list.push(self.parse_value(Some(close))?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) => return Err(self.unexpected(c)),
+                None => return Err(self.end_of_input(Some(close))),
             }
-            Data::Object(mut object) => {
-                if current_key.is_empty() {
-                    current_key = format!("{}", index);
+        }
+        Ok(Data::List(list))
+    }
+
+    fn parse_value(&mut self, close: Option<char>) -> Result<Data, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(format!("expected a value at column {}", self.column())),
+            Some(quote @ ('"' | '\'')) => self.parse_quoted(quote),
+            Some(open @ ('[' | '{')) => {
+                self.pos += 1;
+                self.parse_group(open)
+            }
+            Some(_) => self.parse_bare(close),
+        }
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<Data, String> {
+        let start_column = self.column();
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(format!(
+                        "unterminated string starting at column {}",
+                        start_column
+                    ))
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    text.push(self.parse_escape()?);
+                    continue;
                 }
-                object.insert(current_key.clone(), _data);
-                data = Data::Object(object);
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) => text.push(c),
             }
-            _ => {
-                println!("{}, {}, {}", is_key, current_key, current_item);
-                unreachable!()
+            self.pos += 1;
+        }
+        Ok(Data::String(text))
+    }
+
+    // The character after a backslash, the position is moved past it
+    fn parse_escape(&mut self) -> Result<char, String> {
+        let c = self
+            .peek()
+            .ok_or_else(|| format!("unfinished escape at column {}", self.column()))?;
+        self.pos += 1;
+        Ok(match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            other => other, // \\ \" \' \, \] and friends stand for themselves
+        })
+    }
+
+    // Unquoted text up to the next separator, `:` is allowed so times like 10:30 work
+    fn parse_bare(&mut self, close: Option<char>) -> Result<Data, String> {
+        let start_column = self.column();
+        let mut text = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => {
+                    self.pos += 1;
+                    text.push(self.parse_escape()?);
+                    escaped = true;
+                    continue;
+                }
+                ',' => break,
+                ']' | '}' if Some(c) == close => break,
+                ']' | '}' | '[' | '{' => return Err(self.unexpected(c)),
+                _ => text.push(c),
             }
+            self.pos += 1;
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(format!("expected a value at column {}", start_column));
+        }
+        Ok(if escaped {
+            Data::String(text.to_string())
+        } else {
+            Data::from_string(text)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(input: &str) -> HashMap<String, Data> {
+        match parse_data(input) {
+            Ok(Data::Object(object)) => object,
+            other => panic!("{:?} is not an object", other),
         }
     }
-    data
+
+    #[test]
+    fn parses_values() {
+        let data =
+            object(r#"title: Lunch, count: 3, all-day: true, tags: [a, "b, c"], when: 10:30"#);
+        assert_eq!(data["title"].as_text().as_deref(), Some("Lunch"));
+        assert!(matches!(data["count"], Data::Int(3)));
+        assert!(matches!(data["all-day"], Data::Bool(true)));
+        assert_eq!(data["when"].as_text().as_deref(), Some("10:30"));
+        let Data::List(tags) = &data["tags"] else {
+            panic!("tags is not a list");
+        };
+        let tags: Vec<_> = tags.iter().filter_map(Data::as_text).collect();
+        assert_eq!(tags, ["a", "b, c"]);
+    }
+
+    #[test]
+    fn brackets_and_escapes() {
+        let data = object(r#"[title: a\, b, note: 'it\'s', nested: {x: 1}, list: [k: v]]"#);
+        assert_eq!(data["title"].as_text().as_deref(), Some("a, b"));
+        assert_eq!(data["note"].as_text().as_deref(), Some("it's"));
+        assert!(matches!(data["nested"], Data::Object(_)));
+        assert!(matches!(data["list"], Data::Object(_)));
+        // An escaped number stays text
+        assert!(matches!(object(r"n: \42")["n"], Data::String(_)));
+    }
+
+    #[test]
+    fn errors_name_the_column() {
+        let error = |input: &str| parse_data(input).unwrap_err();
+        assert_eq!(error(""), "no event data given");
+        assert_eq!(
+            error("[title: a"),
+            "missing ']' at column 10 (end of input)"
+        );
+        assert_eq!(
+            error("title a"),
+            "expected ':' after 'title' at column 7, found 'a'"
+        );
+        assert_eq!(
+            error("title: \"a"),
+            "unterminated string starting at column 8"
+        );
+        assert_eq!(error("title: a]"), "unexpected ']' at column 9");
+        assert_eq!(error("[title: a] x"), "unexpected 'x' at column 12");
+        assert_eq!(
+            error("title: a, , x: b"),
+            "expected a field name at column 11, found ','"
+        );
+        assert_eq!(error("title: "), "expected a value at column 8");
+        assert_eq!(error("a: 1, A: 2"), "field 'A' at column 7 is given twice");
+        assert_eq!(error(r"title: a\"), "unfinished escape at column 10");
+    }
+
+    #[test]
+    fn nesting_depth_is_limited() {
+        let nested = |depth: usize| format!("x: {}1{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse_data(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse_data(&nested(MAX_DEPTH + 1)).unwrap_err(),
+            format!("nesting too deep at column {}", MAX_DEPTH + 4)
+        );
+        // Far deeper input fails the same way instead of overflowing the stack
+        assert!(parse_data(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn did_you_mean_suggests_close_names() {
+        let fields = ["title", "location", "categories"];
+        assert_eq!(did_you_mean("titel", &fields), Some("title"));
+        assert_eq!(did_you_mean("loction", &fields), Some("location"));
+        assert_eq!(did_you_mean("colour", &fields), None);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn recognizes_data_syntax() {
+        assert!(is_data_syntax("[title: Lunch]"));
+        assert!(is_data_syntax("title: Lunch"));
+        assert!(is_data_syntax("titel: Lunch"));
+        assert!(!is_data_syntax("Dentist tomorrow 10:30"));
+    }
 }
@@ -0,0 +1,418 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Timelike};
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+use crate::events::event::Event;
+use crate::miscs::render::{category_color, pad, truncate, Style, BOLD, DIM, REVERSE};
+
+const DEFAULT_FIRST_HOUR: u32 = 8;
+const DEFAULT_LAST_HOUR: u32 = 18;
+
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn on_day(event: &Event, day: NaiveDate) -> bool {
+    let start = day.and_time(NaiveTime::MIN);
+    let end = start + Duration::days(1);
+    let event_start = event.start_time.naive_local();
+    let event_end = event.end_time.naive_local();
+    event_start < end && (event_end > start || event_start == start)
+}
+
+fn event_label(style: &Style, event: &Event, width: usize) -> String {
+    let text = pad(&truncate(&event.title, width), width);
+    match event.categories.first() {
+        Some(category) => style.paint(category_color(category), &text),
+        None => text,
+    }
+}
+
+// Hours shown in week and day views: working hours, widened to fit the given events
+fn visible_hours<'a>(events: impl Iterator<Item = &'a Event>) -> (u32, u32) {
+    let mut first = DEFAULT_FIRST_HOUR;
+    let mut last = DEFAULT_LAST_HOUR;
+    for event in events.filter(|event| !event.is_all_day) {
+        first = first.min(event.start_time.hour());
+        let end = event.end_time;
+        let end_hour = if end.minute() > 0 {
+            end.hour() + 1
+        } else {
+            end.hour()
+        };
+        if end.date_naive() == event.start_time.date_naive() {
+            last = last.max(end_hour);
+        } else {
+            last = 24;
+        }
+    }
+    (first, last.max(first + 1))
+}
+
+// Month grid, days with events are marked and listed below the grid
+pub fn write_month(
+    out: &mut impl Write,
+    occurrences: &[(usize, Event)],
+    date: NaiveDate,
+    style: &Style,
+) -> io::Result<()> {
+    let first = month_start(date);
+    let today = Local::now().date_naive();
+    let title = first.format("%B %Y").to_string();
+    let title = format!("{:^27}", title);
+    writeln!(out, "{}", style.paint(BOLD, title.trim_end()))?;
+    writeln!(out, " Mo  Tu  We  Th  Fr  Sa  Su")?;
+
+    let mut line = "    ".repeat(first.weekday().num_days_from_monday() as usize);
+    let mut day = first;
+    while day.month() == first.month() {
+        let count = occurrences
+            .iter()
+            .filter(|(_, event)| on_day(event, day))
+            .count();
+        let marker = match count {
+            0 => " ",
+            1 => "•",
+            _ => "+",
+        };
+        let cell = format!("{:>3}", day.day());
+        let cell = if day == today {
+            style.paint(REVERSE, &cell)
+        } else if count > 0 {
+            style.paint(BOLD, &cell)
+        } else {
+            cell
+        };
+        line += &format!("{}{}", cell, marker);
+        if day.weekday().num_days_from_monday() == 6 {
+            writeln!(out, "{}", line.trim_end())?;
+            line.clear();
+        }
+        day += Duration::days(1);
+    }
+    if !line.is_empty() {
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    let mut day = first;
+    let mut has_events = false;
+    while day.month() == first.month() {
+        let titles: Vec<&str> = occurrences
+            .iter()
+            .filter(|(_, event)| on_day(event, day))
+            .map(|(_, event)| event.title.as_str())
+            .collect();
+        if !titles.is_empty() {
+            if !has_events {
+                writeln!(out)?;
+                has_events = true;
+            }
+            let text = format!("{:>3}  {}", day.day(), titles.join(", "));
+            let text = match style.width {
+                Some(width) => truncate(&text, width),
+                None => text,
+            };
+            writeln!(out, "{}", text)?;
+        }
+        day += Duration::days(1);
+    }
+    Ok(())
+}
+
+// Seven columns with one row per hour, all-day events in a row above the hours
+pub fn write_week(
+    out: &mut impl Write,
+    occurrences: &[(usize, Event)],
+    date: NaiveDate,
+    style: &Style,
+) -> io::Result<()> {
+    let first = week_start(date);
+    let today = Local::now().date_naive();
+    let days: Vec<NaiveDate> = (0..7).map(|i| first + Duration::days(i)).collect();
+    let column = match style.width {
+        Some(width) => (width.saturating_sub(6) / 7).saturating_sub(1).max(8),
+        None => 14,
+    };
+
+    let mut header = "      ".to_string();
+    for day in &days {
+        let text = pad(&day.format("%a %d.%m").to_string(), column);
+        let text = if *day == today {
+            style.paint(REVERSE, &text)
+        } else {
+            style.paint(BOLD, &text)
+        };
+        header += &format!("{} ", text);
+    }
+    writeln!(out, "{}", header.trim_end())?;
+
+    let events_on = |day: NaiveDate, all_day: bool| -> Vec<&Event> {
+        occurrences
+            .iter()
+            .map(|(_, event)| event)
+            .filter(|event| event.is_all_day == all_day && on_day(event, day))
+            .collect()
+    };
+
+    let all_day_rows = days
+        .iter()
+        .map(|day| events_on(*day, true).len())
+        .max()
+        .unwrap_or(0);
+    for row in 0..all_day_rows {
+        let mut line = if row == 0 {
+            "all   ".to_string()
+        } else {
+            "      ".to_string()
+        };
+        for day in &days {
+            let cell = match events_on(*day, true).get(row) {
+                Some(event) => event_label(style, event, column),
+                None => " ".repeat(column),
+            };
+            line += &format!("{} ", cell);
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    let (first_hour, last_hour) = visible_hours(occurrences.iter().map(|(_, event)| event));
+    for hour in first_hour..last_hour {
+        let mut line = format!("{:02}:00 ", hour);
+        for day in &days {
+            let slot_start = day.and_hms_opt(hour, 0, 0).unwrap();
+            let slot_end = slot_start + Duration::hours(1);
+            let in_slot: Vec<&Event> = events_on(*day, false)
+                .into_iter()
+                .filter(|event| {
+                    event.start_time.naive_local() < slot_end
+                        && event.end_time.naive_local() > slot_start
+                })
+                .collect();
+            let starting: Vec<&&Event> = in_slot
+                .iter()
+                .filter(|event| event.start_time.naive_local() >= slot_start)
+                .collect();
+            let cell = if let Some(event) = starting.first() {
+                let label = if in_slot.len() > 1 {
+                    format!("{} +{}", event.title, in_slot.len() - 1)
+                } else {
+                    event.title.clone()
+                };
+                let text = pad(&truncate(&label, column), column);
+                match event.categories.first() {
+                    Some(category) => style.paint(category_color(category), &text),
+                    None => text,
+                }
+            } else if !in_slot.is_empty() {
+                style.paint(DIM, &pad("│", column))
+            } else {
+                " ".repeat(column)
+            };
+            line += &format!("{} ", cell);
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+// Assigns overlapping events to side by side lanes, returns (lane, event) pairs and the lane count
+fn assign_lanes<'a>(events: &[&'a Event]) -> (Vec<(usize, &'a Event)>, usize) {
+    let mut sorted = events.to_vec();
+    sorted.sort_by_key(|event| (event.start_time, event.end_time));
+    let mut lane_ends: Vec<DateTime<Local>> = vec![];
+    let mut result = vec![];
+    for event in sorted {
+        let lane = match lane_ends.iter().position(|end| *end <= event.start_time) {
+            Some(lane) => {
+                lane_ends[lane] = event.end_time;
+                lane
+            }
+            None => {
+                lane_ends.push(event.end_time);
+                lane_ends.len() - 1
+            }
+        };
+        result.push((lane, event));
+    }
+    (result, lane_ends.len())
+}
+
+// Timeline of a single day in half hour slots, overlapping events side by side
+pub fn write_day(
+    out: &mut impl Write,
+    occurrences: &[(usize, Event)],
+    date: NaiveDate,
+    style: &Style,
+) -> io::Result<()> {
+    let today = Local::now().date_naive();
+    let heading = date.format("%A, %Y-%m-%d").to_string();
+    let heading = if date == today {
+        format!("{} (Today)", heading)
+    } else {
+        heading
+    };
+    writeln!(out, "{}", style.paint(BOLD, &heading))?;
+
+    let events: Vec<&Event> = occurrences
+        .iter()
+        .map(|(_, event)| event)
+        .filter(|event| on_day(event, date))
+        .collect();
+    for event in events.iter().filter(|event| event.is_all_day) {
+        writeln!(
+            out,
+            "all day  {}",
+            event_label(style, event, event.title.width())
+        )?;
+    }
+
+    let timed: Vec<&Event> = events
+        .iter()
+        .copied()
+        .filter(|event| !event.is_all_day)
+        .collect();
+    let (lanes, lane_count) = assign_lanes(&timed);
+    let lane_width = match style.width {
+        Some(width) => (width.saturating_sub(8) / lane_count.max(1))
+            .saturating_sub(1)
+            .max(8),
+        None => 24,
+    };
+
+    let (first_hour, last_hour) = visible_hours(timed.iter().copied());
+    let mut slot_start = date.and_hms_opt(first_hour, 0, 0).unwrap();
+    let day_end = if last_hour >= 24 {
+        (date + Duration::days(1)).and_time(NaiveTime::MIN)
+    } else {
+        date.and_hms_opt(last_hour, 0, 0).unwrap()
+    };
+    while slot_start < day_end {
+        let slot_end = slot_start + Duration::minutes(30);
+        let time_label = if slot_start.minute() == 0 {
+            slot_start.format("%H:%M").to_string()
+        } else {
+            "     ".to_string()
+        };
+        let mut line = format!("{}  │ ", time_label);
+        for lane in 0..lane_count {
+            let event = lanes.iter().find(|(event_lane, event)| {
+                *event_lane == lane
+                    && event.start_time.naive_local() < slot_end
+                    && event.end_time.naive_local() > slot_start
+            });
+            let cell = match event {
+                Some((_, event))
+                    if event.start_time.naive_local() >= slot_start
+                        || slot_start == date.and_hms_opt(first_hour, 0, 0).unwrap() =>
+                {
+                    let label = format!("{} {}", event.start_time.format("%H:%M"), event.title);
+                    let text = pad(&truncate(&label, lane_width), lane_width);
+                    match event.categories.first() {
+                        Some(category) => style.paint(category_color(category), &text),
+                        None => text,
+                    }
+                }
+                Some(_) => style.paint(DIM, &pad("┃", lane_width)),
+                None => " ".repeat(lane_width),
+            };
+            line += &format!("{} ", cell);
+        }
+        writeln!(out, "{}", line.trim_end())?;
+        slot_start = slot_end;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miscs::utils::datetime_from_str;
+
+    const PLAIN: Style = Style {
+        color: false,
+        width: None,
+    };
+
+    fn event(title: &str, start: &str, end: &str) -> Event {
+        Event {
+            title: title.to_string(),
+            start_time: datetime_from_str(start, None).unwrap(),
+            end_time: datetime_from_str(end, None).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn finds_days_and_hours() {
+        assert_eq!(week_start(date(21)), date(19));
+        assert_eq!(week_start(date(19)), date(19));
+        assert_eq!(month_start(date(21)), date(1));
+
+        let night = event("Night", "2026-10-20 22:00", "2026-10-21 02:00");
+        assert!(on_day(&night, date(20)) && on_day(&night, date(21)));
+        assert!(!on_day(&night, date(22)));
+        let early = event("Early", "2026-10-20 06:30", "2026-10-20 19:15");
+        assert_eq!(visible_hours([&early].into_iter()), (6, 20));
+        assert_eq!(visible_hours([&early, &night].into_iter()), (6, 24));
+        assert_eq!(visible_hours([].into_iter()), (8, 18));
+    }
+
+    #[test]
+    fn overlapping_events_get_their_own_lane() {
+        let a = event("A", "2026-10-20 09:00", "2026-10-20 11:00");
+        let b = event("B", "2026-10-20 10:00", "2026-10-20 10:30");
+        let c = event("C", "2026-10-20 11:00", "2026-10-20 12:00");
+        let (lanes, count) = assign_lanes(&[&c, &b, &a]);
+        let lanes: Vec<_> = lanes
+            .iter()
+            .map(|(lane, event)| (*lane, event.title.as_str()))
+            .collect();
+        assert_eq!(lanes, [(0, "A"), (1, "B"), (0, "C")]);
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn month_grid_marks_busy_days() {
+        let occurrences = [
+            (0, event("Dentist", "2026-10-05 09:00", "2026-10-05 10:00")),
+            (1, event("Lunch", "2026-10-05 12:00", "2026-10-05 13:00")),
+            (2, event("Call", "2026-10-30 16:00", "2026-10-30 17:00")),
+        ];
+        let mut out = Vec::new();
+        write_month(&mut out, &occurrences, date(18), &PLAIN).unwrap();
+        let month = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = month.lines().collect();
+        assert_eq!(lines[0].trim(), "October 2026");
+        assert_eq!(lines[1], " Mo  Tu  We  Th  Fr  Sa  Su");
+        // October 2026 starts on a Thursday
+        assert_eq!(lines[2], "              1   2   3   4");
+        assert_eq!(lines[3], "  5+  6   7   8   9  10  11");
+        assert_eq!(lines[6], " 26  27  28  29  30• 31");
+        assert!(month.ends_with("\n  5  Dentist, Lunch\n 30  Call\n"));
+    }
+
+    #[test]
+    fn day_timeline_puts_overlaps_side_by_side() {
+        let occurrences = [
+            (0, event("Review", "2026-10-20 09:00", "2026-10-20 10:00")),
+            (1, event("Call", "2026-10-20 09:30", "2026-10-20 10:00")),
+        ];
+        let mut out = Vec::new();
+        write_day(&mut out, &occurrences, date(20), &PLAIN).unwrap();
+        let day = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = day.lines().collect();
+        assert_eq!(lines[0], "Tuesday, 2026-10-20");
+        assert_eq!(lines[1], "08:00  │");
+        assert_eq!(lines[3], "09:00  │ 09:00 Review");
+        assert_eq!(lines[4], format!("       │ {} 09:30 Call", pad("┃", 24)));
+        assert_eq!(lines.len(), 1 + 20);
+    }
+}
@@ -0,0 +1,574 @@
+use clap::{Args, Parser, Subcommand};
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+use crate::events::event::Priority;
+use crate::events::event_manager::SearchType;
+use crate::miscs::{
+    help::{data_syntax_help, query_syntax_help},
+    output::{ExportFormat, OutputFormat},
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "RustyPlanner",
+    version,
+    about = "A command-line tool for managing your appointments and events.",
+    after_help = "Run without a command to open the full-screen calendar, or with --repl for the command prompt."
+)]
+pub struct Cli {
+    /// Start the interactive command prompt instead of the full-screen calendar
+    #[arg(long)]
+    pub repl: bool,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Add a new event or category
+    Add(Box<AddArgs>),
+    /// Edit an event, interactively or with --set
+    Edit {
+        /// Index of the event as shown by `list`, or its ID
+        event: String,
+        /// Change only the named field, can be repeated
+        #[arg(long = "set", value_name = "FIELD=VALUE")]
+        set: Vec<String>,
+    },
+    /// Remove an event
+    Remove {
+        /// Index of the event as shown by `list`, or its ID
+        event: String,
+    },
+    /// List events or categories
+    #[command(
+        after_help = "Ranges: today, tomorrow, yesterday, week, next week, month, next month, next <duration> (e.g. next 7d)\nViews: @<name> lists the events of a view saved with `view save`"
+    )]
+    List {
+        /// Only show events in a range such as `today`, `week` or `next 7d`, or of a view like `@work`
+        range: Vec<String>,
+        /// Start of the range, e.g. "2026-10-20" or "2026-10-20 14:00"
+        #[arg(long)]
+        from: Option<String>,
+        /// End of the range, a date alone includes that whole day
+        #[arg(long)]
+        to: Option<String>,
+        /// Only show events with this tag, can be repeated
+        #[arg(short = 'g', long = "tag")]
+        tags: Vec<String>,
+        /// Only show events of a priority, `high+` for high and urgent ones
+        #[arg(short, long)]
+        priority: Option<String>,
+        /// Output format for events
+        #[arg(short, long, value_enum, default_value_t, global = true)]
+        format: OutputFormat,
+        #[command(subcommand)]
+        target: Option<ListTarget>,
+    },
+    /// List overlapping busy events, in the next 30 days unless a range is given
+    #[command(
+        after_help = "Ranges: today, tomorrow, yesterday, week, next week, month, next month, next <duration> (e.g. next 7d)\nFree events, set with --transparency free on an event or its category, never conflict."
+    )]
+    Conflicts {
+        /// Only check a range such as `today`, `week` or `next 90d`
+        range: Vec<String>,
+        /// Start of the range, e.g. "2026-10-20" or "2026-10-20 14:00"
+        #[arg(long)]
+        from: Option<String>,
+        /// End of the range, a date alone includes that whole day
+        #[arg(long)]
+        to: Option<String>,
+        /// Output format for the conflicts
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Find open slots of a given length within the working hours, in the next 7 days unless a range is given
+    #[command(
+        after_help = "Ranges: today, tomorrow, yesterday, week, next week, month, next month, next <duration> (e.g. next 7d)\nWorking hours and days default to the config keys working_hours and working_days."
+    )]
+    Free {
+        /// Length of the slot, e.g. "45m" or "1h30m"
+        duration: String,
+        /// Only look in a range such as `today`, `week` or `next 14d`
+        range: Vec<String>,
+        /// Start of the range, e.g. "2026-10-20" or "2026-10-20 14:00"
+        #[arg(long)]
+        from: Option<String>,
+        /// End of the range, a date alone includes that whole day
+        #[arg(long)]
+        to: Option<String>,
+        /// Working hours such as "9-17" or "08:30-12:00"
+        #[arg(long, value_name = "HOURS")]
+        between: Option<String>,
+        /// Days to look on such as "mon-fri" or "mon,wed,fri"
+        #[arg(long)]
+        days: Option<String>,
+        /// Only events in this category or its subcategories take up time, can be repeated
+        #[arg(short, long = "category")]
+        categories: Vec<String>,
+        /// Output format for the slots
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Show a month, week or day calendar
+    Cal {
+        #[command(subcommand)]
+        view: Option<CalView>,
+    },
+    /// Search for events, e.g. `title:standup AND category:Work NOT location:remote`
+    #[command(after_long_help = query_syntax_help())]
+    Search {
+        /// Query, or a field followed by the text to search for in it
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Ignore upper and lower case
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Treat the values as regular expressions
+        #[arg(short, long)]
+        regex: bool,
+        /// Sort the results by a field instead of their order in the list
+        #[arg(short, long, value_parser = SearchType::NAMES)]
+        sort: Option<String>,
+        /// Output format for the results
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Manage categories, their settings and subcategories
+    Category {
+        #[command(subcommand)]
+        action: CategoryAction,
+    },
+    /// Manage the contacts that attendees are picked from
+    Contact {
+        #[command(subcommand)]
+        action: ContactAction,
+    },
+    /// Show, save or remove named searches
+    #[command(args_conflicts_with_subcommands = true)]
+    View {
+        /// Name of the saved view to show
+        name: Option<String>,
+        /// Output format for the results
+        #[arg(short, long, value_enum, default_value_t, global = true)]
+        format: OutputFormat,
+        #[command(subcommand)]
+        action: Option<ViewAction>,
+    },
+    /// Import events from a JSON or iCalendar (.ics) file
+    Import {
+        /// File containing a JSON list of events or an iCalendar file, events with a known UID replace that event.
+        /// Invitation replies update the attendees' answers, cancellations remove the event.
+        file: PathBuf,
+        /// Replace all existing events instead of appending
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Export all events as JSON or iCalendar
+    Export {
+        /// Output file, stdout if omitted
+        file: Option<PathBuf>,
+        /// Only export the events of a saved view
+        #[arg(long, value_name = "NAME")]
+        view: Option<String>,
+        /// File format, iCalendar for files ending in .ics and JSON otherwise
+        #[arg(short, long, value_enum)]
+        format: Option<ExportFormat>,
+    },
+    /// Answer an invitation for yourself (config user_email) or, with --as, for an attendee
+    Respond {
+        /// Index of the event as shown by `list`, or its ID
+        event: String,
+        #[arg(value_parser = ["accept", "decline", "tentative"])]
+        answer: String,
+        /// Contact ID, name or email of the attendee who answered
+        #[arg(long = "as", value_name = "ATTENDEE")]
+        attendee: Option<String>,
+    },
+    /// Email the attendees of an event you organize its invitation, see `config set smtp_server`
+    Invite {
+        /// Index of the event as shown by `list`, or its ID
+        event: String,
+    },
+    /// Manage the background notification service
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Show or change settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Check all events for invalid or suspicious fields
+    Check,
+    /// Save events to file
+    Save,
+    /// Clear all events
+    Clear,
+    /// Clear the screen
+    Cls,
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+}
+
+#[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true, after_long_help = data_syntax_help())]
+pub struct AddArgs {
+    #[command(subcommand)]
+    pub target: Option<AddTarget>,
+
+    #[command(flatten)]
+    pub event: EventArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AddTarget {
+    /// Add an event from flags and/or the `[key: value, ...]` syntax
+    #[command(after_long_help = data_syntax_help())]
+    Event(Box<EventArgs>),
+    /// Add a category, `Work/Project-X` adds a subcategory
+    Category {
+        /// Name of the category
+        name: String,
+        #[command(flatten)]
+        settings: CategorySettings,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CategoryAction {
+    /// List the categories as a tree with their settings
+    List,
+    /// Add a category, `Work/Project-X` adds a subcategory
+    Add {
+        name: String,
+        #[command(flatten)]
+        settings: CategorySettings,
+    },
+    /// Change the colour, icon, default duration or reminders of a category
+    Edit {
+        name: String,
+        #[command(flatten)]
+        settings: CategorySettings,
+    },
+    /// Rename a category with its subcategories in the list and in all events
+    Rename { from: String, to: String },
+    /// Move the events and subcategories of a category into another one and remove it
+    Merge { from: String, into: String },
+    /// Remove a category with its subcategories from the list and from all events
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ContactAction {
+    /// List the contacts
+    List {
+        /// Output format for the contacts
+        #[arg(short, long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Add a contact
+    Add {
+        name: String,
+        #[command(flatten)]
+        details: ContactDetails,
+    },
+    /// Change a contact, the events it attends get its new name and email
+    Edit {
+        /// ID, name or email of the contact
+        contact: String,
+        /// New name of the contact
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        details: ContactDetails,
+    },
+    /// Remove a contact, the events it attends keep its name and email
+    Remove {
+        /// ID, name or email of the contact
+        contact: String,
+    },
+    /// Add the contacts of vCard (.vcf) files, those with a known email update that contact
+    Import {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ContactDetails {
+    /// Email address, the first one is used for events, can be repeated. Replaces all emails on edit
+    #[arg(short, long = "email")]
+    pub emails: Vec<String>,
+    /// Phone number, "" to remove it
+    #[arg(long)]
+    pub phone: Option<String>,
+    /// Notes about the contact, "" to remove them
+    #[arg(long)]
+    pub notes: Option<String>,
+}
+
+// Settings of a category, subcategories inherit those they don't set
+#[derive(Args, Debug, Default)]
+pub struct CategorySettings {
+    /// Colour: red, green, yellow, blue, magenta, cyan, gray, white or bright-<colour>, "" to inherit
+    #[arg(long)]
+    pub color: Option<String>,
+    /// Icon shown before the name, e.g. an emoji, "" to inherit
+    #[arg(long)]
+    pub icon: Option<String>,
+    /// Length of new events in the category, e.g. "30m", "" to inherit
+    #[arg(long)]
+    pub duration: Option<String>,
+    /// Reminder of new events in the category, e.g. "15m" or "1d at 18:00", can be repeated
+    #[arg(short = 'a', long = "notify")]
+    pub notify: Vec<String>,
+    /// Remove the category's reminders, so the parent's or the configured ones are used
+    #[arg(long, conflicts_with = "notify")]
+    pub no_notify: bool,
+    /// Whether events in the category block their time: busy or free, "" to inherit
+    #[arg(long, value_parser = ["busy", "free", ""])]
+    pub transparency: Option<String>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct EventArgs {
+    /// Title of the event
+    #[arg(short, long)]
+    pub title: Option<String>,
+    /// Start of the event, e.g. "2026-10-20 14:00", "20.10.2026" or "14:00"
+    #[arg(long)]
+    pub at: Option<String>,
+    /// Duration of the event, e.g. "1h30m"
+    #[arg(long)]
+    pub duration: Option<String>,
+    /// Description of the event
+    #[arg(short, long)]
+    pub description: Option<String>,
+    /// Location of the event
+    #[arg(short, long)]
+    pub location: Option<String>,
+    /// Category of the event, can be repeated
+    #[arg(short, long = "category")]
+    pub categories: Vec<String>,
+    /// Free-form tag of the event, can be repeated
+    #[arg(short = 'g', long = "tag")]
+    pub tags: Vec<String>,
+    /// Priority of the event, urgent ones get an extra early reminder
+    #[arg(short, long, value_parser = Priority::NAMES)]
+    pub priority: Option<String>,
+    /// Free events never conflict with others, by default the categories decide
+    #[arg(long, value_parser = ["busy", "free"])]
+    pub transparency: Option<String>,
+    /// Contact ID, name or email, or "Name <email>" for someone not in the contacts, can be repeated
+    #[arg(long = "attendee", value_name = "CONTACT")]
+    pub attendees: Vec<String>,
+    /// Like --attendee for someone whose attendance is optional
+    #[arg(long = "optional", value_name = "CONTACT")]
+    pub optional: Vec<String>,
+    /// Who invites the attendees, yourself (config user_email) by default
+    #[arg(long, value_name = "CONTACT")]
+    pub organizer: Option<String>,
+    /// Reminder in minutes, as a duration like "1h" or as "the day before at 18:00", can be repeated
+    #[arg(short = 'a', long = "notify", visible_alias = "alarm")]
+    pub notify: Vec<String>,
+    /// Mark the event as lasting whole days, --duration 3d spans three days
+    #[arg(long)]
+    pub all_day: bool,
+    /// IANA time zone the times are given in, e.g. "America/New_York", the local one by default
+    #[arg(long = "tz", value_name = "ZONE")]
+    pub time_zone: Option<String>,
+    /// Read the event as a JSON object from stdin, flags override its fields
+    #[arg(long)]
+    pub json: bool,
+    /// Add one or many events from a JSON or YAML file, `-` or `add -` reads stdin
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+    /// Save the event without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Quick-add line like `Standup mon-fri 09:15 30m @Room4 #Work +daily ^high !10m -- notes`,
+    /// or an event in the `[key: value, ...]` syntax
+    #[arg(trailing_var_arg = true)]
+    pub data: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ListTarget {
+    /// List all events
+    Events,
+    /// Show a single event by index or ID, or all events without one
+    Event { event: Option<String> },
+    /// List all categories
+    Categories,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ViewAction {
+    /// Save a search under a name, e.g. `view save work-week "category:Work range:week"`
+    Save {
+        /// Letters, digits, `-` and `_`
+        name: String,
+        /// Query as taken by `search`
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Ignore upper and lower case
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Treat the values as regular expressions
+        #[arg(short, long)]
+        regex: bool,
+        /// Sort the results by a field instead of their order in the list
+        #[arg(short, long, value_parser = SearchType::NAMES)]
+        sort: Option<String>,
+    },
+    /// List the saved views
+    List,
+    /// Remove a saved view
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CalView {
+    /// Month grid with event markers (default)
+    Month {
+        /// Any date in the month, e.g. "2026-10-01", "today" or "next month"
+        date: Option<String>,
+    },
+    /// Seven columns with hourly time slots
+    Week {
+        /// Any date in the week, e.g. "2026-10-20", "today" or "next week"
+        date: Option<String>,
+    },
+    /// Timeline of a single day, overlapping events side by side
+    Day {
+        /// The day to show, e.g. "2026-10-20" or "tomorrow"
+        date: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum ServiceAction {
+    /// Start the background service
+    Start,
+    /// Stop the background service
+    Stop,
+    /// Restart the background service
+    Restart,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// List all settings
+    List,
+    /// Print the value of a setting
+    Get { key: String },
+    /// Change the value of a setting
+    Set { key: String, value: String },
+    /// Print the path of the config file
+    Path,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("RustyPlanner").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn no_command_is_interactive() {
+        assert!(parse(&[]).unwrap().command.is_none());
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        assert!(matches!(
+            parse(&["remove", "#3"]).unwrap().command,
+            Some(Commands::Remove { event }) if event == "#3"
+        ));
+        let Some(Commands::Edit { event, set }) =
+            parse(&["edit", "2", "--set", "title=Lunch", "--set", "duration=1h"])
+                .unwrap()
+                .command
+        else {
+            panic!("not an edit command");
+        };
+        assert_eq!(event, "2");
+        assert_eq!(set, ["title=Lunch", "duration=1h"]);
+        assert!(matches!(
+            parse(&["list", "categories", "--format", "csv"])
+                .unwrap()
+                .command,
+            Some(Commands::List {
+                target: Some(ListTarget::Categories),
+                format: OutputFormat::Csv,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&["list", "next", "7d"]).unwrap().command,
+            Some(Commands::List { range, target: None, .. }) if range == ["next", "7d"]
+        ));
+        let Some(Commands::Service { action }) = parse(&["service", "stop"]).unwrap().command
+        else {
+            panic!("not a service command");
+        };
+        assert!(matches!(action, ServiceAction::Stop));
+    }
+
+    #[test]
+    fn add_takes_flags_or_a_category() {
+        let Some(Commands::Add(add)) = parse(&[
+            "add", "-t", "Lunch", "-c", "work", "-c", "food", "-a", "15", "-a", "1d@18:00",
+        ])
+        .unwrap()
+        .command
+        else {
+            panic!("not an add command");
+        };
+        assert!(add.target.is_none());
+        assert_eq!(add.event.title.as_deref(), Some("Lunch"));
+        assert_eq!(add.event.categories, ["work", "food"]);
+        assert_eq!(add.event.notify, ["15", "1d@18:00"]);
+
+        let Some(Commands::Add(add)) = parse(&[
+            "add",
+            "category",
+            "Work/Project-X",
+            "--color",
+            "red",
+            "--duration",
+            "30m",
+        ])
+        .unwrap()
+        .command
+        else {
+            panic!("not an add command");
+        };
+        let Some(AddTarget::Category { name, settings }) = add.target else {
+            panic!("not a category");
+        };
+        assert_eq!(name, "Work/Project-X");
+        assert_eq!(settings.color.as_deref(), Some("red"));
+        assert_eq!(settings.duration.as_deref(), Some("30m"));
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        let kind = |args: &[&str]| parse(args).unwrap_err().kind();
+        assert_eq!(kind(&["frobnicate"]), ErrorKind::InvalidSubcommand);
+        assert_eq!(kind(&["remove"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(
+            kind(&["search", "--sort", "color"]),
+            ErrorKind::InvalidValue
+        );
+    }
+}
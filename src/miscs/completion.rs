@@ -0,0 +1,303 @@
+use clap::CommandFactory;
+use rustyline::completion::{Completer, Pair};
+use rustyline::{Context, Helper, Highlighter, Hinter, Validator};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::events::category::category_names;
+use crate::events::contact::contact_list;
+use crate::events::event::{EVENT_FIELDS, SETTABLE_FIELDS};
+use crate::events::event_manager::{EventManager, SearchType};
+use crate::events::query::DATE_FIELDS;
+use crate::miscs::cli::Cli;
+use crate::miscs::views::Views;
+
+// Words that are only understood by the interactive mode itself
+const REPL_COMMANDS: [&str; 2] = ["exit", "quit"];
+
+// Tab completion for the interactive mode: commands and flags from the clap definition,
+// plus categories, contacts, event IDs and field keys from the current data
+#[derive(Helper, Hinter, Highlighter, Validator)]
+pub struct ReplHelper {
+    event_manager: Arc<Mutex<EventManager>>,
+    data_dir: PathBuf, // Where the saved views are read from
+}
+
+impl ReplHelper {
+    pub fn new(event_manager: Arc<Mutex<EventManager>>, data_dir: PathBuf) -> Self {
+        Self {
+            event_manager,
+            data_dir,
+        }
+    }
+
+    fn view_names(&self, prefix: &str) -> Vec<Pair> {
+        let views = Views::load(&self.data_dir).unwrap_or_default();
+        plain(
+            views
+                .names()
+                .iter()
+                .map(|name| format!("{}{}", prefix, name))
+                .collect(),
+        )
+    }
+
+    fn event_references(&self) -> Vec<Pair> {
+        let manager = self.event_manager.lock().unwrap();
+        manager
+            .events()
+            .iter()
+            .map(|event| Pair {
+                display: format!("{}  {}", event.event_id, event.title),
+                replacement: format!("{} ", event.event_id),
+            })
+            .collect()
+    }
+
+    fn contact_references(&self) -> Vec<Pair> {
+        contact_list()
+            .iter()
+            .map(|contact| Pair {
+                display: format!(
+                    "{}  {} <{}>",
+                    contact.contact_id,
+                    contact.name,
+                    contact.email()
+                ),
+                replacement: format!("{} ", contact.contact_id),
+            })
+            .collect()
+    }
+
+    fn candidates(&self, words: &[&str]) -> Vec<Pair> {
+        let mut command = Cli::command();
+        command.build(); // propagates global flags like --format to the subcommands
+
+        // Walk down the subcommands that are already typed
+        let mut current = &command;
+        let mut depth = 0;
+        while let Some(sub) = words
+            .get(depth)
+            .and_then(|word| current.find_subcommand(word))
+        {
+            current = sub;
+            depth += 1;
+        }
+        let positional_count = words.len() - depth;
+        let previous = words.last().copied().unwrap_or("");
+
+        if words.is_empty() {
+            let mut names = subcommand_names(current);
+            names.extend(REPL_COMMANDS.iter().map(|name| name.to_string()));
+            return plain(names);
+        }
+        if matches!(previous, "-c" | "--category") {
+            return plain(category_names());
+        }
+        if matches!(
+            previous,
+            "--attendee" | "--optional" | "--organizer" | "--as"
+        ) {
+            return self.contact_references();
+        }
+        // Values of a flag, e.g. the output formats after `--format`
+        if let Some(argument) = current.get_arguments().find(|argument| {
+            let short = previous
+                .strip_prefix('-')
+                .and_then(|short| short.chars().next());
+            let long = previous.strip_prefix("--");
+            (long.is_some() && long == argument.get_long())
+                || (previous.len() == 2 && short.is_some() && short == argument.get_short())
+        }) {
+            let values = argument.get_possible_values();
+            if !values.is_empty() {
+                return plain(
+                    values
+                        .iter()
+                        .map(|value| value.get_name().to_string())
+                        .collect(),
+                );
+            }
+        }
+        if previous == "--set" {
+            return SETTABLE_FIELDS
+                .iter()
+                .map(|field| Pair {
+                    display: field.to_string(),
+                    replacement: format!("{}=", field),
+                })
+                .collect();
+        }
+
+        let mut candidates = vec![];
+        if positional_count == 0 {
+            candidates.extend(plain(subcommand_names(current)));
+        }
+        match current.get_name() {
+            "view" | "remove" if positional_count == 0 && words[0] == "view" => {
+                candidates.extend(self.view_names(""));
+            }
+            "edit" | "remove" if positional_count == 0 && words[0] == "contact" => {
+                candidates.extend(self.contact_references());
+            }
+            "edit" | "remove" | "event" if positional_count == 0 => {
+                candidates.extend(self.event_references());
+            }
+            "list" if positional_count == 0 => candidates.extend(self.view_names("@")),
+            "add" => {
+                candidates.extend(EVENT_FIELDS.iter().map(|[field, _]| Pair {
+                    display: field.to_string(),
+                    replacement: format!("{}: ", field),
+                }));
+            }
+            "search" => {
+                let fields = SearchType::NAMES.iter().chain(DATE_FIELDS.iter());
+                candidates.extend(fields.map(|field| Pair {
+                    display: format!("{}:", field),
+                    replacement: format!("{}:", field),
+                }));
+            }
+            _ => {}
+        }
+        // Values of the next positional argument, e.g. the search types
+        if let Some(argument) = current
+            .get_positionals()
+            .nth(positional_count)
+            .filter(|argument| !argument.get_possible_values().is_empty())
+        {
+            candidates.extend(plain(
+                argument
+                    .get_possible_values()
+                    .iter()
+                    .map(|value| value.get_name().to_string())
+                    .collect(),
+            ));
+        }
+        candidates.extend(plain(
+            current
+                .get_arguments()
+                .filter_map(|argument| argument.get_long())
+                .map(|long| format!("--{}", long))
+                .collect(),
+        ));
+        candidates
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .rfind(|c: char| c.is_whitespace())
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+        let words: Vec<&str> = before[..start].split_whitespace().collect();
+
+        let mut candidates: Vec<Pair> = self
+            .candidates(&words)
+            .into_iter()
+            .filter(|pair| {
+                pair.replacement.starts_with(word)
+                    || (!word.is_empty()
+                        && pair.display.to_lowercase().contains(&word.to_lowercase()))
+            })
+            .collect();
+        candidates.dedup_by(|a, b| a.replacement == b.replacement);
+        Ok((start, candidates))
+    }
+}
+
+fn subcommand_names(command: &clap::Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect()
+}
+
+fn plain(values: Vec<String>) -> Vec<Pair> {
+    values
+        .into_iter()
+        .map(|value| Pair {
+            display: value.clone(),
+            replacement: format!("{} ", value),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event::Event;
+    use crate::events::event_manager::EventManagerMode;
+    use crate::miscs::views::View;
+    use rustyline::history::DefaultHistory;
+
+    fn helper() -> ReplHelper {
+        let data_dir = std::env::temp_dir().join("rustyplanner-completion");
+        let _ = std::fs::remove_dir_all(&data_dir);
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let mut views = Views::default();
+        let view = View {
+            query: "category:Work".to_string(),
+            ..Default::default()
+        };
+        views.insert("work", view).unwrap();
+        views.save(&data_dir).unwrap();
+        let path = data_dir.join("events.json");
+        let manager = EventManager::new(path, false, EventManagerMode::Active);
+        manager.lock().unwrap().add_event(Event {
+            title: "Lunch".to_string(),
+            ..Default::default()
+        });
+        ReplHelper::new(manager, data_dir)
+    }
+
+    fn complete(helper: &ReplHelper, line: &str) -> (usize, Vec<String>) {
+        let history = DefaultHistory::new();
+        let (start, pairs) = helper
+            .complete(line, line.len(), &Context::new(&history))
+            .unwrap();
+        (
+            start,
+            pairs.into_iter().map(|pair| pair.replacement).collect(),
+        )
+    }
+
+    #[test]
+    fn completes_commands_and_flags() {
+        let helper = helper();
+        let (start, words) = complete(&helper, "lis");
+        assert_eq!((start, words), (0, vec!["list ".to_string()]));
+        assert!(complete(&helper, "").1.contains(&"exit ".to_string()));
+        let (start, words) = complete(&helper, "list --f");
+        assert_eq!(start, 5);
+        assert_eq!(words, ["--from ", "--format "]);
+        let (_, words) = complete(&helper, "list --format j");
+        assert_eq!(words, ["json ", "jsonl "]);
+        let (_, words) = complete(&helper, "search tit");
+        assert_eq!(words, ["title:"]);
+    }
+
+    #[test]
+    fn completes_data() {
+        let helper = helper();
+        // Event IDs also match by title
+        let (_, words) = complete(&helper, "edit lun");
+        assert_eq!(words, ["#1 "]);
+        let (_, words) = complete(&helper, "edit #1 --set dur");
+        assert_eq!(words, ["duration="]);
+        let (_, words) = complete(&helper, "add locat");
+        assert_eq!(words, ["location: ", "--location "]);
+        let (_, words) = complete(&helper, "list @w");
+        assert_eq!(words, ["@work "]);
+        let (_, words) = complete(&helper, "view remove w");
+        assert_eq!(words, ["work "]);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// User settings, persisted as config.json next to dates.json
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub default_duration: String, // Duration used when an event is added without one, e.g. "1h"
+    pub default_notify_before: i64, // Minutes before an event for the default notification
+    pub all_day_reminder: String, // Default reminder of all-day events, e.g. "1d at 18:00", "" for none
+    pub urgent_reminder: String,  // Extra early reminder of urgent events, e.g. "1d", "" for none
+    pub working_hours: String,    // Part of the day `free` looks in, e.g. "09:00-17:00"
+    pub working_days: String,     // Days `free` looks in, e.g. "mon-fri"
+    pub user_name: String,        // Your name as the organizer of events
+    pub user_email: String,       // Your email, for organizing and answering invitations
+    pub send_invitations: bool,   // Email attendees when events you organize change
+    pub smtp_server: String,      // Host the invitations are sent through
+    pub smtp_port: i64,           // 0 for the usual port of smtp_security
+    pub smtp_security: String,    // "starttls", "tls" or "none"
+    pub smtp_user: String,        // "" to send without logging in
+    pub smtp_password: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_duration: "1h".to_string(),
+            default_notify_before: 10,
+            all_day_reminder: "1d at 18:00".to_string(),
+            urgent_reminder: "1d".to_string(),
+            working_hours: "09:00-17:00".to_string(),
+            working_days: "mon-fri".to_string(),
+            user_name: String::new(),
+            user_email: String::new(),
+            send_invitations: false,
+            smtp_server: String::new(),
+            smtp_port: 0,
+            smtp_security: "starttls".to_string(),
+            smtp_user: String::new(),
+            smtp_password: String::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("config.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        let path = Self::path(data_dir);
+        let json_string = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json_string).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn to_object(&self) -> serde_json::Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(object)) => object,
+            _ => serde_json::Map::new(),
+        }
+    }
+
+    // Every key and value, passwords hidden
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.to_object()
+            .into_iter()
+            .map(|(key, value)| match value_to_string(&value) {
+                value if key.ends_with("password") && !value.is_empty() => {
+                    (key, "********".to_string())
+                }
+                value => (key, value),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.to_object().get(key).map(value_to_string)
+    }
+
+    // Sets a key from its string form, parsed according to the type of the current value
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut object = self.to_object();
+        let current = object
+            .get(key)
+            .ok_or_else(|| format!("unknown config key '{}'", key))?;
+        let new_value = match current {
+            Value::String(_) => Value::String(value.to_string()),
+            Value::Bool(_) => Value::Bool(
+                value
+                    .parse::<bool>()
+                    .map_err(|_| format!("'{}' expects true or false", key))?,
+            ),
+            Value::Number(_) => Value::from(
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("'{}' expects a whole number", key))?,
+            ),
+            _ => {
+                serde_json::from_str(value).map_err(|e| format!("'{}' expects JSON: {}", key, e))?
+            }
+        };
+        object.insert(key.to_string(), new_value);
+        *self = serde_json::from_value(Value::Object(object)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
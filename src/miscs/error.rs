@@ -0,0 +1,41 @@
+use std::process::ExitCode;
+
+// Errors surfaced by command handlers, each mapped to its own process exit code
+#[derive(Debug)]
+pub enum AppError {
+    Usage(String),    // Command was understood but used incorrectly
+    NotFound(String), // Referenced event, category or key does not exist
+    Invalid(String),  // Input could not be parsed into valid data
+    Io(String),       // Reading or writing a file failed
+    Service(String),  // Background service could not be managed
+}
+
+impl AppError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AppError::Usage(_) => 2,
+            AppError::NotFound(_) => 3,
+            AppError::Invalid(_) => 4,
+            AppError::Io(_) => 5,
+            AppError::Service(_) => 6,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Usage(msg) => write!(f, "usage error: {}", msg),
+            AppError::NotFound(msg) => write!(f, "not found: {}", msg),
+            AppError::Invalid(msg) => write!(f, "invalid input: {}", msg),
+            AppError::Io(msg) => write!(f, "i/o error: {}", msg),
+            AppError::Service(msg) => write!(f, "service error: {}", msg),
+        }
+    }
+}
+
+impl From<AppError> for ExitCode {
+    fn from(error: AppError) -> Self {
+        ExitCode::from(error.exit_code())
+    }
+}
@@ -1,26 +1,16 @@
-use crate::events::event::{ATTENDEE_FIELDS, EVENT_FIELDS, RECURRENCE_FIELDS};
+use crate::events::event::{ATTENDEE_FIELDS, EVENT_FIELDS, NOTIFICATION_FIELDS, RECURRENCE_FIELDS};
+use crate::events::event_manager::SearchType;
 
-pub fn print_help() {
-    println!("Available commands:");
-    println!("  add    - Add a new event or category");
-    println!("  save   - Save events to file");
-    println!("  remove - Remove an event by index");
-    println!("  edit   - Edit an event by index");
-    println!("  cls    - Clear the screen");
-    println!("  list   - List all events");
-    println!("  clear  - Clear all events");
-    println!("  help   - Show this help message");
-    println!("  exit   - Exit the application");
-    println!();
-    //println!("Use the 'add' command to create a new event with optional parameters for description, location, and notification time.");
-    println!(
-        "For more details on each command run `help [Command Name]`, refer to the documentation."
-    );
-}
-
-pub fn print_add_help() {
-    let mut help_message = "Usage: add [event|category] [OPTIONS]\n\n".to_string();
-    help_message += "event:\n";
+// Describes the `[key: value, ...]` syntax accepted by `add event`
+pub fn data_syntax_help() -> String {
+    let mut help_message =
+        "Event data syntax: [key: value, key: [nested: value], ...]\n\n".to_string();
+    help_message += "The outer brackets are optional and `-` or `_` may be used in keys.\n";
+    help_message +=
+        "Values are bare text, \"quoted text\" (with \\\" and \\\\ escapes), numbers,\n";
+    help_message += "true/false, [lists, of, values] or [nested: objects]. Quote values\n";
+    help_message += "containing commas or brackets, or escape them with a backslash.\n\n";
+    help_message += "Event Attributes:\n";
     for attribute in EVENT_FIELDS.iter() {
         let part_a = format!("\t{}:", attribute[0]);
         let part_b = format!("\t[{}]", attribute[1]);
@@ -34,57 +24,53 @@ pub fn print_add_help() {
             }
         }
         if attribute[0] == "attendees" {
-            help_message += "\tAttendee Attributes:\n";
+            help_message += "\t\tAttendee Attributes:\n";
             for attendee_attributes in ATTENDEE_FIELDS.iter() {
                 let part_a = format!("\t{}:", attendee_attributes[0]);
                 let part_b = format!("\t[{}]", attendee_attributes[1]);
                 help_message += &format!("\t\t{:<20}{}\n", part_a, part_b);
             }
         }
+        if attribute[0] == "notification_settings" {
+            help_message += "\t\tNotification Attributes:\n";
+            for notification_attributes in NOTIFICATION_FIELDS.iter() {
+                let part_a = format!("\t{}:", notification_attributes[0]);
+                let part_b = format!("\t[{}]", notification_attributes[1]);
+                help_message += &format!("\t\t{:<20}{}\n", part_a, part_b);
+            }
+        }
     }
-    help_message += "\ncategory:\n";
-    help_message += "\t[name]";
-    println!("{}", help_message);
+    help_message
 }
 
-pub fn print_save_help() {
-    println!("  save           - Save events to file");
-    println!("                  Usage: save <filename>");
-    println!("                  Description: Saves all current events to the specified file.");
-}
-
-pub fn print_remove_help() {
-    println!("  remove <index> - Remove an event by index");
-    println!("                  Usage: remove <index>");
-    println!("                  Description: Removes the event at the specified index from the list of events.");
-}
-
-pub fn print_edit_help() {
-    println!("  edit <index>   - Edit an event by index");
-    println!("                  Usage: edit <index> [options]");
-    println!("                  Description: Edits the event at the specified index. Options can include");
-    println!("                  mode, name, date, time, description, location, and alarm time.");
-}
-
-pub fn print_cls_help() {
-    println!("  cls            - Clear the screen");
-    println!("                  Description: Clears the console screen for better visibility.");
-}
-
-pub fn print_list_help() {
-    println!("  list [events|categories] - List all events");
-    println!(
-        "                            Description: Displays all current events in the calendar."
-    );
-}
-
-pub fn print_search_help() {
-    println!("  search <type> <keyword> - Search for events by keyword");
-    println!("                           Description: Searches for events that contain the specified keyword."
-    );
-}
-
-pub fn print_clear_help() {
-    println!("  clear          - Clear all events");
-    println!("                  Description: Removes all events from the calendar.");
+// Describes the query language of `search`
+pub fn query_syntax_help() -> String {
+    let mut help_message =
+        "Query syntax: field:value terms combined with AND, OR, NOT and ( )\n\n".to_string();
+    help_message += "Terms next to each other must all match, AND binds before OR. Words\n";
+    help_message += "without a field are full-text searched in title, description, location,\n";
+    help_message += "categories, tags and attendees, also as word starts or with typos, best\n";
+    help_message += "first.\n";
+    help_message += "Quote values with spaces: title:\"team sync\".\n\n";
+    help_message += "Fields:\n";
+    for field in SearchType::NAMES {
+        if field == "priority" {
+            help_message += "\tpriority:\tlow, normal, high or urgent, high+ for high or urgent\n";
+        } else {
+            help_message += &format!("\t{}:\n", field);
+        }
+    }
+    help_message += "\tafter:\t\tevents still going on or repeating after a date\n";
+    help_message += "\tbefore:\t\tevents starting before a date\n";
+    help_message +=
+        "\trange:\t\tevents taking place today, tomorrow, week, month, next 7d, ...\n\n";
+    help_message += "Examples:\n";
+    help_message +=
+        "\tsearch title:standup AND category:Work AND after:2026-10-01 NOT location:remote\n";
+    help_message +=
+        "\tsearch -i \"(category:work OR category:family) before:\\\"next friday\\\"\"\n";
+    help_message += "\tsearch -r 'title:^(Lunch|Dinner)$'\n";
+    help_message += "\tsearch tag:travel priority:high+\n";
+    help_message += "\tsearch location Room 4 (the old form, a field and the text to find)\n";
+    help_message
 }
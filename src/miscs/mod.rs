@@ -1,4 +1,13 @@
 pub mod arg_parsing;
+pub mod calendar;
+pub mod cli;
+pub mod completion;
+pub mod config;
+pub mod error;
 pub mod help;
+pub mod natural;
 pub mod notification;
+pub mod output;
+pub mod render;
 pub mod utils;
+pub mod views;
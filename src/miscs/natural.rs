@@ -0,0 +1,745 @@
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveTime, Timelike, Weekday,
+};
+
+use chrono_tz::Tz;
+
+use crate::events::event::{
+    parse_weekday, DayReminder, Notification, Recurrence, RecurrenceFrequency, MAX_INTERVAL,
+};
+use crate::miscs::utils::{
+    parse_duration, try_date_from_str, wall_time, zoned_from_naive, MAX_DURATION_DAYS, TIME_FORMATS,
+};
+
+// Result of parsing a phrase like "tomorrow 3pm for 45m" or "every monday at 10"
+#[derive(Debug)]
+pub struct When {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>, // None when neither a duration nor an end was given
+    pub all_day: bool,                // No time of day was given
+    pub recurrence: Option<Recurrence>,
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+fn parse_month(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(word))
+        .map(|index| index as u32 + 1)
+}
+
+fn parse_number(word: &str) -> Option<i64> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        _ => word.parse().ok(),
+    }
+}
+
+// "15:00", "9:30am", "3pm", "noon" and "midnight"
+pub fn parse_time(word: &str) -> Option<NaiveTime> {
+    let word = word.to_lowercase();
+    match word.as_str() {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    if let Some(time) = TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(&word, format).ok())
+    {
+        return Some(time);
+    }
+    let (clock, pm) = if let Some(clock) = word.strip_suffix("pm") {
+        (clock, true)
+    } else {
+        (word.strip_suffix("am")?, false)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    let hour = match (hour, pm) {
+        (12, false) => 0,
+        (12, true) => 12,
+        (hour, true) => hour + 12,
+        (hour, false) => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// "9-17", "09:00-17:30" or "9am-5pm" as the start and end of a part of the day
+pub fn parse_hours(spec: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let clock = |word: &str| {
+        let word = word.trim();
+        parse_time(word).or_else(|| NaiveTime::from_hms_opt(word.parse().ok()?, 0, 0))
+    };
+    let (start, end) = spec.split_once('-')?;
+    Some((clock(start)?, clock(end)?))
+}
+
+// First `weekday` on or after `date`, or strictly after it with `skip_today`
+fn next_weekday(date: NaiveDate, weekday: Weekday, skip_today: bool) -> NaiveDate {
+    let mut days = (weekday.num_days_from_monday() as i64
+        - date.weekday().num_days_from_monday() as i64)
+        .rem_euclid(7);
+    if days == 0 && skip_today {
+        days = 7;
+    }
+    date + Duration::days(days)
+}
+
+// "mon-fri", "sat-sun" or "mon,wed,fri" (also "weekday" for mon-fri), in week order
+pub fn parse_weekday_set(word: &str) -> Option<Vec<Weekday>> {
+    if word == "weekday" {
+        return parse_weekday_set("mon-fri");
+    }
+    let mut days = vec![];
+    for part in word.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let mut day = parse_weekday(first)?;
+                let last = parse_weekday(last)?;
+                while day != last {
+                    days.push(day);
+                    day = day.succ();
+                }
+                days.push(last);
+            }
+            None => days.push(parse_weekday(part)?),
+        }
+    }
+    days.sort_by_key(|day| day.num_days_from_monday());
+    days.dedup();
+    Some(days)
+}
+
+// The first of `days` on or after `today`
+fn first_of(days: &[Weekday], today: NaiveDate) -> NaiveDate {
+    days.iter()
+        .map(|day| next_weekday(today, *day, false))
+        .min()
+        .unwrap_or(today)
+}
+
+// The length of `count` units, None for unknown units and an error for lengths no date can take
+fn unit_duration(unit: &str, count: i64) -> Option<Result<Duration, String>> {
+    let make = match unit.trim_end_matches('s') {
+        "m" | "min" | "minute" => Duration::try_minutes,
+        "h" | "hr" | "hour" => Duration::try_hours,
+        "d" | "day" => Duration::try_days,
+        "w" | "week" => Duration::try_weeks,
+        _ => return None,
+    };
+    Some(
+        make(count)
+            .filter(|duration| duration.num_days().abs() <= MAX_DURATION_DAYS)
+            .ok_or_else(|| format!("{} {} is too long", count, unit)),
+    )
+}
+
+fn unit_frequency(unit: &str) -> Option<RecurrenceFrequency> {
+    match unit.trim_end_matches('s') {
+        "hour" => Some(RecurrenceFrequency::Hourly),
+        "day" => Some(RecurrenceFrequency::Daily),
+        "week" => Some(RecurrenceFrequency::Weekly),
+        "month" => Some(RecurrenceFrequency::Monthly),
+        "year" => Some(RecurrenceFrequency::Yearly),
+        _ => None,
+    }
+}
+
+// Parses a day on its own: a date in one of the usual formats, today, tomorrow, yesterday,
+// a weekday or "next <weekday>"
+pub fn parse_day(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Some(date) = try_date_from_str(input) {
+        return Some(date);
+    }
+    let words: Vec<String> = input.split_whitespace().map(str::to_lowercase).collect();
+    match words
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["today"] => Some(today),
+        ["tomorrow"] => Some(today + Duration::days(1)),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        [day] | ["this", day] => Some(next_weekday(today, parse_weekday(day)?, false)),
+        ["next", day] => Some(next_weekday(today, parse_weekday(day)?, true)),
+        _ => None,
+    }
+}
+
+// Parses phrases like "tomorrow 3pm for 45m", "next friday 09:30", "in 2 hours",
+// "every monday at 10" or "oct 20 14:00 until 16:00". Anything not understood is an error.
+// Days and times are wall-clock times in `zone`, the local time zone if None.
+pub fn parse_when(input: &str, now: DateTime<Local>, zone: Option<Tz>) -> Result<When, String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|word| word.trim_end_matches(',').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return Err("no date or time given".to_string());
+    }
+    let today = wall_time(now, zone).date();
+    let unknown = |word: &str| format!("cannot understand '{}' in '{}'", word, input.trim());
+
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut relative: Option<DateTime<Local>> = None;
+    let mut length: Option<Duration> = None;
+    let mut end_time: Option<NaiveTime> = None;
+    let mut end_date: Option<NaiveDate> = None; // Last day of an all-day event
+    let mut repeat: Option<(RecurrenceFrequency, i64)> = None;
+    let mut week_days: Vec<Weekday> = vec![];
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].as_str();
+        let next = words.get(i + 1).map(String::as_str);
+        let after_next = words.get(i + 2).map(String::as_str);
+        i += 1;
+
+        match word {
+            "on" | "the" => {}
+            "at" => {
+                let value = next.ok_or_else(|| "expected a time after 'at'".to_string())?;
+                // "at 10" means 10:00, "at 3 pm" is 15:00
+                let (parsed, used) = match (value.parse::<u32>(), after_next) {
+                    (Ok(hour), Some(suffix @ ("am" | "pm"))) => {
+                        (parse_time(&format!("{}{}", hour, suffix)), 2)
+                    }
+                    (Ok(hour), _) => (NaiveTime::from_hms_opt(hour, 0, 0), 1),
+                    _ => (parse_time(value), 1),
+                };
+                time = Some(parsed.ok_or_else(|| unknown(value))?);
+                i += used;
+            }
+            "today" | "tomorrow" | "yesterday" | "tonight" => {
+                let day = parse_day(word, today).unwrap_or(today); // tonight is today
+                set_date(&mut date, day, word)?;
+                if word == "tonight" && time.is_none() {
+                    time = NaiveTime::from_hms_opt(20, 0, 0);
+                }
+            }
+            "this" | "next" => {
+                let value = next.ok_or_else(|| format!("expected a day after '{}'", word))?;
+                let day = if let Some(weekday) = parse_weekday(value) {
+                    next_weekday(today, weekday, word == "next")
+                } else {
+                    match (word, value) {
+                        ("next", "week") => next_weekday(today, Weekday::Mon, true),
+                        ("next", "month") => (today.with_day(1).unwrap()) + Months::new(1),
+                        ("next", "year") => {
+                            NaiveDate::from_ymd_opt(today.year() + 1, 1, 1).unwrap()
+                        }
+                        _ => return Err(unknown(value)),
+                    }
+                };
+                set_date(&mut date, day, word)?;
+                i += 1;
+            }
+            "in" => {
+                let count = next.and_then(parse_number).ok_or_else(|| {
+                    "expected an amount after 'in', e.g. 'in 2 hours'".to_string()
+                })?;
+                let unit = after_next.unwrap_or("");
+                let offset = unit_duration(unit, count).ok_or_else(|| unknown(unit))??;
+                relative = Some(
+                    now.checked_add_signed(offset)
+                        .ok_or_else(|| format!("in {} {} is too far away", count, unit))?,
+                );
+                i += 2;
+            }
+            "for" => {
+                let value = next.ok_or_else(|| "expected a duration after 'for'".to_string())?;
+                if let Ok(duration) = parse_duration(value) {
+                    length = Some(duration);
+                    i += 1;
+                } else {
+                    let count = parse_number(value).ok_or_else(|| unknown(value))?;
+                    let unit = after_next.unwrap_or("");
+                    length = Some(unit_duration(unit, count).ok_or_else(|| unknown(unit))??);
+                    i += 2;
+                }
+            }
+            "until" | "till" | "to" | "through" | "-" => {
+                let value =
+                    next.ok_or_else(|| format!("expected a time or day after '{}'", word))?;
+                let base = date.unwrap_or(today);
+                if let Some(parsed) = parse_time(value) {
+                    end_time = Some(parsed);
+                } else if let Some(day) = try_date_from_str(value) {
+                    end_date = Some(day);
+                } else if let Some(weekday) = parse_weekday(value) {
+                    end_date = Some(next_weekday(base, weekday, false));
+                } else if let Some((day, used)) = parse_month_day(
+                    value,
+                    after_next,
+                    words.get(i + 2).map(String::as_str),
+                    base,
+                ) {
+                    end_date = Some(day);
+                    i += used;
+                } else {
+                    return Err(unknown(value));
+                }
+                i += 1;
+            }
+            "every" => {
+                let mut value = next.ok_or_else(|| "expected a unit after 'every'".to_string())?;
+                let mut interval = 1;
+                if let Some(count) = value.parse::<i64>().ok().filter(|count| *count > 0) {
+                    if count > MAX_INTERVAL {
+                        return Err(format!(
+                            "every {} is too far apart, at most every {} is possible",
+                            count, MAX_INTERVAL
+                        ));
+                    }
+                    interval = count;
+                    value =
+                        after_next.ok_or_else(|| "expected a unit after 'every'".to_string())?;
+                    i += 1;
+                } else if value == "other" {
+                    interval = 2;
+                    value = after_next
+                        .ok_or_else(|| "expected a unit after 'every other'".to_string())?;
+                    i += 1;
+                }
+                if let Some(days) = parse_weekday_set(value) {
+                    repeat = Some((RecurrenceFrequency::Weekly, interval));
+                    set_date(&mut date, first_of(&days, today), value)?;
+                    if days.len() > 1 {
+                        week_days = days;
+                    }
+                } else {
+                    let frequency = unit_frequency(value).ok_or_else(|| unknown(value))?;
+                    repeat = Some((frequency, interval));
+                }
+                i += 1;
+            }
+            "hourly" | "daily" | "weekly" | "monthly" | "yearly" => {
+                repeat = Some((RecurrenceFrequency::from_str(word), 1));
+            }
+            "weekdays" => {
+                week_days = parse_weekday_set("mon-fri").unwrap_or_default();
+                repeat = Some((RecurrenceFrequency::Weekly, 1));
+                set_date(&mut date, first_of(&week_days, today), word)?;
+            }
+            _ => {
+                if let Some(parsed) = parse_time(word) {
+                    time = Some(parsed);
+                } else if let Some(suffix @ ("am" | "pm")) =
+                    next.filter(|_| word.parse::<u32>().is_ok())
+                {
+                    time = Some(
+                        parse_time(&format!("{}{}", word, suffix)).ok_or_else(|| unknown(word))?,
+                    );
+                    i += 1;
+                } else if let Some(day) = try_date_from_str(word) {
+                    set_date(&mut date, day, word)?;
+                } else if let Some(weekday) = parse_weekday(word) {
+                    set_date(&mut date, next_weekday(today, weekday, false), word)?;
+                } else if let Some(days) = parse_weekday_set(word) {
+                    // "mon-fri" or "mon,wed,fri" repeat weekly on those days
+                    set_date(&mut date, first_of(&days, today), word)?;
+                    repeat = Some((RecurrenceFrequency::Weekly, 1));
+                    week_days = days;
+                } else if let Some((day, used)) = parse_month_day(word, next, after_next, today) {
+                    set_date(&mut date, day, word)?;
+                    i += used;
+                } else if let Ok(duration) = parse_duration(word) {
+                    length = Some(duration);
+                } else {
+                    return Err(unknown(word));
+                }
+            }
+        }
+    }
+
+    let all_day = time.is_none() && relative.is_none() && end_time.is_none();
+    let start = match (relative, date, time) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err("'in ...' cannot be combined with a date or time".to_string())
+        }
+        (Some(relative), None, None) => relative,
+        (None, date, time) => zoned_from_naive(
+            date.unwrap_or(today)
+                .and_time(time.unwrap_or(NaiveTime::MIN)),
+            zone,
+        )?,
+    };
+
+    // All-day events end at midnight after their last day, counted in days so DST can't shift it
+    let first_day = wall_time(start, zone).date();
+    let midnight_after = |last: NaiveDate| {
+        zoned_from_naive((last + Duration::days(1)).and_time(NaiveTime::MIN), zone)
+    };
+    let end = match (length, end_time, end_date) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+            return Err("give either a duration or an end, not both".to_string())
+        }
+        (None, None, Some(_)) if !all_day => {
+            return Err("an end day can only be given for all-day events".to_string())
+        }
+        (None, None, Some(last)) if last < first_day => {
+            return Err(format!("the last day {} is before the first day", last))
+        }
+        (None, None, Some(last)) => Some(midnight_after(last)?),
+        (Some(length), None, None) if all_day => {
+            let days = (length.num_seconds() + 86_399) / 86_400;
+            Some(midnight_after(first_day + Duration::days(days.max(1) - 1))?)
+        }
+        (Some(length), None, None) => Some(start + length),
+        (None, Some(end_time), None) => {
+            let mut end = wall_time(start, zone).date().and_time(end_time);
+            if end <= wall_time(start, zone) {
+                end += Duration::days(1);
+            }
+            Some(zoned_from_naive(end, zone)?)
+        }
+        (None, None, None) if all_day => Some(midnight_after(first_day)?),
+        (None, None, None) => None,
+    };
+
+    let recurrence = repeat.map(|(frequency, interval)| {
+        pinned_recurrence(frequency, interval, week_days, start, zone)
+    });
+
+    Ok(When {
+        start,
+        end,
+        all_day,
+        recurrence,
+    })
+}
+
+// A series starting at `start`, on `week_days` for weekly ones if not empty. The pattern is
+// pinned to the start so notifications fire at the right moment.
+pub fn pinned_recurrence(
+    frequency: RecurrenceFrequency,
+    interval: i64,
+    week_days: Vec<Weekday>,
+    start: DateTime<Local>,
+    zone: Option<Tz>,
+) -> Recurrence {
+    let wall = wall_time(start, zone);
+    Recurrence {
+        minute: Some(wall.minute()),
+        hour: (frequency != RecurrenceFrequency::Hourly).then(|| wall.hour()),
+        day: matches!(
+            frequency,
+            RecurrenceFrequency::Monthly | RecurrenceFrequency::Yearly
+        )
+        .then(|| wall.day()),
+        week_day: (frequency == RecurrenceFrequency::Weekly && week_days.is_empty())
+            .then(|| wall.weekday()),
+        month: (frequency == RecurrenceFrequency::Yearly).then(|| wall.month()),
+        year: None,
+        week_days,
+        frequency,
+        interval,
+        start_date: start,
+        end_date: None,
+    }
+}
+
+fn set_date(slot: &mut Option<NaiveDate>, value: NaiveDate, word: &str) -> Result<(), String> {
+    if slot.replace(value).is_some() {
+        Err(format!("more than one date given at '{}'", word))
+    } else {
+        Ok(())
+    }
+}
+
+// "oct 20", "20 october" or "october 20th 2027", returns the date and how many extra words were used.
+// Without a year the next such date from today is taken.
+fn parse_month_day(
+    word: &str,
+    next: Option<&str>,
+    after_next: Option<&str>,
+    today: NaiveDate,
+) -> Option<(NaiveDate, usize)> {
+    let day_number = |word: &str| -> Option<u32> {
+        word.trim_end_matches(|c: char| c.is_alphabetic())
+            .parse()
+            .ok()
+            .filter(|day| (1..=31).contains(day))
+    };
+    let (month, day) = match (parse_month(word), day_number(word)) {
+        (Some(month), _) => (month, day_number(next?)?),
+        (None, Some(day)) => (parse_month(next?)?, day),
+        _ => return None,
+    };
+    if let Some(year) = after_next
+        .and_then(|year| year.parse::<i32>().ok())
+        .filter(|year| *year > 999)
+    {
+        return Some((NaiveDate::from_ymd_opt(year, month, day)?, 2));
+    }
+    let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if date < today {
+        Some((NaiveDate::from_ymd_opt(today.year() + 1, month, day)?, 1))
+    } else {
+        Some((date, 1))
+    }
+}
+
+// A reminder: minutes ("15"), a duration before the start ("10m", "1h") or a time of day on
+// or before the event's day ("the day before at 18:00", "2 days before at 9am",
+// "on the day at 8:00", or the short form "1d@18:00")
+pub fn parse_reminder(input: &str) -> Result<Notification, String> {
+    let text = input.trim().to_lowercase();
+    let invalid = || {
+        format!(
+            "cannot understand the reminder '{}', try e.g. 10m, 1h or \"the day before at 18:00\"",
+            input.trim()
+        )
+    };
+    let split = text
+        .split_once('@')
+        .or_else(|| text.split_once(" at "))
+        .or_else(|| text.strip_prefix("at ").map(|time| ("", time)));
+    let Some((day, time)) = split else {
+        let minutes = match text.parse::<i64>() {
+            Ok(minutes) => minutes,
+            Err(_) => parse_duration(&text).map_err(|_| invalid())?.num_minutes(),
+        };
+        // Further away than any date could be
+        if minutes.abs() > MAX_DURATION_DAYS * 24 * 60 {
+            return Err(format!("the reminder '{}' is too early", input.trim()));
+        }
+        return Ok(Notification {
+            notify_before: minutes,
+            ..Default::default()
+        });
+    };
+
+    let time = parse_time(time.trim()).ok_or_else(invalid)?;
+    let day = day.trim();
+    let day = day.strip_prefix("the ").unwrap_or(day);
+    let days_before = match day.strip_suffix(" before").unwrap_or(day).trim() {
+        "" | "on the day" | "on day" | "same day" | "day of" => 0,
+        "day" | "a day" | "one day" => 1,
+        amount => match amount.split_once(' ') {
+            Some((count, "day" | "days")) => parse_number(count).ok_or_else(invalid)?,
+            _ => parse_duration(amount)
+                .ok()
+                .filter(|duration| duration.num_seconds() % 86_400 == 0)
+                .map(|duration| duration.num_days())
+                .ok_or_else(invalid)?,
+        },
+    };
+    if days_before > MAX_DURATION_DAYS {
+        return Err(format!("the reminder '{}' is too early", input.trim()));
+    }
+    Ok(Notification {
+        on_day: Some(DayReminder {
+            days_before: u32::try_from(days_before).map_err(|_| invalid())?,
+            time,
+        }),
+        ..Default::default()
+    })
+}
+
+// A point in time, either in one of the strict formats or as a phrase like "next friday 09:30"
+pub fn parse_datetime(
+    input: &str,
+    now: DateTime<Local>,
+    zone: Option<Tz>,
+) -> Result<DateTime<Local>, String> {
+    if let Ok(datetime) = crate::miscs::utils::datetime_from_str(input, zone) {
+        return Ok(datetime);
+    }
+    let when = parse_when(input, now, zone)?;
+    if when.recurrence.is_some() || when.end.is_some() && !when.all_day {
+        return Err(format!("'{}' is not a single point in time", input.trim()));
+    }
+    Ok(when.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONE: Option<Tz> = Some(chrono_tz::Europe::Berlin);
+
+    // Wednesday 2026-10-14 09:00 in Berlin
+    fn now() -> DateTime<Local> {
+        zoned_from_naive(at("2026-10-14 09:00"), ZONE).unwrap()
+    }
+
+    fn at(text: &str) -> chrono::NaiveDateTime {
+        chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    // Start and end as wall-clock times in Berlin
+    fn when(input: &str) -> (chrono::NaiveDateTime, Option<chrono::NaiveDateTime>, bool) {
+        let when = parse_when(input, now(), ZONE).unwrap();
+        let end = when.end.map(|end| wall_time(end, ZONE));
+        (wall_time(when.start, ZONE), end, when.all_day)
+    }
+
+    #[test]
+    fn days_and_times() {
+        let tomorrow = at("2026-10-15 15:00");
+        assert_eq!(when("tomorrow 3pm"), (tomorrow, None, false));
+        assert_eq!(when("tomorrow at 3 pm"), (tomorrow, None, false));
+        assert_eq!(
+            when("tomorrow 3pm for 45m"),
+            (tomorrow, Some(at("2026-10-15 15:45")), false)
+        );
+        assert_eq!(
+            when("friday 09:30 until 11:00"),
+            (at("2026-10-16 09:30"), Some(at("2026-10-16 11:00")), false)
+        );
+        assert_eq!(when("next wednesday at 10").0, at("2026-10-21 10:00"));
+        assert_eq!(when("wednesday at 10").0, at("2026-10-14 10:00"));
+        assert_eq!(when("oct 20 14:00").0, at("2026-10-20 14:00"));
+        // Dates already past this year are next year's
+        assert_eq!(when("3 march").0, at("2027-03-03 00:00"));
+        assert_eq!(when("in 2 hours").0, at("2026-10-14 11:00"));
+    }
+
+    #[test]
+    fn all_day_events() {
+        assert_eq!(
+            when("tomorrow"),
+            (at("2026-10-15 00:00"), Some(at("2026-10-16 00:00")), true)
+        );
+        assert_eq!(
+            when("oct 20 through oct 22"),
+            (at("2026-10-20 00:00"), Some(at("2026-10-23 00:00")), true)
+        );
+        assert_eq!(when("tomorrow for 2d").1, Some(at("2026-10-17 00:00")));
+    }
+
+    #[test]
+    fn recurrences() {
+        let when = parse_when("every other monday at 10", now(), ZONE).unwrap();
+        let recurrence = when.recurrence.unwrap();
+        assert_eq!(recurrence.frequency, RecurrenceFrequency::Weekly);
+        assert_eq!(recurrence.interval, 2);
+        assert_eq!(recurrence.week_day, Some(Weekday::Mon));
+        assert_eq!(wall_time(when.start, ZONE), at("2026-10-19 10:00"));
+
+        let when = parse_when("weekdays 8:00", now(), ZONE).unwrap();
+        assert_eq!(when.recurrence.unwrap().week_days.len(), 5);
+        assert_eq!(wall_time(when.start, ZONE), at("2026-10-14 08:00"));
+        let when = parse_when("sat,sun 10:00", now(), ZONE).unwrap();
+        let recurrence = when.recurrence.unwrap();
+        assert_eq!(recurrence.week_days, [Weekday::Sat, Weekday::Sun]);
+        assert_eq!(wall_time(when.start, ZONE), at("2026-10-17 10:00"));
+
+        let when = parse_when("every 3 months", now(), ZONE).unwrap();
+        let recurrence = when.recurrence.unwrap();
+        assert_eq!(recurrence.frequency, RecurrenceFrequency::Monthly);
+        assert_eq!(recurrence.interval, 3);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_read() {
+        let error = |input: &str| parse_when(input, now(), ZONE).unwrap_err();
+        assert_eq!(error(""), "no date or time given");
+        assert_eq!(
+            error("tomorrow blah"),
+            "cannot understand 'blah' in 'tomorrow blah'"
+        );
+        assert_eq!(
+            error("today tomorrow"),
+            "more than one date given at 'tomorrow'"
+        );
+        assert_eq!(
+            error("in 2 hours tomorrow"),
+            "'in ...' cannot be combined with a date or time"
+        );
+        assert_eq!(
+            error("3pm for 1h until 5pm"),
+            "give either a duration or an end, not both"
+        );
+        assert_eq!(
+            error("oct 20 through 2026-10-18"),
+            "the last day 2026-10-18 is before the first day"
+        );
+    }
+
+    #[test]
+    fn rejects_lengths_no_date_can_take() {
+        let error = |input: &str| parse_when(input, now(), ZONE).unwrap_err();
+        assert_eq!(
+            error("in 99999999999 weeks"),
+            "99999999999 weeks is too long"
+        );
+        assert_eq!(
+            error("in 9223372036854775807 minutes"),
+            "9223372036854775807 minutes is too long"
+        );
+        assert_eq!(
+            error("tomorrow 3pm for 99999999 days"),
+            "99999999 days is too long"
+        );
+        assert_eq!(
+            error("tomorrow 3pm 99999999w"),
+            "cannot understand '99999999w' in 'tomorrow 3pm 99999999w'"
+        );
+        assert_eq!(error("in 300000 weeks"), "300000 weeks is too long");
+        assert_eq!(
+            error("every 400000000 years"),
+            "every 400000000 is too far apart, at most every 1000 is possible"
+        );
+    }
+
+    #[test]
+    fn reminders() {
+        assert_eq!(parse_reminder("15").unwrap().notify_before, 15);
+        assert_eq!(parse_reminder("1h30m").unwrap().notify_before, 90);
+        let reminder = |input: &str| {
+            let day = parse_reminder(input).unwrap().on_day.unwrap();
+            (day.days_before, day.time)
+        };
+        let six_pm = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        assert_eq!(reminder("the day before at 18:00"), (1, six_pm));
+        assert_eq!(reminder("2 days before at 6pm"), (2, six_pm));
+        assert_eq!(reminder("on the day at 18:00"), (0, six_pm));
+        assert_eq!(reminder("1d@18:00"), (1, six_pm));
+        assert!(parse_reminder("soonish").is_err());
+        assert!(parse_reminder("1h@18:00").is_err());
+    }
+
+    #[test]
+    fn rejects_reminders_too_early() {
+        let error = |input: &str| parse_reminder(input).unwrap_err();
+        assert_eq!(
+            error("999999999999"),
+            "the reminder '999999999999' is too early"
+        );
+        assert_eq!(
+            error("999999999 days before at 9:00"),
+            "the reminder '999999999 days before at 9:00' is too early"
+        );
+        assert!(error("9999999999w").contains("cannot understand"));
+    }
+}
@@ -1,13 +1,15 @@
 use notify_rust::Notification;
 use std::process::Command;
 
-pub fn send_notification(title: &str, message: &str) {
+use crate::events::event::Priority;
+
+pub fn send_notification(title: &str, message: &str, priority: Priority) {
     if is_wsl() {
         println!("Sending WSL notification");
-        send_wsl_notification(title, message);
+        send_wsl_notification(title, message, priority);
     } else {
         println!("Sending generic notification");
-        send_generic_notification(title, message);
+        send_generic_notification(title, message, priority);
     }
 }
 
@@ -23,18 +25,31 @@ fn is_wsl() -> bool {
     false
 }
 
-fn send_generic_notification(title: &str, message: &str) {
-    Notification::new()
-        .summary(title)
-        .body(message)
-        .show()
-        .unwrap();
+fn send_generic_notification(title: &str, message: &str, priority: Priority) {
+    let mut notification = Notification::new();
+    notification.summary(title).body(message);
+    // Urgent events stay on screen until dismissed, only freedesktop servers know urgency
+    #[cfg(all(unix, not(target_os = "macos")))]
+    notification.urgency(match priority {
+        Priority::Low => notify_rust::Urgency::Low,
+        Priority::Normal | Priority::High => notify_rust::Urgency::Normal,
+        Priority::Urgent => notify_rust::Urgency::Critical,
+    });
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = priority;
+    notification.show().unwrap();
 }
 
-fn send_wsl_notification(title: &str, message: &str) {
+fn send_wsl_notification(title: &str, message: &str, priority: Priority) {
+    // -Urgent shows the toast even with focus assist turned on
+    let urgent = if priority == Priority::Urgent {
+        " -Urgent"
+    } else {
+        ""
+    };
     let powershell_command = format!(
-        "powershell.exe -Command \"Import-Module BurntToast; New-BurntToastNotification -Text '{}', '{}'\"",
-        title, message
+        "powershell.exe -Command \"Import-Module BurntToast; New-BurntToastNotification -Text '{}', '{}'{}\"",
+        title, message, urgent
     );
 
     Command::new("sh")
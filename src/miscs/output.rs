@@ -0,0 +1,727 @@
+use chrono::{DateTime, Local, Offset};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+use crate::events::category::Category;
+use crate::events::conflicts::{transparency, Booking, Conflict};
+use crate::events::contact::Contact;
+use crate::events::event::{Event, ParticipationStatus, Priority, Transparency};
+use crate::miscs::render::{
+    answers, category_color, category_label, pad, write_agenda, write_table, Style, BOLD,
+};
+use crate::miscs::utils::duration_to_string;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Table, // Aligned columns for humans
+    Agenda, // Events grouped by day
+    Json,   // Pretty printed JSON array (object for a single event)
+    Jsonl,  // One compact JSON object per line
+    Csv,    // RFC 4180 CSV with a header row
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json, // The events as saved in dates.json
+    Ics,  // iCalendar, for other calendar programs
+}
+
+const CSV_HEADER: [&str; 14] = [
+    "index",
+    "event_id",
+    "title",
+    "start_time",
+    "end_time",
+    "is_all_day",
+    "location",
+    "categories",
+    "description",
+    "tags",
+    "priority",
+    "attendees",
+    "accepted",
+    "declined",
+];
+
+// Prints events to stdout, `index` being the 0-based position in the event list.
+// A closed pipe (e.g. `| head`) is not an error for the caller.
+pub fn print_events(events: &[(usize, &Event)], format: OutputFormat) -> io::Result<()> {
+    ignore_broken_pipe(write_events(&mut io::stdout().lock(), events, format))
+}
+
+pub fn print_event(index: usize, event: &Event, format: OutputFormat) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let result = match format {
+        OutputFormat::Table => write_event_details(&mut out, index, event),
+        OutputFormat::Json => serde_json::to_string_pretty(event)
+            .map_err(io::Error::other)
+            .and_then(|json| writeln!(out, "{}", json)),
+        _ => write_events(&mut out, &[(index, event)], format),
+    };
+    ignore_broken_pipe(result)
+}
+
+fn ignore_broken_pipe(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other,
+    }
+}
+
+pub fn write_events(
+    out: &mut impl Write,
+    events: &[(usize, &Event)],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => write_table(out, events, &Style::detect()),
+        OutputFormat::Agenda => write_agenda(out, events, &Style::detect()),
+        OutputFormat::Json => {
+            let list: Vec<&Event> = events.iter().map(|(_, event)| *event).collect();
+            let json = serde_json::to_string_pretty(&list).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Jsonl => {
+            for (_, event) in events {
+                let json = serde_json::to_string(event).map_err(io::Error::other)?;
+                writeln!(out, "{}", json)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER.join(","))?;
+            for (index, event) in events {
+                let row = [
+                    (index + 1).to_string(),
+                    event.event_id.clone(),
+                    event.title.clone(),
+                    event.start_time.to_rfc3339(),
+                    event.end_time.to_rfc3339(),
+                    event.is_all_day.to_string(),
+                    event.location.clone(),
+                    event.categories.join(";"),
+                    event.description.clone(),
+                    event.tags.join(";"),
+                    event.priority.to_string(),
+                    event.attendees.len().to_string(),
+                    event
+                        .count_answers(ParticipationStatus::Accepted)
+                        .to_string(),
+                    event
+                        .count_answers(ParticipationStatus::Declined)
+                        .to_string(),
+                ];
+                let row: Vec<String> = row.iter().map(|field| csv_escape(field)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// "2026-10-20 09:00-09:30", with the end date as well if it ends on another day
+pub fn span_text(start: DateTime<Local>, end: DateTime<Local>) -> String {
+    let end_format = if end.date_naive() == start.date_naive() {
+        "%H:%M"
+    } else {
+        "%Y-%m-%d %H:%M"
+    };
+    format!(
+        "{}-{}",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format(end_format)
+    )
+}
+
+// A free slot as written to JSON and CSV
+#[derive(Serialize)]
+struct SlotRecord {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    minutes: i64,
+}
+
+// Free slots, one line per slot with its weekday and length
+pub fn print_free_slots(
+    slots: &[(DateTime<Local>, DateTime<Local>)],
+    format: OutputFormat,
+) -> io::Result<()> {
+    ignore_broken_pipe(write_free_slots(&mut io::stdout().lock(), slots, format))
+}
+
+fn write_free_slots(
+    out: &mut impl Write,
+    slots: &[(DateTime<Local>, DateTime<Local>)],
+    format: OutputFormat,
+) -> io::Result<()> {
+    let records: Vec<SlotRecord> = slots
+        .iter()
+        .map(|(start, end)| SlotRecord {
+            start: *start,
+            end: *end,
+            minutes: (*end - *start).num_minutes(),
+        })
+        .collect();
+    match format {
+        OutputFormat::Table | OutputFormat::Agenda => {
+            if records.is_empty() {
+                return writeln!(out, "No free slots.");
+            }
+            let style = Style::detect();
+            for record in &records {
+                writeln!(
+                    out,
+                    "{} {}  {}",
+                    record.start.format("%a"),
+                    style.paint(BOLD, &span_text(record.start, record.end)),
+                    duration_to_string(&(record.end - record.start))
+                )?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&records).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                let json = serde_json::to_string(record).map_err(io::Error::other)?;
+                writeln!(out, "{}", json)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "start,end,minutes")?;
+            for record in &records {
+                writeln!(
+                    out,
+                    "{},{},{}",
+                    record.start.to_rfc3339(),
+                    record.end.to_rfc3339(),
+                    record.minutes
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// A conflict as written to JSON and CSV, with 1-based indices as shown by `list`
+#[derive(Serialize)]
+struct ConflictRecord<'a> {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    minutes: i64,
+    events: [BookingRecord<'a>; 2],
+}
+
+#[derive(Serialize)]
+struct BookingRecord<'a> {
+    index: usize,
+    event_id: &'a str,
+    title: &'a str,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+}
+
+impl<'a> BookingRecord<'a> {
+    fn new(events: &'a [Event], booking: &Booking) -> Self {
+        let event = &events[booking.index];
+        Self {
+            index: booking.index + 1,
+            event_id: &event.event_id,
+            title: &event.title,
+            start_time: booking.start,
+            end_time: booking.end,
+        }
+    }
+}
+
+// Overlapping events, one line per pair: when they overlap, then both occurrences
+pub fn print_conflicts(
+    events: &[Event],
+    conflicts: &[Conflict],
+    format: OutputFormat,
+) -> io::Result<()> {
+    ignore_broken_pipe(write_conflicts(
+        &mut io::stdout().lock(),
+        events,
+        conflicts,
+        format,
+    ))
+}
+
+fn write_conflicts(
+    out: &mut impl Write,
+    events: &[Event],
+    conflicts: &[Conflict],
+    format: OutputFormat,
+) -> io::Result<()> {
+    let records: Vec<ConflictRecord> = conflicts
+        .iter()
+        .map(|conflict| ConflictRecord {
+            start: conflict.start(),
+            end: conflict.end(),
+            minutes: conflict.overlap().num_minutes(),
+            events: [
+                BookingRecord::new(events, &conflict.first),
+                BookingRecord::new(events, &conflict.second),
+            ],
+        })
+        .collect();
+    match format {
+        OutputFormat::Table | OutputFormat::Agenda => {
+            if records.is_empty() {
+                return writeln!(out, "No conflicts.");
+            }
+            let style = Style::detect();
+            for record in &records {
+                let [first, second] = &record.events;
+                let booking = |booking: &BookingRecord| {
+                    format!(
+                        "{} {} ({})",
+                        booking.event_id,
+                        booking.title,
+                        span_text(booking.start_time, booking.end_time)
+                    )
+                };
+                writeln!(
+                    out,
+                    "{}  {} and {}",
+                    style.paint(BOLD, &span_text(record.start, record.end)),
+                    booking(first),
+                    booking(second)
+                )?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&records).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                let json = serde_json::to_string(record).map_err(io::Error::other)?;
+                writeln!(out, "{}", json)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            writeln!(
+                out,
+                "start,end,minutes,first_index,first_event_id,first_title,first_start_time,first_end_time,second_index,second_event_id,second_title,second_start_time,second_end_time"
+            )?;
+            for record in &records {
+                let mut row = vec![
+                    record.start.to_rfc3339(),
+                    record.end.to_rfc3339(),
+                    record.minutes.to_string(),
+                ];
+                for booking in &record.events {
+                    row.extend([
+                        booking.index.to_string(),
+                        booking.event_id.to_string(),
+                        booking.title.to_string(),
+                        booking.start_time.to_rfc3339(),
+                        booking.end_time.to_rfc3339(),
+                    ]);
+                }
+                let row: Vec<String> = row.iter().map(|field| csv_escape(field)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Categories as a tree with their own settings, or as JSON/CSV records
+pub fn print_categories(categories: &[Category], format: OutputFormat) -> io::Result<()> {
+    let mut sorted: Vec<&Category> = categories.iter().collect();
+    sorted.sort_by(|a, b| a.name.split('/').cmp(b.name.split('/')));
+    ignore_broken_pipe(write_categories(&mut io::stdout().lock(), &sorted, format))
+}
+
+fn write_categories(
+    out: &mut impl Write,
+    categories: &[&Category],
+    format: OutputFormat,
+) -> io::Result<()> {
+    let reminders = |category: &Category| -> Vec<String> {
+        category
+            .notification_settings
+            .iter()
+            .map(|notification| match notification.on_day {
+                Some(on_day) => on_day.to_string(),
+                None => format!("{}m before", notification.notify_before),
+            })
+            .collect()
+    };
+    match format {
+        OutputFormat::Table | OutputFormat::Agenda => {
+            let style = Style::detect();
+            writeln!(out, "Categories:")?;
+            for category in categories {
+                let mut name = category.leaf().to_string();
+                if let Some(icon) = category.icon.as_ref().filter(|icon| !icon.is_empty()) {
+                    name = format!("{} {}", icon, name);
+                }
+                let mut settings = vec![];
+                if let Some(color) = &category.color {
+                    settings.push(format!("colour: {}", color));
+                }
+                if let Some(duration) = &category.duration {
+                    settings.push(format!("duration: {}", duration));
+                }
+                if !category.notification_settings.is_empty() {
+                    settings.push(format!("reminders: {}", reminders(category).join(", ")));
+                }
+                if let Some(transparency) = category.transparency {
+                    settings.push(transparency.to_string());
+                }
+                let mut line = format!(
+                    "\t{}{}",
+                    "  ".repeat(category.depth()),
+                    style.paint(category_color(&category.name), &name)
+                );
+                if !settings.is_empty() {
+                    line += &format!(" ({})", settings.join(", "));
+                }
+                writeln!(out, "{}", line)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(categories).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Jsonl => {
+            for category in categories {
+                let json = serde_json::to_string(category).map_err(io::Error::other)?;
+                writeln!(out, "{}", json)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "name,color,icon,duration,reminders,transparency")?;
+            for category in categories {
+                let row = [
+                    category.name.clone(),
+                    category.color.clone().unwrap_or_default(),
+                    category.icon.clone().unwrap_or_default(),
+                    category.duration.clone().unwrap_or_default(),
+                    reminders(category).join(";"),
+                    category
+                        .transparency
+                        .map(|transparency| transparency.to_string())
+                        .unwrap_or_default(),
+                ];
+                let row: Vec<String> = row.iter().map(|field| csv_escape(field)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn print_contacts(contacts: &[Contact], format: OutputFormat) -> io::Result<()> {
+    ignore_broken_pipe(write_contacts(&mut io::stdout().lock(), contacts, format))
+}
+
+fn write_contacts(
+    out: &mut impl Write,
+    contacts: &[Contact],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table | OutputFormat::Agenda => {
+            if contacts.is_empty() {
+                return writeln!(out, "No contacts.");
+            }
+            let style = Style::detect();
+            let rows: Vec<[String; 5]> = contacts
+                .iter()
+                .map(|contact| {
+                    [
+                        contact.contact_id.clone(),
+                        contact.name.clone(),
+                        contact.emails.join(", "),
+                        contact.phone.clone(),
+                        contact.notes.lines().next().unwrap_or("").to_string(),
+                    ]
+                })
+                .collect();
+            let header = ["ID", "Name", "Email", "Phone", "Notes"];
+            let widths: Vec<usize> = (0..header.len())
+                .map(|column| {
+                    rows.iter()
+                        .map(|row| UnicodeWidthStr::width(row[column].as_str()))
+                        .chain([header[column].len()])
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            let line = |cells: [&str; 5]| {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| pad(cell, *width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            writeln!(out, "{}", style.paint(BOLD, &line(header)))?;
+            for row in &rows {
+                writeln!(out, "{}", line(row.each_ref().map(String::as_str)))?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(contacts).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)
+        }
+        OutputFormat::Jsonl => {
+            for contact in contacts {
+                let json = serde_json::to_string(contact).map_err(io::Error::other)?;
+                writeln!(out, "{}", json)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "contact_id,name,emails,phone,notes")?;
+            for contact in contacts {
+                let row = [
+                    contact.contact_id.clone(),
+                    contact.name.clone(),
+                    contact.emails.join(";"),
+                    contact.phone.clone(),
+                    contact.notes.clone(),
+                ];
+                let row: Vec<String> = row.iter().map(|field| csv_escape(field)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn write_event_details(out: &mut impl Write, index: usize, event: &Event) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} (ID {}, index {})",
+        event.title,
+        event.event_id,
+        index + 1
+    )?;
+    if event.is_all_day {
+        let (first, last) = event.all_day_dates();
+        let days = (last - first).num_days() + 1;
+        if days == 1 {
+            writeln!(
+                out,
+                "  When:          {} (all day)",
+                first.format("%Y-%m-%d")
+            )?;
+        } else {
+            writeln!(
+                out,
+                "  When:          {} - {} (all day, {} days)",
+                first.format("%Y-%m-%d"),
+                last.format("%Y-%m-%d"),
+                days
+            )?;
+        }
+    } else {
+        writeln!(
+            out,
+            "  When:          {} - {}",
+            event.start_time.format("%Y-%m-%d %H:%M"),
+            event.end_time.format("%Y-%m-%d %H:%M")
+        )?;
+    }
+    if let Some(zone) = event.time_zone.filter(|_| !event.is_all_day) {
+        // Remote events also show the times on the clock where they take place
+        let start = event.start_time.with_timezone(&zone);
+        let end = event.end_time.with_timezone(&zone);
+        if start.offset().fix() == event.start_time.offset().fix() {
+            writeln!(out, "  Time zone:     {}", zone)?;
+        } else {
+            let end_format = if end.date_naive() == start.date_naive() {
+                "%H:%M"
+            } else {
+                "%Y-%m-%d %H:%M"
+            };
+            writeln!(
+                out,
+                "  Time zone:     {} ({} - {} there)",
+                zone,
+                start.format("%Y-%m-%d %H:%M"),
+                end.format(end_format)
+            )?;
+        }
+    }
+    if !event.location.is_empty() {
+        writeln!(out, "  Location:      {}", event.location)?;
+    }
+    if !event.description.is_empty() {
+        writeln!(out, "  Description:   {}", event.description)?;
+    }
+    if !event.categories.is_empty() {
+        let labels: Vec<String> = event
+            .categories
+            .iter()
+            .map(|category| category_label(category))
+            .collect();
+        writeln!(out, "  Categories:    {}", labels.join(", "))?;
+    }
+    if !event.tags.is_empty() {
+        writeln!(out, "  Tags:          {}", event.tags.join(", "))?;
+    }
+    if event.priority != Priority::Normal {
+        writeln!(out, "  Priority:      {}", event.priority)?;
+    }
+    if transparency(event) == Transparency::Free {
+        writeln!(out, "  Shows as:      free")?;
+    }
+    if event.is_recurring {
+        if let Some(recurrence) = &event.recurrence {
+            write!(
+                out,
+                "  Repeats:       {:?}, every {}",
+                recurrence.frequency, recurrence.interval
+            )?;
+            if recurrence.week_days.is_empty() {
+                writeln!(out)?;
+            } else {
+                let days: Vec<String> = recurrence
+                    .week_days
+                    .iter()
+                    .map(|day| day.to_string())
+                    .collect();
+                writeln!(out, " on {}", days.join(", "))?;
+            }
+        }
+    }
+    if let Some(organizer) = &event.organizer {
+        writeln!(
+            out,
+            "  Organizer:     {} <{}>",
+            organizer.name, organizer.email
+        )?;
+    }
+    for attendee in &event.attendees {
+        writeln!(out, "  Attendee:      {}", attendee)?;
+    }
+    if let Some(answers) = answers(event) {
+        writeln!(out, "  Answers:       {}", answers)?;
+    }
+    for notification in &event.notification_settings {
+        writeln!(out, "  Notification:  {}", notification)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(events: &[(usize, &Event)], format: OutputFormat) -> String {
+        let mut out = Vec::new();
+        write_events(&mut out, events, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn events() -> [Event; 2] {
+        let lunch = Event {
+            event_id: "#1".to_string(),
+            title: "Lunch, with \"Bob\"".to_string(),
+            description: "two\nlines".to_string(),
+            categories: vec!["food".to_string(), "work".to_string()],
+            tags: vec!["team".to_string()],
+            priority: Priority::High,
+            ..Default::default()
+        };
+        let call = Event {
+            event_id: "#2".to_string(),
+            title: "Call".to_string(),
+            ..Default::default()
+        };
+        [lunch, call]
+    }
+
+    #[test]
+    fn csv_quotes_only_where_needed() {
+        let [lunch, call] = events();
+        let csv = render(&[(0, &lunch), (4, &call)], OutputFormat::Csv);
+        let (header, rows) = csv.split_once('\n').unwrap();
+        assert_eq!(header, CSV_HEADER.join(","));
+        assert!(rows.starts_with("1,#1,\"Lunch, with \"\"Bob\"\"\","));
+        assert!(rows.contains(",food;work,\"two\nlines\",team,high,0,0,0\n5,#2,Call,"));
+        assert!(rows.ends_with(",,normal,0,0,0\n"));
+        assert_eq!(csv_escape("plain"), "plain");
+    }
+
+    #[test]
+    fn json_and_jsonl_read_back() {
+        let [lunch, call] = events();
+        let list = [(0, &lunch), (1, &call)];
+
+        let json: Vec<Event> = serde_json::from_str(&render(&list, OutputFormat::Json)).unwrap();
+        let titles: Vec<_> = json.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, [lunch.title.as_str(), "Call"]);
+
+        let jsonl = render(&list, OutputFormat::Jsonl);
+        let lines: Vec<Event> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].description, "two\nlines");
+        assert_eq!(lines[1].event_id, "#2");
+    }
+
+    #[test]
+    fn lists_categories() {
+        let parent = Category {
+            color: Some("red".to_string()),
+            ..Category::new("Work")
+        };
+        let child = Category {
+            duration: Some("30m".to_string()),
+            transparency: Some(Transparency::Free),
+            notification_settings: vec![crate::events::event::Notification {
+                notify_before: 15,
+                ..Default::default()
+            }],
+            ..Category::new("Work/a, b")
+        };
+        let categories = [&parent, &child];
+        let write = |format| {
+            let mut out = Vec::new();
+            write_categories(&mut out, &categories, format).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            write(OutputFormat::Csv),
+            "name,color,icon,duration,reminders,transparency\nWork,red,,,,\n\"Work/a, b\",,,30m,15m before,free\n"
+        );
+        let lines: Vec<Category> = write(OutputFormat::Jsonl)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[1].name, "Work/a, b");
+        assert_eq!(lines[1].notification_settings[0].notify_before, 15);
+        assert_eq!(render(&[], OutputFormat::Table), "No events.\n");
+    }
+}
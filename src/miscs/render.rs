@@ -0,0 +1,541 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::io::{self, IsTerminal, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::events::category::inherited;
+use crate::events::event::{Event, ParticipationStatus, Priority};
+
+pub const RESET: &str = "\x1b[0m";
+pub const DIM: &str = "\x1b[2m";
+pub const BOLD: &str = "\x1b[1m";
+pub const RED: &str = "\x1b[31m";
+pub const REVERSE: &str = "\x1b[7m";
+// Colours that can be given to a category
+pub const COLOR_NAMES: [(&str, &str); 14] = [
+    ("red", "\x1b[31m"),
+    ("green", "\x1b[32m"),
+    ("yellow", "\x1b[33m"),
+    ("blue", "\x1b[34m"),
+    ("magenta", "\x1b[35m"),
+    ("cyan", "\x1b[36m"),
+    ("gray", "\x1b[90m"),
+    ("bright-red", "\x1b[91m"),
+    ("bright-green", "\x1b[92m"),
+    ("bright-yellow", "\x1b[93m"),
+    ("bright-blue", "\x1b[94m"),
+    ("bright-magenta", "\x1b[95m"),
+    ("bright-cyan", "\x1b[96m"),
+    ("white", "\x1b[97m"),
+];
+const CATEGORY_COLORS: [&str; 6] = [
+    "\x1b[32m", // green
+    "\x1b[33m", // yellow
+    "\x1b[34m", // blue
+    "\x1b[35m", // magenta
+    "\x1b[36m", // cyan
+    "\x1b[91m", // bright red
+];
+
+// Terminal capabilities of stdout, detected once per rendering
+pub struct Style {
+    pub color: bool,
+    pub width: Option<usize>, // None when not writing to a terminal, nothing gets truncated
+}
+
+impl Style {
+    pub fn detect() -> Self {
+        let is_terminal = io::stdout().is_terminal();
+        Self {
+            color: is_terminal && std::env::var_os("NO_COLOR").is_none(),
+            width: if is_terminal {
+                terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn paint(&self, code: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+pub fn color_code(name: &str) -> Option<&'static str> {
+    COLOR_NAMES
+        .iter()
+        .find(|(color, _)| color.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+}
+
+// The category's colour, else a stable one per top-level category, so "Work" and
+// "Work/Project-X" look the same in every listing
+pub fn category_color(category: &str) -> &'static str {
+    if let Some(code) = inherited(category, |known| {
+        known.color.as_deref().and_then(color_code)
+    }) {
+        return code;
+    }
+    let root = category.split('/').next().unwrap_or(category);
+    let hash = root.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    CATEGORY_COLORS[hash % CATEGORY_COLORS.len()]
+}
+
+// The category name with its icon in front, if it or a parent has one
+pub fn category_label(category: &str) -> String {
+    match inherited(category, |known| known.icon.clone()) {
+        Some(icon) if !icon.is_empty() => format!("{} {}", icon, category),
+        _ => category.to_string(),
+    }
+}
+
+// The title with "!" in front for high and "!!" for urgent events
+pub fn marked_title(event: &Event) -> String {
+    match event.priority {
+        Priority::High => format!("! {}", event.title),
+        Priority::Urgent => format!("!! {}", event.title),
+        Priority::Low | Priority::Normal => event.title.clone(),
+    }
+}
+
+// Who is coming, e.g. "2/4 accepted, 1 declined", None without attendees
+pub fn answers(event: &Event) -> Option<String> {
+    if event.attendees.is_empty() {
+        return None;
+    }
+    let mut text = format!(
+        "{}/{} accepted",
+        event.count_answers(ParticipationStatus::Accepted),
+        event.attendees.len()
+    );
+    for status in [
+        ParticipationStatus::Declined,
+        ParticipationStatus::Tentative,
+    ] {
+        let count = event.count_answers(status);
+        if count > 0 {
+            text += &format!(", {} {}", count, status);
+        }
+    }
+    Some(text)
+}
+
+// The title as listed, with the answers of the attendees after it
+fn listed_title(event: &Event) -> String {
+    match answers(event) {
+        Some(answers) => format!("{} ({})", marked_title(event), answers),
+        None => marked_title(event),
+    }
+}
+
+// Category labels in their colour followed by the tags as "+tag"
+fn labels(event: &Event) -> Vec<(String, Option<&'static str>)> {
+    let categories = event
+        .categories
+        .iter()
+        .map(|category| (category_label(category), Some(category_color(category))));
+    let tags = event.tags.iter().map(|tag| (format!("+{}", tag), None));
+    categories.chain(tags).collect()
+}
+
+// Cuts `text` to at most `width` columns, marking the cut with an ellipsis
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut result = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width - 1 {
+            break;
+        }
+        used += char_width;
+        result.push(c);
+    }
+    result.push('…');
+    result
+}
+
+// Pads with spaces up to `width` display columns (format! pads by chars, not columns)
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+pub fn time_range(event: &Event) -> String {
+    if event.is_all_day {
+        let (first, last) = event.all_day_dates();
+        if first == last {
+            "all day".to_string()
+        } else {
+            format!("all day to {}", last.format("%m-%d"))
+        }
+    } else if event.start_time.date_naive() == event.end_time.date_naive() {
+        format!(
+            "{}-{}",
+            event.start_time.format("%H:%M"),
+            event.end_time.format("%H:%M")
+        )
+    } else {
+        format!(
+            "{}-{}",
+            event.start_time.format("%H:%M"),
+            event.end_time.format("%m-%d %H:%M")
+        )
+    }
+}
+
+pub fn relative_day(date: NaiveDate, today: NaiveDate) -> String {
+    match (date - today).num_days() {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        -1 => "Yesterday".to_string(),
+        days if days > 1 => format!("in {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
+
+fn is_overdue(event: &Event, now: DateTime<Local>) -> bool {
+    event.end_time < now
+}
+
+fn categories_text(style: &Style, event: &Event, width: Option<usize>) -> String {
+    let mut used = 0;
+    let mut parts = vec![];
+    let paint = |color: Option<&str>, text: &str| match color {
+        Some(color) => style.paint(color, text),
+        None => text.to_string(),
+    };
+    for (label, color) in labels(event) {
+        let separator = if parts.is_empty() { 0 } else { 2 };
+        let mut text = label.clone();
+        if let Some(width) = width {
+            if used + separator + label.width() > width {
+                text = truncate(&label, width.saturating_sub(used + separator));
+                if !text.is_empty() {
+                    parts.push(paint(color, &text));
+                }
+                used = width;
+                break;
+            }
+        }
+        used += separator + text.width();
+        parts.push(paint(color, &text));
+    }
+    let joined = parts.join(", ");
+    match width {
+        // painted text contains escape codes, so pad by the visible width
+        Some(width) => format!("{}{}", joined, " ".repeat(width.saturating_sub(used))),
+        None => joined,
+    }
+}
+
+// Compact table: index, ID, date, time range, title, location, categories and tags
+pub fn write_table(
+    out: &mut impl Write,
+    events: &[(usize, &Event)],
+    style: &Style,
+) -> io::Result<()> {
+    if events.is_empty() {
+        return writeln!(out, "No events.");
+    }
+    let now = Local::now();
+
+    let index_width = events
+        .iter()
+        .map(|(index, _)| (index + 1).to_string().len())
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let id_width = events
+        .iter()
+        .map(|(_, event)| event.event_id.width())
+        .max()
+        .unwrap_or(2)
+        .max(2);
+    let time_width = events
+        .iter()
+        .map(|(_, event)| time_range(event).width())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+    let fixed = index_width + id_width + 10 + time_width + 4 * 2;
+
+    // Title, location and categories share what is left of the terminal width
+    let (title_width, location_width, category_width) = match style.width {
+        Some(width) => {
+            let flexible = width.saturating_sub(fixed + 2 * 2).max(30);
+            let location_width = events
+                .iter()
+                .map(|(_, event)| event.location.width())
+                .max()
+                .unwrap_or(0)
+                .min(flexible / 4)
+                .max(8);
+            let category_width = (flexible / 4).max(10);
+            let title_width = flexible.saturating_sub(location_width + category_width);
+            (
+                Some(title_width),
+                Some(location_width),
+                Some(category_width),
+            )
+        }
+        None => (None, None, None),
+    };
+    let title_column = title_width.unwrap_or_else(|| {
+        events
+            .iter()
+            .map(|(_, event)| listed_title(event).width())
+            .max()
+            .unwrap_or(5)
+            .max(5)
+    });
+    let location_column = location_width.unwrap_or_else(|| {
+        events
+            .iter()
+            .map(|(_, event)| event.location.width())
+            .max()
+            .unwrap_or(8)
+            .max(8)
+    });
+
+    let header = format!(
+        "{:>index_width$}  {}  {}  {}  {}  {}  Categories",
+        "#",
+        pad("ID", id_width),
+        pad("Date", 10),
+        pad("Time", time_width),
+        pad("Title", title_column),
+        pad("Location", location_column),
+    );
+    writeln!(out, "{}", style.paint(BOLD, header.trim_end()))?;
+
+    for (index, event) in events {
+        let title = truncate(&listed_title(event), title_column);
+        let location = truncate(&event.location, location_column);
+        let date = event.start_time.format("%Y-%m-%d").to_string();
+        let line = format!(
+            "{:>index_width$}  {}  {}  {}  {}  {}  ",
+            index + 1,
+            pad(&event.event_id, id_width),
+            date,
+            pad(&time_range(event), time_width),
+            pad(&title, title_column),
+            pad(&location, location_column),
+        );
+        let line = if event.categories.is_empty() && event.tags.is_empty() {
+            line.trim_end().to_string()
+        } else {
+            line
+        };
+        let line = if is_overdue(event, now) {
+            style.paint(DIM, &line)
+        } else {
+            line
+        };
+        let categories = categories_text(style, event, category_width);
+        writeln!(out, "{}", format!("{}{}", line, categories).trim_end())?;
+    }
+    Ok(())
+}
+
+// A day of the agenda, the event and which of its days it is, e.g. (2, 3) for "day 2/3"
+type AgendaEntry<'a> = (NaiveDate, &'a Event, Option<(i64, i64)>);
+
+// Agenda: events grouped by day with relative day names
+
+pub fn write_agenda(
+    out: &mut impl Write,
+    events: &[(usize, &Event)],
+    style: &Style,
+) -> io::Result<()> {
+    if events.is_empty() {
+        return writeln!(out, "No events.");
+    }
+    let now = Local::now();
+    let today = now.date_naive();
+
+    // Multi-day all-day events are listed on each of their days, all-day events come first
+    let mut entries: Vec<AgendaEntry> = vec![];
+    for (_, event) in events {
+        if event.is_all_day {
+            let (first, last) = event.all_day_dates();
+            let days = (last - first).num_days() + 1;
+            for i in 0..days {
+                let part = (days > 1).then_some((i + 1, days));
+                entries.push((first + chrono::Duration::days(i), event, part));
+            }
+        } else {
+            entries.push((event.start_time.date_naive(), event, None));
+        }
+    }
+    entries.sort_by_key(|(day, event, _)| (*day, !event.is_all_day, event.start_time));
+
+    let mut current_day: Option<NaiveDate> = None;
+    for (day, event, part) in entries {
+        if current_day != Some(day) {
+            if current_day.is_some() {
+                writeln!(out)?;
+            }
+            let heading = format!(
+                "{} ({})",
+                day.format("%a %Y-%m-%d"),
+                relative_day(day, today)
+            );
+            writeln!(out, "{}", style.paint(BOLD, &heading))?;
+            current_day = Some(day);
+        }
+
+        let time = match part {
+            Some(_) => "all day".to_string(),
+            None => time_range(event),
+        };
+        let prefix = format!("  {:<11}  ", time);
+        let mut details = listed_title(event);
+        if let Some((i, days)) = part {
+            details += &format!(" (day {}/{})", i, days);
+        }
+        if !event.location.is_empty() {
+            details += &format!(" @ {}", event.location);
+        }
+        details += &format!(" ({})", event.event_id);
+        let labels = labels(event);
+        let reserved = prefix.width()
+            + labels
+                .iter()
+                .map(|(label, _)| label.width() + 3)
+                .sum::<usize>();
+        if let Some(width) = style.width {
+            details = truncate(&details, width.saturating_sub(reserved).max(10));
+        }
+
+        let overdue = is_overdue(event, now);
+        let mut line = format!("{}{}", prefix, details);
+        if overdue {
+            line = style.paint(RED, &format!("{} (overdue)", line));
+        }
+        let categories: Vec<String> = labels
+            .iter()
+            .map(|(label, color)| match color {
+                Some(color) => style.paint(color, &format!("[{}]", label)),
+                None => format!("[{}]", label),
+            })
+            .collect();
+        if categories.is_empty() {
+            writeln!(out, "{}", line)?;
+        } else {
+            writeln!(out, "{} {}", line, categories.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miscs::utils::datetime_from_str;
+
+    const PLAIN: Style = Style {
+        color: false,
+        width: None,
+    };
+
+    fn event(id: &str, title: &str, start: &str, end: &str) -> Event {
+        Event {
+            event_id: id.to_string(),
+            title: title.to_string(),
+            start_time: datetime_from_str(start, None).unwrap(),
+            end_time: datetime_from_str(end, None).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    type Writer = fn(&mut Vec<u8>, &[(usize, &Event)], &Style) -> io::Result<()>;
+
+    fn render(write: Writer, events: &[(usize, &Event)], style: &Style) -> String {
+        let mut out = Vec::new();
+        write(&mut out, events, style).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn truncates_by_display_width() {
+        assert_eq!(truncate("Lunch", 5), "Lunch");
+        assert_eq!(truncate("Lunch break", 6), "Lunch…");
+        assert_eq!(truncate("日本語の会議", 5), "日本…");
+        assert_eq!(truncate("Lunch", 0), "");
+        assert_eq!(pad("日本", 6).width(), 6);
+    }
+
+    #[test]
+    fn names_days_and_time_ranges() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        assert_eq!(relative_day(day(18), today), "Today");
+        assert_eq!(relative_day(day(19), today), "Tomorrow");
+        assert_eq!(relative_day(day(17), today), "Yesterday");
+        assert_eq!(relative_day(day(25), today), "in 7 days");
+        assert_eq!(relative_day(day(15), today), "3 days ago");
+
+        let lunch = event("#1", "Lunch", "2026-10-20 12:00", "2026-10-20 13:00");
+        assert_eq!(time_range(&lunch), "12:00-13:00");
+        let night = event("#2", "Night", "2026-10-20 22:00", "2026-10-21 06:00");
+        assert_eq!(time_range(&night), "22:00-10-21 06:00");
+    }
+
+    #[test]
+    fn agenda_groups_by_day_in_order() {
+        let late = event("#1", "Late", "2026-10-21 18:00", "2026-10-21 19:00");
+        let early = event("#2", "Early", "2026-10-20 08:00", "2026-10-20 09:00");
+        let mut also_early = event("#3", "Also", "2026-10-20 10:00", "2026-10-20 11:00");
+        also_early.location = "Office".to_string();
+        also_early.categories = vec!["work".to_string()];
+        let agenda = render(
+            write_agenda,
+            &[(0, &late), (1, &early), (2, &also_early)],
+            &PLAIN,
+        );
+        let lines: Vec<_> = agenda.lines().collect();
+        assert!(lines[0].starts_with("Tue 2026-10-20 ("));
+        assert!(lines[1].contains("08:00-09:00  Early (#2)"));
+        assert!(lines[2].ends_with("Also @ Office (#3) [work]"));
+        assert_eq!(lines[3], "");
+        assert!(lines[4].starts_with("Wed 2026-10-21 ("));
+        assert!(lines[5].contains("Late (#1)"));
+    }
+
+    #[test]
+    fn table_fits_the_terminal() {
+        let mut lunch = event(
+            "#1",
+            "A very long title that does not fit in a narrow terminal at all",
+            "2026-10-20 12:00",
+            "2026-10-20 13:00",
+        );
+        lunch.categories = vec!["food".to_string()];
+        let narrow = Style {
+            color: false,
+            width: Some(60),
+        };
+        let table = render(write_table, &[(0, &lunch)], &narrow);
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].starts_with("#  ID  Date        Time         Title"));
+        assert!(lines[1].starts_with("1  #1  2026-10-20  12:00-13:00  A very long"));
+        assert!(lines[1].contains('…'));
+        assert!(lines[1].ends_with("food"));
+
+        // Without a terminal nothing is cut
+        let table = render(write_table, &[(0, &lunch)], &PLAIN);
+        assert!(table.contains(&lunch.title));
+        assert_eq!(render(write_table, &[], &PLAIN), "No events.\n");
+    }
+}
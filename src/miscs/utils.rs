@@ -1,4 +1,7 @@
-use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
+use chrono_tz::Tz;
 #[cfg(not(test))]
 use directories::BaseDirs;
 use regex::Regex;
@@ -7,6 +10,8 @@ use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
+use crate::miscs::natural::{parse_datetime, parse_day, parse_time};
+
 #[cfg(test)]
 pub fn get_path() -> std::option::Option<PathBuf> {
     let tmp_dir = std::env::temp_dir();
@@ -20,7 +25,7 @@ pub fn get_path() -> std::option::Option<PathBuf> {
     if let Some(base_dirs) = BaseDirs::new() {
         let data_base_dir = base_dirs.data_dir();
 
-        println!("Data Directory: {:?}", data_base_dir);
+        eprintln!("Data Directory: {:?}", data_base_dir);
 
         let data_dir = data_base_dir.join("RustyPlanner");
 
@@ -35,35 +40,124 @@ pub fn get_path() -> std::option::Option<PathBuf> {
     data_file_path
 }
 
-#[allow(dead_code)]
+// "45m", "2h" or "1h30m", leaving out the parts that are zero
 pub fn duration_to_string(duration: &Duration) -> String {
     let seconds = duration.num_seconds();
     let hours = seconds / 3600;
     let minutes = (seconds % 3600) / 60;
-    let _seconds = seconds % 60;
 
-    format!("{}h{}m", hours, minutes)
+    match (hours, minutes) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
 }
 
-pub fn date_from_str(date_str: &str) -> NaiveDate {
-    let formats = ["%Y-%m-%d", "%d-%m-%Y", "%d.%m.%Y", "%m/%d/%Y"];
-    for format in &formats {
-        if let Ok(date) = NaiveDate::parse_from_str(date_str, format) {
-            return date;
-        }
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%d-%m-%Y", "%d.%m.%Y", "%m/%d/%Y"];
+pub const TIME_FORMATS: [&str; 3] = ["%H:%M:%S", "%H:%M", "%I:%M %p"];
+
+// A date in one of DATE_FORMATS or a day like "tomorrow" or "next friday"
+pub fn date_from_str(date_str: &str) -> Result<NaiveDate, String> {
+    parse_day(date_str, Local::now().date_naive())
+        .ok_or_else(|| format!("cannot parse '{}' as a date", date_str.trim()))
+}
+
+pub fn try_date_from_str(date_str: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date_str.trim(), format).ok())
+}
+
+// A wall-clock time, turned into an instant together with a date by `local_from_naive`
+pub fn time_from_str(time_str: &str) -> Result<NaiveTime, String> {
+    parse_time(time_str.trim())
+        .ok_or_else(|| format!("cannot parse '{}' as a time", time_str.trim()))
+}
+
+// Parses "<date> <time>", "<date>T<time>", a date (midnight) or a time (today) in `zone`,
+// the local time zone if None
+pub fn datetime_from_str(input: &str, zone: Option<Tz>) -> Result<DateTime<Local>, String> {
+    let input = input.trim();
+    let parse_time = |s: &str| {
+        TIME_FORMATS
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(s, format).ok())
+    };
+
+    let naive = if let Some(date) = try_date_from_str(input) {
+        date.and_time(NaiveTime::MIN)
+    } else if let Some(time) = parse_time(input) {
+        wall_time(Local::now(), zone).date().and_time(time)
+    } else {
+        input
+            .split_once(['T', ' '])
+            .and_then(|(date, time)| {
+                Some(try_date_from_str(date)?.and_time(parse_time(time.trim())?))
+            })
+            .ok_or_else(|| format!("cannot parse '{}' as a date and time", input))?
+    };
+    zoned_from_naive(naive, zone)
+}
+
+pub fn local_from_naive(naive: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("{} does not exist in the local time zone", naive))
+}
+
+// A wall-clock time in the event's time zone, or in the local one for events without a zone.
+// The offset comes from that date, so times on the other side of a DST change stay put.
+pub fn zoned_from_naive(naive: NaiveDateTime, zone: Option<Tz>) -> Result<DateTime<Local>, String> {
+    match zone {
+        Some(zone) => zone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|time| time.with_timezone(&Local))
+            .ok_or_else(|| format!("{} does not exist in {}", naive, zone)),
+        None => local_from_naive(naive),
     }
-    Local::now().naive_utc().date()
 }
 
-pub fn time_from_str(time_str: &str) -> NaiveTime {
-    let formats = ["%H:%M:%S", "%H:%M", "%I:%M %p"];
-    for format in &formats {
-        if let Ok(time) = NaiveTime::parse_from_str(time_str, format) {
-            println!("{}", time.format("%H:%M:%S"));
-            return time - *Local::now().offset();
+// The wall-clock time of an instant in the event's time zone, or in the local one
+pub fn wall_time(time: DateTime<Local>, zone: Option<Tz>) -> NaiveDateTime {
+    match zone {
+        Some(zone) => time.with_timezone(&zone).naive_local(),
+        None => time.naive_local(),
+    }
+}
+
+// An IANA time zone like "Europe/Berlin", "local" or an empty string for the local time zone
+pub fn parse_time_zone(name: &str) -> Result<Option<Tz>, String> {
+    let name = name.trim();
+    if name.is_empty() || name.eq_ignore_ascii_case("local") {
+        return Ok(None);
+    }
+    name.parse::<Tz>().map(Some).map_err(|_| {
+        format!(
+            "unknown time zone '{}', expected an IANA name like Europe/Berlin or America/New_York",
+            name
+        )
+    })
+}
+
+// Splits an interactive command line like a shell would, except that `#` starts an
+// event ID (`edit #3`) instead of a comment. None if quotes are unbalanced.
+pub fn split_command_line(line: &str) -> Option<Vec<String>> {
+    let mut escaped = String::new();
+    let mut quote = None;
+    let mut previous = ' ';
+    for c in line.chars() {
+        match (quote, c) {
+            (None, '#') if previous.is_whitespace() => escaped.push('\\'),
+            (None, '\'' | '"') if previous != '\\' => quote = Some(c),
+            (Some(open), _) if c == open && (open == '\'' || previous != '\\') => quote = None,
+            _ => {}
         }
+        escaped.push(c);
+        previous = c;
     }
-    Local::now().naive_utc().time()
+    shlex::split(&escaped)
 }
 
 pub fn clear_screen() {
@@ -75,29 +169,228 @@ pub fn clear_screen() {
     io::stdout().flush().unwrap();
 }
 
+// About a thousand years, the longest duration accepted
+pub const MAX_DURATION_DAYS: i64 = 366_000;
+
+// Parses durations like "1h30m", "45m", "2d" or "1w2d", units are w, d, h and m
 pub fn parse_duration(s: &str) -> Result<Duration, String> {
     let trimmed = s.trim();
-    // println!("{}", trimmed);
 
-    // Regular expression to match hours and minutes
-    let re =
-        Regex::new(r"(?:(\d+)h)?(?:(\d+)m)?").map_err(|_| "Failed to compile regex".to_string())?;
+    // Regular expression to match weeks, days, hours and minutes
+    let re = Regex::new(r"^(?:(\d+)w)?(?:(\d+)d)?(?:(\d+)h)?(?:(\d+)m)?$")
+        .map_err(|_| "Failed to compile regex".to_string())?;
 
-    // Capture groups for hours and minutes
-    let caps = re.captures(trimmed).ok_or("Invalid format".to_string())?;
+    // Capture groups for each unit
+    let caps = re
+        .captures(trimmed)
+        .filter(|_| !trimmed.is_empty())
+        .ok_or(format!(
+            "invalid duration '{}', expected e.g. 1h30m",
+            trimmed
+        ))?;
 
-    //println!("Captured groups: {:?}", caps);
+    let unit = |i: usize, make: fn(i64) -> Option<Duration>| match caps.get(i) {
+        Some(m) => m.as_str().parse::<i64>().ok().and_then(make),
+        None => Some(Duration::zero()),
+    };
 
-    // Parse hours and minutes
-    let hours = caps
-        .get(1)
-        .and_then(|m| m.as_str().parse::<i64>().ok())
-        .unwrap_or(0);
-    let minutes = caps
-        .get(2)
-        .and_then(|m| m.as_str().parse::<i64>().ok())
-        .unwrap_or(0);
+    // Create a Duration from the parsed values, numbers too large for one are rejected
+    [
+        unit(1, Duration::try_weeks),
+        unit(2, Duration::try_days),
+        unit(3, Duration::try_hours),
+        unit(4, Duration::try_minutes),
+    ]
+    .into_iter()
+    .try_fold(Duration::zero(), |total, part| total.checked_add(&part?))
+    // Longer ones couldn't be added to a date anyway
+    .filter(|duration| duration.num_days() <= MAX_DURATION_DAYS)
+    .ok_or_else(|| "duration too large".to_string())
+}
+
+fn start_of_day(date: NaiveDate) -> Result<DateTime<Local>, String> {
+    local_from_naive(date.and_time(NaiveTime::MIN))
+}
 
-    // Create a Duration from the parsed values
-    Ok(Duration::hours(hours) + Duration::minutes(minutes))
+// Resolves a range like "today", "tomorrow", "week", "month", "next week" or "next 7d"
+// to a half-open interval [start, end)
+pub fn parse_range(
+    spec: &str,
+    now: DateTime<Local>,
+) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let spec = spec.trim().to_lowercase();
+    let today = now.date_naive();
+    let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let month_start = today.with_day(1).unwrap();
+    let next_month = |date: NaiveDate| {
+        date.checked_add_months(Months::new(1))
+            .ok_or_else(|| "date out of range".to_string())
+    };
+
+    let (start, end) = match spec.as_str() {
+        "today" => (today, today + Duration::days(1)),
+        "tomorrow" => (today + Duration::days(1), today + Duration::days(2)),
+        "yesterday" => (today - Duration::days(1), today),
+        "week" | "this week" => (week_start, week_start + Duration::weeks(1)),
+        "next week" => (
+            week_start + Duration::weeks(1),
+            week_start + Duration::weeks(2),
+        ),
+        "month" | "this month" => (month_start, next_month(month_start)?),
+        "next month" => {
+            let start = next_month(month_start)?;
+            (start, next_month(start)?)
+        }
+        _ => {
+            let duration = spec
+                .strip_prefix("next")
+                .map(|duration| parse_duration(duration.trim()))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown range '{}', expected today, tomorrow, week, month or next <duration>",
+                        spec
+                    )
+                })??;
+            if duration <= Duration::zero() {
+                return Err("the end of the range must be after its start".to_string());
+            }
+            return Ok((now, now + duration));
+        }
+    };
+    Ok((start_of_day(start)?, start_of_day(end)?))
+}
+
+// Resolves `--from`/`--to` bounds, a date without time for `to` includes that whole day
+pub fn parse_bounds(
+    from: Option<&str>,
+    to: Option<&str>,
+    now: DateTime<Local>,
+) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let start = match from {
+        Some(from) => parse_datetime(from, now, None)?,
+        None => start_of_day(now.date_naive())?,
+    };
+    let end = match to {
+        Some(to) => match parse_day(to, now.date_naive()) {
+            Some(date) => start_of_day(date + Duration::days(1))?,
+            None => parse_datetime(to, now, None)?,
+        },
+        None => start + Duration::days(365),
+    };
+    if end <= start {
+        return Err("the end of the range must be after its start".to_string());
+    }
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> DateTime<Local> {
+        datetime_from_str(input, None).unwrap()
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration(" 2w1d "), Ok(Duration::days(15)));
+        assert_eq!(parse_duration("0m"), Ok(Duration::zero()));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("30m1h").is_err());
+        assert!(parse_duration("90s").is_err());
+        assert_eq!(
+            parse_duration("99999999999999w"),
+            Err("duration too large".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_named_ranges() {
+        // A Wednesday afternoon
+        let now = at("2026-10-21 15:30");
+        let range = |spec| parse_range(spec, now).unwrap();
+        assert_eq!(range("today"), (at("2026-10-21"), at("2026-10-22")));
+        assert_eq!(range("Yesterday"), (at("2026-10-20"), at("2026-10-21")));
+        assert_eq!(range("week"), (at("2026-10-19"), at("2026-10-26")));
+        assert_eq!(range("next week"), (at("2026-10-26"), at("2026-11-02")));
+        assert_eq!(range("this month"), (at("2026-10-01"), at("2026-11-01")));
+        assert_eq!(range("next month"), (at("2026-11-01"), at("2026-12-01")));
+        assert_eq!(range("next 2d"), (now, at("2026-10-23 15:30")));
+    }
+
+    #[test]
+    fn rejects_bad_ranges() {
+        let now = at("2026-10-21 15:30");
+        assert!(parse_range("someday", now)
+            .unwrap_err()
+            .starts_with("unknown range 'someday'"));
+        assert!(parse_range("next fortnight", now).is_err());
+        assert_eq!(
+            parse_range("next 0d", now),
+            Err("the end of the range must be after its start".to_string())
+        );
+    }
+
+    #[test]
+    fn bounds_include_the_whole_last_day() {
+        let now = at("2026-10-21 15:30");
+        assert_eq!(
+            parse_bounds(Some("2026-10-01"), Some("2026-10-31"), now),
+            Ok((at("2026-10-01"), at("2026-11-01")))
+        );
+        assert_eq!(
+            parse_bounds(None, Some("2026-10-22 09:00"), now),
+            Ok((at("2026-10-21"), at("2026-10-22 09:00")))
+        );
+        assert_eq!(
+            parse_bounds(Some("2026-10-21 12:00"), None, now),
+            Ok((at("2026-10-21 12:00"), at("2027-10-21 12:00")))
+        );
+        assert!(parse_bounds(Some("2026-10-21"), Some("2026-10-20"), now).is_err());
+    }
+
+    #[test]
+    fn splits_command_lines() {
+        let split = |line| split_command_line(line).unwrap();
+        assert_eq!(
+            split("edit #3 --set 'title=Team lunch'"),
+            ["edit", "#3", "--set", "title=Team lunch"]
+        );
+        assert_eq!(
+            split(r#"add [title: "a # b"] -t x#y"#),
+            ["add", "[title:", "a # b]", "-t", "x#y"]
+        );
+        assert_eq!(split(r"add it\'s"), ["add", "it's"]);
+        assert_eq!(split_command_line("add 'open"), None);
+    }
+
+    #[test]
+    fn reads_time_zones() {
+        assert_eq!(parse_time_zone(""), Ok(None));
+        assert_eq!(parse_time_zone(" Local "), Ok(None));
+        assert_eq!(
+            parse_time_zone("America/New_York"),
+            Ok(Some(chrono_tz::America::New_York))
+        );
+        assert!(parse_time_zone("Mars/Olympus")
+            .unwrap_err()
+            .contains("unknown time zone 'Mars/Olympus'"));
+    }
+
+    #[test]
+    fn zoned_times_keep_their_wall_clock() {
+        let zone = Some(chrono_tz::Europe::Berlin);
+        let naive = |input: &str| NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap();
+        // Summer and winter time give different offsets for the same wall-clock hour
+        let summer = zoned_from_naive(naive("2026-10-24 10:00"), zone).unwrap();
+        let winter = zoned_from_naive(naive("2026-10-26 10:00"), zone).unwrap();
+        assert_eq!(winter - summer, Duration::hours(49));
+        assert_eq!(wall_time(winter, zone), naive("2026-10-26 10:00"));
+        assert_eq!(datetime_from_str("2026-10-24 10:00", zone), Ok(summer));
+        // 02:30 is skipped when Berlin springs forward
+        assert!(zoned_from_naive(naive("2026-03-29 02:30"), zone)
+            .unwrap_err()
+            .contains("does not exist in Europe/Berlin"));
+    }
 }
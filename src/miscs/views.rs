@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::events::query::{Query, QueryOptions};
+use crate::miscs::arg_parsing::did_you_mean;
+
+// Subcommands of `view` that would hide views of the same name
+const RESERVED: [&str; 3] = ["save", "list", "remove"];
+
+// A named search saved with `view save`, the query is parsed again on every use so ranges
+// like `range:week` follow the current date
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct View {
+    pub query: String,
+    pub ignore_case: bool,
+    pub regex: bool,
+    pub sort: Option<String>, // Search field to sort by, see SearchType::NAMES
+}
+
+impl View {
+    pub fn options(&self) -> QueryOptions {
+        QueryOptions {
+            ignore_case: self.ignore_case,
+            regex: self.regex,
+        }
+    }
+
+    pub fn parse(&self) -> Result<Query, String> {
+        Query::parse(&self.query, self.options())
+    }
+}
+
+// Saved views by name, persisted as views.json next to categories.txt
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Views(BTreeMap<String, View>);
+
+impl Views {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("views.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        let path = Self::path(data_dir);
+        let json_string = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json_string).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &View)> {
+        self.0.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&View, String> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        self.0
+            .get(name)
+            .ok_or_else(|| match did_you_mean(name, &self.names()) {
+                Some(suggestion) => format!("no view '{}', did you mean '{}'?", name, suggestion),
+                None => format!(
+                    "no view '{}', save one with `view save {} <query>`",
+                    name, name
+                ),
+            })
+    }
+
+    // Adds or replaces a view after checking that its query parses
+    pub fn insert(&mut self, name: &str, view: View) -> Result<(), String> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "'{}' is not a view name, use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+        if RESERVED.contains(&name) {
+            return Err(format!("'{}' is a view command and can't be a name", name));
+        }
+        view.parse()?;
+        self.0.insert(name.to_string(), view);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<View, String> {
+        self.get(name)?;
+        let name = name.strip_prefix('@').unwrap_or(name);
+        Ok(self.0.remove(name).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(query: &str) -> View {
+        View {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn names_views_and_finds_them_again() {
+        let mut views = Views::default();
+        views.insert("work", view("category:Work")).unwrap();
+        views
+            .insert("next-week", view("range:\"next week\""))
+            .unwrap();
+        assert_eq!(views.names(), ["next-week", "work"]);
+        assert_eq!(views.get("@work").unwrap().query, "category:Work");
+        assert_eq!(
+            views.get("wrok").unwrap_err(),
+            "no view 'wrok', did you mean 'work'?"
+        );
+        assert_eq!(
+            views.get("home").unwrap_err(),
+            "no view 'home', save one with `view save home <query>`"
+        );
+        assert_eq!(views.remove("@work").unwrap().query, "category:Work");
+        assert_eq!(views.names(), ["next-week"]);
+    }
+
+    #[test]
+    fn rejects_bad_names_and_queries() {
+        let mut views = Views::default();
+        assert_eq!(
+            views.insert("my view", view("lunch")).unwrap_err(),
+            "'my view' is not a view name, use letters, digits, '-' and '_'"
+        );
+        assert_eq!(
+            views.insert("list", view("lunch")).unwrap_err(),
+            "'list' is a view command and can't be a name"
+        );
+        assert!(views.insert("broken", view("lunch OR")).is_err());
+        assert!(views.names().is_empty());
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let data_dir = std::env::temp_dir().join("rustyplanner-views");
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+        assert!(Views::load(&data_dir).unwrap().names().is_empty());
+
+        let mut views = Views::default();
+        let regex = View {
+            regex: true,
+            sort: Some("start".to_string()),
+            ..view("title:^Stand")
+        };
+        views.insert("standups", regex).unwrap();
+        views.save(&data_dir).unwrap();
+        let loaded = Views::load(&data_dir).unwrap();
+        let view = loaded.get("standups").unwrap();
+        assert!(view.regex && !view.ignore_case);
+        assert_eq!(view.sort.as_deref(), Some("start"));
+
+        fs::write(Views::path(&data_dir), "{ not json").unwrap();
+        assert!(Views::load(&data_dir).is_err());
+    }
+}
@@ -0,0 +1,593 @@
+use chrono::{Duration, Local, Months, NaiveDate, NaiveTime};
+use ratatui::{
+    crossterm::event::{self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style as TuiStyle},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::collections::HashSet;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration as StdDuration;
+
+use crate::events::event::Event;
+use crate::events::event_manager::EventManager;
+use crate::events::query::{Query, QueryOptions};
+use crate::events::validation::check_event;
+use crate::miscs::calendar::{month_start, week_start, write_day, write_month, write_week};
+use crate::miscs::render::{answers, marked_title, time_range, Style};
+use crate::miscs::utils::local_from_naive;
+
+// How often the screen is redrawn without input, so changes to the events file show up
+const REFRESH_INTERVAL: StdDuration = StdDuration::from_millis(250);
+
+// Fields that can be edited inline, as (Event::set_field key, label)
+const EDIT_FIELDS: [(&str, &str); 4] = [
+    ("title", "Title"),
+    ("start_time", "Start"),
+    ("location", "Location"),
+    ("categories", "Categories"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    Day,
+    Week,
+    Month,
+}
+
+enum Mode {
+    Normal,
+    Search,
+    Edit { field: usize, buffer: String },
+    ConfirmDelete,
+}
+
+struct Tui {
+    event_manager: Arc<Mutex<EventManager>>,
+    view: View,
+    date: NaiveDate, // Any date inside the shown day, week or month
+    selected: usize, // Selected row of the event list
+    mode: Mode,
+    search: String,
+    status: String,
+    invalid: usize, // Invalid events in the data file, as last reported in the status line
+    quit: bool,
+}
+
+pub fn run(event_manager: Arc<Mutex<EventManager>>) -> io::Result<()> {
+    // Printing to stderr would scribble over the screen, the status line shows it instead
+    event_manager.lock().unwrap().set_report_invalid(false);
+    let mut terminal = ratatui::init();
+    let result = Tui::new(event_manager).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Tui {
+    fn new(event_manager: Arc<Mutex<EventManager>>) -> Self {
+        Self {
+            event_manager,
+            view: View::Week,
+            date: Local::now().date_naive(),
+            selected: 0,
+            mode: Mode::Normal,
+            search: String::new(),
+            status: String::new(),
+            invalid: 0,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            let invalid = self.event_manager.lock().unwrap().invalid_count();
+            if invalid != self.invalid {
+                self.invalid = invalid;
+                if invalid > 0 {
+                    self.status = format!(
+                        "{} invalid events in the data file, run `check` for details",
+                        invalid
+                    );
+                }
+            }
+            let occurrences = self.visible_occurrences();
+            self.selected = self.selected.min(occurrences.len().saturating_sub(1));
+            terminal.draw(|frame| self.draw(frame, &occurrences))?;
+
+            if event::poll(REFRESH_INTERVAL)? {
+                if let TermEvent::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key, &occurrences);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn range(&self) -> (NaiveDate, NaiveDate) {
+        match self.view {
+            View::Day => (self.date, self.date + Duration::days(1)),
+            View::Week => {
+                let first = week_start(self.date);
+                (first, first + Duration::weeks(1))
+            }
+            View::Month => {
+                let first = month_start(self.date);
+                (first, first + Months::new(1))
+            }
+        }
+    }
+
+    fn step(&mut self, forward: bool) {
+        self.date = match (self.view, forward) {
+            (View::Day, true) => self.date + Duration::days(1),
+            (View::Day, false) => self.date - Duration::days(1),
+            (View::Week, true) => self.date + Duration::weeks(1),
+            (View::Week, false) => self.date - Duration::weeks(1),
+            (View::Month, true) => self.date + Months::new(1),
+            (View::Month, false) => self.date - Months::new(1),
+        };
+        self.selected = 0;
+    }
+
+    // Occurrences in the shown period that match the search, read fresh from the manager
+    fn visible_occurrences(&self) -> Vec<(usize, Event)> {
+        let (first, last) = self.range();
+        let to_local = |date: NaiveDate| local_from_naive(date.and_time(NaiveTime::MIN));
+        let (Ok(start), Ok(end)) = (to_local(first), to_local(last)) else {
+            return vec![];
+        };
+        // Half-typed queries like `title:"team` still filter by the plain text
+        let options = QueryOptions {
+            ignore_case: true,
+            regex: false,
+        };
+        let query =
+            Query::parse(&self.search, options).unwrap_or_else(|_| Query::text(&self.search));
+        let manager = self.event_manager.lock().unwrap();
+        let matching: HashSet<usize> = manager
+            .search_event(&query)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        manager
+            .occurrences_between(start, end)
+            .into_iter()
+            .filter(|(index, _)| matching.contains(index))
+            .collect()
+    }
+
+    fn field_value(&self, index: usize, field: usize) -> String {
+        let manager = self.event_manager.lock().unwrap();
+        let Some(event) = manager.get_event(index) else {
+            return String::new();
+        };
+        match EDIT_FIELDS[field].0 {
+            "title" => event.title.clone(),
+            "start_time" => event.start_time.format("%Y-%m-%d %H:%M").to_string(),
+            "location" => event.location.clone(),
+            "categories" => event.categories.join(", "),
+            _ => String::new(),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, occurrences: &[(usize, Event)]) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        let selected = occurrences.get(self.selected).map(|(index, _)| *index);
+
+        match &mut self.mode {
+            Mode::Normal => match key.code {
+                KeyCode::Char('q') => self.quit = true,
+                KeyCode::Esc if !self.search.is_empty() => self.search.clear(),
+                KeyCode::Esc => self.quit = true,
+                KeyCode::Char('d') => self.view = View::Day,
+                KeyCode::Char('w') => self.view = View::Week,
+                KeyCode::Char('m') => self.view = View::Month,
+                KeyCode::Char('t') => {
+                    self.date = Local::now().date_naive();
+                    self.selected = 0;
+                }
+                KeyCode::Left | KeyCode::Char('h') | KeyCode::PageUp => self.step(false),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::PageDown => self.step(true),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < occurrences.len() => {
+                    self.selected += 1;
+                }
+                KeyCode::Char('/') => {
+                    self.mode = Mode::Search;
+                    self.status.clear();
+                }
+                KeyCode::Char('e') | KeyCode::Enter => {
+                    if let Some(index) = selected {
+                        let buffer = self.field_value(index, 0);
+                        self.mode = Mode::Edit { field: 0, buffer };
+                        self.status.clear();
+                    }
+                }
+                KeyCode::Char('x') | KeyCode::Delete if selected.is_some() => {
+                    self.mode = Mode::ConfirmDelete;
+                }
+                _ => {}
+            },
+            Mode::Search => match key.code {
+                KeyCode::Enter => self.mode = Mode::Normal,
+                KeyCode::Esc => {
+                    self.search.clear();
+                    self.mode = Mode::Normal;
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.selected = 0;
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.selected = 0;
+                }
+                _ => {}
+            },
+            Mode::Edit { field, buffer } => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.status = "Edit cancelled".to_string();
+                }
+                KeyCode::Tab | KeyCode::Down | KeyCode::BackTab | KeyCode::Up => {
+                    let next = if matches!(key.code, KeyCode::Tab | KeyCode::Down) {
+                        (*field + 1) % EDIT_FIELDS.len()
+                    } else {
+                        (*field + EDIT_FIELDS.len() - 1) % EDIT_FIELDS.len()
+                    };
+                    if let Some(index) = selected {
+                        let buffer = self.field_value(index, next);
+                        self.mode = Mode::Edit {
+                            field: next,
+                            buffer,
+                        };
+                    }
+                }
+                KeyCode::Backspace => {
+                    buffer.pop();
+                }
+                KeyCode::Char(c) => buffer.push(c),
+                KeyCode::Enter => {
+                    let (key, label) = EDIT_FIELDS[*field];
+                    let value = buffer.clone();
+                    if let Some(index) = selected {
+                        self.status = match self.apply_edit(index, key, &value) {
+                            Ok(()) => format!("{} updated", label),
+                            Err(e) => format!("Error: {}", e),
+                        };
+                    }
+                    self.mode = Mode::Normal;
+                }
+                _ => {}
+            },
+            Mode::ConfirmDelete => {
+                if let (KeyCode::Char('y') | KeyCode::Char('Y'), Some(index)) = (key.code, selected)
+                {
+                    let mut manager = self.event_manager.lock().unwrap();
+                    self.status = match manager.remove_event(index) {
+                        Some(event) => match manager.save_events() {
+                            Ok(()) => format!("Deleted '{}'", event.title),
+                            Err(e) => format!("Error: {}", e),
+                        },
+                        None => "Event no longer exists".to_string(),
+                    };
+                } else {
+                    self.status = "Delete cancelled".to_string();
+                }
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    fn apply_edit(&self, index: usize, key: &str, value: &str) -> Result<(), String> {
+        let mut manager = self.event_manager.lock().unwrap();
+        let mut updated = manager
+            .get_event(index)
+            .ok_or_else(|| "event no longer exists".to_string())?
+            .clone();
+        updated.set_field(key, value)?;
+        check_event(&updated)?;
+        manager.replace_event(index, updated);
+        manager.save_events()
+    }
+
+    fn draw(&self, frame: &mut Frame, occurrences: &[(usize, Event)]) {
+        let [header, body, details, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(8),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [calendar, list] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(body);
+
+        self.draw_header(frame, header);
+        self.draw_calendar(frame, calendar, occurrences);
+        self.draw_list(frame, list, occurrences);
+        self.draw_details(frame, details, occurrences);
+        self.draw_footer(frame, footer);
+
+        if let Mode::ConfirmDelete = self.mode {
+            if let Some((_, event)) = occurrences.get(self.selected) {
+                draw_confirm(frame, &format!("Delete '{}'? (y/n)", event.title));
+            }
+        }
+    }
+
+    fn draw_header(&self, frame: &mut Frame, area: Rect) {
+        let (first, last) = self.range();
+        let period = match self.view {
+            View::Day => first.format("%A, %Y-%m-%d").to_string(),
+            View::Week => format!(
+                "Week {} - {}",
+                first.format("%Y-%m-%d"),
+                (last - Duration::days(1)).format("%Y-%m-%d")
+            ),
+            View::Month => first.format("%B %Y").to_string(),
+        };
+        let tab = |label: &str, view: View| {
+            let style = if self.view == view {
+                TuiStyle::default().add_modifier(Modifier::REVERSED)
+            } else {
+                TuiStyle::default()
+            };
+            Span::styled(format!(" {} ", label), style)
+        };
+        let line = Line::from(vec![
+            Span::styled(
+                "RustyPlanner ",
+                TuiStyle::default().add_modifier(Modifier::BOLD),
+            ),
+            tab("Day", View::Day),
+            tab("Week", View::Week),
+            tab("Month", View::Month),
+            Span::raw(format!("  {}", period)),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    // Reuses the `cal` renderers without colours, sized to the pane
+    fn draw_calendar(&self, frame: &mut Frame, area: Rect, occurrences: &[(usize, Event)]) {
+        let block = Block::default().borders(Borders::ALL).title(" Calendar ");
+        let inner = block.inner(area);
+        let style = Style {
+            color: false,
+            width: Some(inner.width as usize),
+        };
+        let mut buffer = vec![];
+        let _ = match self.view {
+            View::Day => write_day(&mut buffer, occurrences, self.date, &style),
+            View::Week => write_week(&mut buffer, occurrences, self.date, &style),
+            View::Month => write_month(&mut buffer, occurrences, self.date, &style),
+        };
+        let text = String::from_utf8_lossy(&buffer).to_string();
+        frame.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    fn draw_list(&self, frame: &mut Frame, area: Rect, occurrences: &[(usize, Event)]) {
+        let title = if self.search.is_empty() {
+            format!(" Events ({}) ", occurrences.len())
+        } else {
+            format!(
+                " Events ({}) matching '{}' ",
+                occurrences.len(),
+                self.search
+            )
+        };
+        let items: Vec<ListItem> = occurrences
+            .iter()
+            .map(|(_, event)| {
+                let mut spans = vec![
+                    Span::styled(
+                        event.start_time.format("%a %d ").to_string(),
+                        TuiStyle::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(format!("{:<11} ", time_range(event))),
+                    Span::raw(marked_title(event)),
+                ];
+                if !event.location.is_empty() {
+                    spans.push(Span::styled(
+                        format!(" @ {}", event.location),
+                        TuiStyle::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(TuiStyle::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        let mut state = ListState::default();
+        if !occurrences.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect, occurrences: &[(usize, Event)]) {
+        let block = Block::default().borders(Borders::ALL).title(" Details ");
+        let Some((index, event)) = occurrences.get(self.selected) else {
+            frame.render_widget(Paragraph::new("No event selected").block(block), area);
+            return;
+        };
+
+        let mut lines = vec![];
+        for (i, (key, label)) in EDIT_FIELDS.iter().enumerate() {
+            let value = match *key {
+                "title" => event.title.clone(),
+                "start_time" => format!(
+                    "{} {}",
+                    event.start_time.format("%Y-%m-%d"),
+                    time_range(event)
+                ),
+                "location" => event.location.clone(),
+                _ => event.categories.join(", "),
+            };
+            let line = match &self.mode {
+                Mode::Edit { field, buffer } if *field == i => Line::from(vec![
+                    Span::styled(
+                        format!("{:<11} ", label),
+                        TuiStyle::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{}█", buffer),
+                        TuiStyle::default().fg(Color::Yellow),
+                    ),
+                ]),
+                _ => Line::from(format!("{:<11} {}", label, value)),
+            };
+            lines.push(line);
+        }
+        if !event.description.is_empty() {
+            lines.push(Line::from(format!(
+                "{:<11} {}",
+                "Description", event.description
+            )));
+        }
+        lines.push(Line::from(Span::styled(
+            format!(
+                "ID {}, index {}, {} attendee(s){}",
+                event.event_id,
+                index + 1,
+                event.attendees.len(),
+                answers(event)
+                    .map(|answers| format!(" ({})", answers))
+                    .unwrap_or_default()
+            ),
+            TuiStyle::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let text = match &self.mode {
+            Mode::Search => format!("/{}█   Enter: keep filter  Esc: clear", self.search),
+            Mode::Edit { .. } => {
+                "Editing - Enter: save  Tab/Up/Down: other field  Esc: cancel".to_string()
+            }
+            Mode::ConfirmDelete => "y: delete  any other key: cancel".to_string(),
+            Mode::Normal if !self.status.is_empty() => self.status.clone(),
+            Mode::Normal => "d/w/m: view  h/l: previous/next  j/k: select  t: today  /: search  e: edit  x: delete  q: quit".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(text).style(TuiStyle::default().fg(Color::Cyan)),
+            area,
+        );
+    }
+}
+
+fn draw_confirm(frame: &mut Frame, message: &str) {
+    let area = frame.area();
+    let width = (message.chars().count() as u16 + 4).min(area.width);
+    let popup = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(message).block(Block::default().borders(Borders::ALL).title(" Confirm ")),
+        popup,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::event_manager::EventManagerMode;
+    use crate::miscs::utils::datetime_from_str;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn press(tui: &mut Tui, keys: &[KeyCode]) {
+        for code in keys {
+            let occurrences = tui.visible_occurrences();
+            tui.handle_key(KeyEvent::new(*code, KeyModifiers::NONE), &occurrences);
+        }
+    }
+
+    fn type_text(tui: &mut Tui, text: &str) {
+        let keys: Vec<_> = text.chars().map(KeyCode::Char).collect();
+        press(tui, &keys);
+    }
+
+    fn tui(file: &str) -> Tui {
+        let path = std::env::temp_dir().join(file);
+        let _ = std::fs::remove_file(&path);
+        Tui::new(EventManager::new(path, false, EventManagerMode::Active))
+    }
+
+    #[test]
+    fn views_cover_their_period() {
+        let mut tui = tui("rustyplanner-tui-views.json");
+        tui.date = date(10, 21);
+        assert_eq!(tui.range(), (date(10, 19), date(10, 26)));
+        type_text(&mut tui, "m");
+        assert_eq!(tui.range(), (date(10, 1), date(11, 1)));
+        press(&mut tui, &[KeyCode::Right]);
+        assert_eq!(tui.range(), (date(11, 1), date(12, 1)));
+        type_text(&mut tui, "dh");
+        assert_eq!(tui.range(), (date(11, 20), date(11, 21)));
+        type_text(&mut tui, "q");
+        assert!(tui.quit);
+    }
+
+    #[test]
+    fn searches_edits_and_deletes() {
+        let mut tui = tui("rustyplanner-tui-edit.json");
+        for title in ["Lunch", "Dentist"] {
+            tui.event_manager.lock().unwrap().add_event(Event {
+                title: title.to_string(),
+                start_time: datetime_from_str("2026-10-20 12:00", None).unwrap(),
+                end_time: datetime_from_str("2026-10-20 13:00", None).unwrap(),
+                ..Default::default()
+            });
+        }
+        tui.date = date(10, 20);
+        assert_eq!(tui.visible_occurrences().len(), 2);
+
+        type_text(&mut tui, "/lun");
+        press(&mut tui, &[KeyCode::Enter]);
+        let found = tui.visible_occurrences();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.title, "Lunch");
+
+        // Edit the title of the selected event
+        press(&mut tui, &[KeyCode::Char('e')]);
+        press(&mut tui, &[KeyCode::Backspace; 5]);
+        type_text(&mut tui, "Brunch");
+        press(&mut tui, &[KeyCode::Enter]);
+        assert_eq!(tui.status, "Title updated");
+        assert!(tui.visible_occurrences().is_empty());
+
+        press(&mut tui, &[KeyCode::Esc]);
+        type_text(&mut tui, "/brunch");
+        press(&mut tui, &[KeyCode::Enter, KeyCode::Char('x')]);
+        type_text(&mut tui, "y");
+        assert_eq!(tui.status, "Deleted 'Brunch'");
+        let manager = tui.event_manager.lock().unwrap();
+        assert_eq!(manager.events().len(), 1);
+        assert_eq!(manager.events()[0].title, "Dentist");
+    }
+}